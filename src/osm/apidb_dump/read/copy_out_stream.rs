@@ -0,0 +1,106 @@
+use std::io::Read;
+use std::sync::mpsc::{Receiver, sync_channel, SyncSender};
+use std::thread;
+
+use anyhow::anyhow;
+use postgres::{Client, NoTls};
+
+// the size of the chunks read from the database
+const CHUNK_SIZE: usize = 256 * 1024;
+// the number of chunks queued before the reading thread blocks
+const QUEUE_SIZE: usize = 16;
+
+/// The output of a `COPY ... TO STDOUT` query as a [Read] stream
+///
+/// The query runs on a dedicated connection and thread in a read only repeatable read transaction,
+/// optionally in an exported snapshot, so that several streams see the same state of the
+/// database.
+pub(crate) struct CopyOutStream {
+    receiver: Receiver<Result<Vec<u8>, String>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl CopyOutStream {
+    /// Start the query. Returns after the transaction, and the snapshot if provided, are set up
+    pub(crate) fn start(connection_params: &str, snapshot: Option<&str>, query: &str) -> Result<CopyOutStream, anyhow::Error> {
+        let (ready_sender, ready_receiver) = sync_channel::<Result<(), anyhow::Error>>(1);
+        let (sender, receiver) = sync_channel::<Result<Vec<u8>, String>>(QUEUE_SIZE);
+        let connection_params = connection_params.to_string();
+        let snapshot = snapshot.map(|snapshot| snapshot.to_string());
+        let thread_query = query.to_string();
+        thread::Builder::new()
+            .name("copy-out".to_string())
+            .spawn(move || {
+                match Self::begin(&connection_params, snapshot.as_deref()) {
+                    Ok(client) => {
+                        ready_sender.send(Ok(())).ok();
+                        if let Err(e) = Self::copy(client, &thread_query, &sender) {
+                            sender.send(Err(format!("{:?}", e))).ok();
+                        }
+                    }
+                    Err(e) => {
+                        ready_sender.send(Err(e)).ok();
+                    }
+                }
+            })?;
+        ready_receiver.recv()
+            .map_err(|_| anyhow!("Query thread stopped: {}", query))??;
+        Ok(
+            CopyOutStream {
+                receiver,
+                chunk: Vec::new(),
+                position: 0,
+            }
+        )
+    }
+
+    fn begin(connection_params: &str, snapshot: Option<&str>) -> Result<Client, anyhow::Error> {
+        let mut client = Client::connect(connection_params, NoTls)?;
+        client.batch_execute("BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY")?;
+        if let Some(snapshot) = snapshot {
+            client.batch_execute(&format!("SET TRANSACTION SNAPSHOT '{}'", snapshot))?;
+        }
+        Ok(client)
+    }
+
+    fn copy(mut client: Client, query: &str, sender: &SyncSender<Result<Vec<u8>, String>>) -> Result<(), anyhow::Error> {
+        let mut reader = client.copy_out(query)?;
+        loop {
+            let mut chunk = vec![0_u8; CHUNK_SIZE];
+            let size = reader.read(&mut chunk)?;
+            if size == 0 {
+                break;
+            }
+            chunk.truncate(size);
+            if sender.send(Ok(chunk)).is_err() {
+                // the stream was dropped
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Read for CopyOutStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.position == self.chunk.len() {
+            match self.receiver.recv() {
+                Ok(Ok(chunk)) => {
+                    self.chunk = chunk;
+                    self.position = 0;
+                }
+                Ok(Err(e)) => {
+                    return Err(std::io::Error::other(e));
+                }
+                Err(_) => {
+                    return Ok(0);
+                }
+            }
+        }
+        let size = buf.len().min(self.chunk.len() - self.position);
+        buf[..size].copy_from_slice(&self.chunk[self.position..self.position + size]);
+        self.position += size;
+        Ok(size)
+    }
+}
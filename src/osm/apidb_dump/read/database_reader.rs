@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use postgres::{Client, NoTls};
+
+use crate::osm::apidb_dump::read::element_iterator::ElementIterator;
+use crate::osm::apidb_dump::read::reader_options::ReaderOptions;
+use crate::osm::apidb_dump::read::table_def::TableDef;
+use crate::osm::apidb_dump::read::table_set::TableSet;
+
+/// Reader of the apidb schema from a live Postgresql database
+///
+/// Produces the same elements as [Reader](crate::osm::apidb_dump::read::reader::Reader) without a
+/// pg_dump directory and without sorting. Each table is read with an ordered
+/// `COPY (SELECT ...) TO STDOUT` query on its own connection. All queries run in a snapshot
+/// exported when the reader is created, so the elements reflect a single consistent state of the
+/// database even if it is modified during the iteration.
+///
+/// Example:
+/// ```
+/// use osm_io::osm::apidb_dump::read::database_reader::DatabaseReader;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let reader = DatabaseReader::new("postgresql://openstreetmap@localhost:5432/openstreetmap")?;
+///     for element in reader.elements()? {
+///         println!("{:?}", element);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct DatabaseReader {
+    // holds the transaction of the exported snapshot open
+    #[allow(dead_code)]
+    client: Client,
+    tables: HashMap<String, TableDef>,
+    table_set: TableSet,
+}
+
+impl DatabaseReader {
+    /// Create a new [DatabaseReader]
+    ///
+    /// * connection_params - connection string either in the key=value format or as a URL, as
+    ///   accepted by [postgres::Config]
+    pub fn new(connection_params: &str) -> Result<DatabaseReader, anyhow::Error> {
+        Self::from_options(connection_params, ReaderOptions::default())
+    }
+
+    /// Create a new [DatabaseReader] with [ReaderOptions]
+    ///
+    /// With [TableSet::Current] only the latest visible version of each element is read from the
+    /// current_* tables.
+    pub fn from_options(connection_params: &str, options: ReaderOptions) -> Result<DatabaseReader, anyhow::Error> {
+        let table_set = options.table_set();
+        let mut client = Client::connect(connection_params, NoTls)?;
+        client.batch_execute("BEGIN ISOLATION LEVEL REPEATABLE READ READ ONLY")?;
+        let snapshot: String = client.query_one("SELECT pg_export_snapshot()", &[])?.get(0);
+
+        let mut tables = HashMap::new();
+        for (name, history_name) in table_set.table_names().into_iter().zip(TableSet::History.table_names()) {
+            let (schema, table) = name.split_once('.')
+                .ok_or(anyhow!("Not a qualified table name: {}", name))?;
+            let fields: Vec<String> = client.query(
+                "SELECT quote_ident(column_name) FROM information_schema.columns \
+                 WHERE table_schema = $1 AND table_name = $2 ORDER BY ordinal_position",
+                &[&schema, &table],
+            )?
+                .iter()
+                .map(|row| row.get(0))
+                .collect();
+            if fields.is_empty() {
+                return Err(anyhow!("Table not found: {}", name));
+            }
+            tables.insert(
+                history_name.to_string(),
+                TableDef::from_database(name.to_string(), fields, connection_params.to_string(), Some(snapshot.clone()))?,
+            );
+        }
+
+        Ok(
+            DatabaseReader {
+                client,
+                tables,
+                table_set,
+            }
+        )
+    }
+
+    /// Create iterator over the elements.
+    ///
+    /// The behaviour is the same as of [Reader::elements](crate::osm::apidb_dump::read::reader::Reader::elements),
+    /// with [Element::Sentinel](crate::osm::model::element::Element::Sentinel) produced after
+    /// completing each type. The reader must not be dropped before this call returns.
+    pub fn elements(&self) -> Result<ElementIterator, anyhow::Error> {
+        ElementIterator::new(self.tables.clone(), self.table_set)
+    }
+}
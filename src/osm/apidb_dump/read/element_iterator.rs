@@ -9,6 +9,7 @@ use crate::osm::apidb_dump::read::relation_relations_reader::{RelationRelationsI
 use crate::osm::apidb_dump::read::table_def::TableDef;
 use crate::osm::apidb_dump::read::table_reader::TableReader;
 use crate::osm::apidb_dump::read::table_record::TableRecord;
+use crate::osm::apidb_dump::read::table_set::TableSet;
 use crate::osm::apidb_dump::read::way_relations_reader::{WayRelationsIterator, WayRelationsReader};
 use crate::osm::model::coordinate::Coordinate;
 use crate::osm::model::element::Element;
@@ -30,13 +31,16 @@ pub struct ElementIterator {
     user_index: BtreeIndex<i64, String>,
     changeset_user_index: BtreeIndex<i64, i64>,
     iteration_state: IterationState,
+    visible_only: bool,
     node_relations_iterator: NodeRelationsIterator,
     way_relations_iterator: WayRelationsIterator,
     relation_relations_iterator: RelationRelationsIterator,
 }
 
 impl ElementIterator {
-    pub(crate) fn new(tables: HashMap<String, TableDef>) -> Result<ElementIterator, anyhow::Error> {
+    /// * tables - table definitions keyed by the name of the history table, also when reading the
+    ///   current tables
+    pub(crate) fn new(tables: HashMap<String, TableDef>, table_set: TableSet) -> Result<ElementIterator, anyhow::Error> {
         let user_index = Self::index_users(&tables)?;
         let changeset_user_index = Self::index_changesets(&tables)?;
         let node_relations_reader = NodeRelationsReader::new(
@@ -64,6 +68,7 @@ impl ElementIterator {
                 user_index,
                 changeset_user_index,
                 iteration_state: IterationState::Start,
+                visible_only: table_set.visible_only(),
                 node_relations_iterator,
                 way_relations_iterator,
                 relation_relations_iterator,
@@ -109,7 +114,8 @@ impl Iterator for ElementIterator {
                 self.next()
             }
             IterationState::Nodes => {
-                let node_relation = self.node_relations_iterator.next();
+                let visible_only = self.visible_only;
+                let node_relation = self.node_relations_iterator.find(|n| !visible_only || n.node().visible());
                 match node_relation {
                     None => {
                         log::info!("Start reading Ways");
@@ -146,7 +152,8 @@ impl Iterator for ElementIterator {
                 }
             }
             IterationState::Ways => {
-                let way_relation = self.way_relations_iterator.next();
+                let visible_only = self.visible_only;
+                let way_relation = self.way_relations_iterator.find(|w| !visible_only || w.way().visible());
                 match way_relation {
                     None => {
                         log::info!("Start reading Relations");
@@ -184,7 +191,8 @@ impl Iterator for ElementIterator {
                 }
             }
             IterationState::Relations => {
-                let relation_relation = self.relation_relations_iterator.next();
+                let visible_only = self.visible_only;
+                let relation_relation = self.relation_relations_iterator.find(|r| !visible_only || r.relation().visible());
                 match relation_relation {
                     None => {
                         self.iteration_state = IterationState::End;
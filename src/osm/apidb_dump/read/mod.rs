@@ -1,4 +1,8 @@
 pub mod reader;
+pub mod database_reader;
+pub mod reader_options;
+pub mod table_set;
+pub mod table_order;
 
 mod table_record;
 mod table_reader;
@@ -22,3 +26,5 @@ mod table_def;
 mod table_fields;
 mod table_pkey;
 mod element_iterator;
+mod table_source;
+mod copy_out_stream;
@@ -62,11 +62,11 @@ impl Iterator for NodeRelationsIterator {
             if let TableRecord::Node { node_record } = node {
                 let mut current_node_tags = Vec::<NodeTagRecord>::new();
                 if let Some(node_tag_record) = self.next_node_tag_record.take() {
-                    if node_tag_record.node_id() == node_record.node_id() && node_tag_record.version() == node_record.version() {
+                    if node_tag_record.node_id() == node_record.node_id() && node_tag_record.version().is_none_or(|version| version == node_record.version()) {
                         current_node_tags.push(node_tag_record);
                         for node_tag in self.node_tags_iterator.by_ref() {
                             if let TableRecord::NodeTag { node_tag_record } = node_tag {
-                                if node_tag_record.node_id() == node_record.node_id() && node_tag_record.version() == node_record.version() {
+                                if node_tag_record.node_id() == node_record.node_id() && node_tag_record.version().is_none_or(|version| version == node_record.version()) {
                                     current_node_tags.push(node_tag_record)
                                 } else {
                                     self.next_node_tag_record = Some(node_tag_record);
@@ -82,7 +82,7 @@ impl Iterator for NodeRelationsIterator {
                 } else {
                     for node_tag in self.node_tags_iterator.by_ref() {
                         if let TableRecord::NodeTag { node_tag_record } = node_tag {
-                            if node_tag_record.node_id() == node_record.node_id() && node_tag_record.version() == node_record.version() {
+                            if node_tag_record.node_id() == node_record.node_id() && node_tag_record.version().is_none_or(|version| version == node_record.version()) {
                                 current_node_tags.push(node_tag_record)
                             } else {
                                 self.next_node_tag_record = Some(node_tag_record);
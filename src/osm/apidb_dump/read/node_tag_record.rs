@@ -1,7 +1,7 @@
 #[derive(Debug)]
 pub(crate) struct NodeTagRecord {
     node_id: i64,
-    version: i64,
+    version: Option<i64>,
     k: String,
     v: String,
 }
@@ -9,7 +9,7 @@ pub(crate) struct NodeTagRecord {
 impl NodeTagRecord {
     pub(crate) fn new(
         node_id: i64,
-        version: i64,
+        version: Option<i64>,
         k: String,
         v: String,
     ) -> NodeTagRecord {
@@ -25,7 +25,7 @@ impl NodeTagRecord {
         self.node_id
     }
 
-    pub(crate) fn version(&self) -> i64 {
+    pub(crate) fn version(&self) -> Option<i64> {
         self.version
     }
 
@@ -1,19 +1,25 @@
 use std::collections::HashMap;
 use std::fs;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::ops::{AddAssign, SubAssign};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use anyhow::{anyhow, Context};
+use flate2::read::MultiGzDecoder;
 
 use regex::Regex;
 use text_file_sort::sort::Sort;
 
 use crate::osm::apidb_dump::read::element_iterator::ElementIterator;
+use crate::osm::apidb_dump::read::reader_options::ReaderOptions;
 use crate::osm::apidb_dump::read::table_def::TableDef;
-use crate::osm::apidb_dump::read::table_fields::TableFields;
+use crate::osm::apidb_dump::read::table_order::TableOrder;
+use crate::osm::apidb_dump::read::table_set::TableSet;
 
 /// Reader of apidb schema dump produced by pg_dump
 pub struct Reader {
     tables: HashMap<String, TableDef>,
+    table_set: TableSet,
 }
 
 impl Reader {
@@ -24,12 +30,46 @@ impl Reader {
     /// ```bash
     ///  pg_dump --host localhost --port 5432 --username openstreetmap --no-password --file /result --format d -d openstreetmap --compress 0 --table public.nodes --table public.node_tags --table public.ways --table public.way_nodes --table public.way_tags --table public.relations --table public.relation_members --table public.relation_tags --table public.changesets --table public.users
     /// ```
-    ///   The input is sorted using primary keys for each table found in input_path/toc.dat which may
-    ///   take significant time depending on the size of the input
+    ///   Dumps compressed with gzip, that is without `--compress 0`, are read as well, at the cost of
+    ///   decompressing the table data into tmp_path.
+    ///   The order of each table found in input_path/toc.dat is verified and the tables that are
+    ///   not ordered are sorted using their primary keys, which may take significant time depending
+    ///   on the size of the input. See [TableOrder]
     /// * tmp_path - location used by the sorting algorithm for intermediate and final result. Should
     ///   have space for at least 2.2 * input size
     pub fn new(input_path: PathBuf, tmp_path: PathBuf) -> Result<Reader, anyhow::Error> {
+        Self::from_options(input_path, tmp_path, ReaderOptions::default())
+    }
+
+    /// Create a new [Reader] with [ReaderOptions]
+    ///
+    /// With [TableSet::Current] the dump must include the current_* tables instead of, or in
+    /// addition to, the history tables, and only the latest visible version of each element is
+    /// produced.
+    ///
+    /// Example:
+    /// ```
+    /// use std::path::PathBuf;
+    /// use osm_io::osm::apidb_dump::read::reader::Reader;
+    /// use osm_io::osm::apidb_dump::read::reader_options::ReaderOptions;
+    /// use osm_io::osm::apidb_dump::read::table_set::TableSet;
+    /// fn example() -> Result<(), anyhow::Error> {
+    ///     let mut options = ReaderOptions::default();
+    ///     options.with_table_set(TableSet::Current);
+    ///     let reader = Reader::from_options(
+    ///         PathBuf::from("./planet-dump"),
+    ///         PathBuf::from("./planet-dump-tmp"),
+    ///         options,
+    ///     )?;
+    ///     for element in reader.elements()? {
+    ///         println!("{:?}", element);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_options(input_path: PathBuf, tmp_path: PathBuf, options: ReaderOptions) -> Result<Reader, anyhow::Error> {
         let mut tables: HashMap<String, TableDef> = HashMap::new();
+        let table_set = options.table_set();
 
         let toc_path = input_path.join("toc.dat");
         let toc = fs::read(&toc_path)
@@ -38,15 +78,21 @@ impl Reader {
         // COPY public.node_tags (node_id, version, k, v) FROM stdin
         let re = Regex::new("^([^ ]+) \\((.+)\\)$").unwrap();
         for raw_table_def in raw_table_defs {
-            let table_data_path = input_path.join(&raw_table_def.1);
             let captures = re.captures(&raw_table_def.0).unwrap();
             let name = captures.get(1).unwrap().as_str();
-            if !TableFields::is_of_interest(name) {
-                continue;
-            }
+            let history_name = match table_set.history_table_name(name) {
+                None => {
+                    continue;
+                }
+                Some(history_name) => {
+                    history_name
+                }
+            };
+            let table_data_path = Self::table_data_path(&input_path, &tmp_path, &raw_table_def.1)?;
             let fields: Vec<&str> = captures.get(2).unwrap().as_str().split(", ").collect();
+            // the current tables are keyed by the name of the history table they stand for
             tables.insert(
-                name.to_string(),
+                history_name.to_string(),
                 TableDef::new(
                     name.to_string(),
                     table_data_path,
@@ -59,29 +105,137 @@ impl Reader {
             );
         }
 
-        Self::sort_tables(&tables)?;
+        Self::order_tables(&mut tables, options.table_order(), options.resume())?;
 
         Ok(
             Reader {
                 tables,
+                table_set,
             }
         )
     }
 
-    fn sort_tables(tables: &HashMap<String, TableDef>) -> Result<(), anyhow::Error> {
+    /// The path of the plain table data file. Table data compressed by pg_dump as NNNN.dat.gz is
+    /// decompressed into tmp_path first, because the sort requires plain input
+    fn table_data_path(input_path: &Path, tmp_path: &Path, file_name: &str) -> Result<PathBuf, anyhow::Error> {
+        let path = input_path.join(file_name);
+        let compressed_path = input_path.join(format!("{}.gz", file_name));
+        if path.exists() || !compressed_path.exists() {
+            return Ok(path);
+        }
+
+        log::info!("Decompress {} table data", compressed_path.display());
+        fs::create_dir_all(tmp_path)?;
+        let decompressed_path = tmp_path.join(file_name);
+        let compressed_file = File::open(&compressed_path)
+            .with_context(|| anyhow!("path: {}", compressed_path.display()))?;
+        let decompressed_file = File::create(&decompressed_path)
+            .with_context(|| anyhow!("path: {}", decompressed_path.display()))?;
+        let mut writer = BufWriter::new(decompressed_file);
+        std::io::copy(&mut MultiGzDecoder::new(BufReader::new(compressed_file)), &mut writer)
+            .with_context(|| anyhow!("Failed to decompress {}", compressed_path.display()))?;
+        writer.flush()?;
+        Ok(decompressed_path)
+    }
+
+    fn order_tables(tables: &mut HashMap<String, TableDef>, table_order: TableOrder, resume: bool) -> Result<(), anyhow::Error> {
+        for (table_name, table_def) in tables.iter_mut() {
+            match table_order {
+                TableOrder::Sort => {
+                    Self::sort_table(table_name, table_def, resume)?;
+                }
+                TableOrder::Verify => {
+                    if resume && Self::is_sorted(table_def)? {
+                        log::info!("Reuse sorted {} table data", table_name);
+                    } else if Self::is_ordered(table_def)? {
+                        log::info!("{} table data is ordered, skip sort", table_name);
+                        table_def.with_sorted_path(table_def.path());
+                    } else {
+                        Self::sort_table(table_name, table_def, resume)?;
+                    }
+                }
+                TableOrder::Trust => {
+                    table_def.with_sorted_path(table_def.path());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Verify that the table data is ordered by the fields the merge joins depend on
+    fn is_ordered(table_def: &TableDef) -> Result<bool, anyhow::Error> {
+        log::info!("Verify {} table data order", table_def.name());
+        let pkey = table_def.pkey();
+        let file = File::open(table_def.path())
+            .with_context(|| anyhow!("path: {}", table_def.path().display()))?;
+        let mut previous = None;
+        for (i, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.starts_with("\\.") {
+                break;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            let key = pkey.merge_key_values(&line)
+                .with_context(|| anyhow!("{}:{}", table_def.path().display(), i + 1))?;
+            if let Some(previous) = &previous {
+                if &key < previous {
+                    log::info!("{} table data is not ordered at line {}", table_def.name(), i + 1);
+                    return Ok(false);
+                }
+            }
+            previous.replace(key);
+        }
+        Ok(true)
+    }
+
+    /// The marker of a completed sort, that records the size of the table data that was sorted
+    fn sort_marker_path(table_def: &TableDef) -> PathBuf {
+        PathBuf::from(format!("{}.done", table_def.sorted_path().display()))
+    }
+
+    fn sort_marker(table_def: &TableDef) -> Result<String, anyhow::Error> {
+        let len = fs::metadata(table_def.path())
+            .with_context(|| anyhow!("path: {}", table_def.path().display()))?
+            .len();
+        Ok(format!("{}\t{}\n", table_def.path().display(), len))
+    }
+
+    /// True if a previous sort of the table data into tmp_path completed
+    fn is_sorted(table_def: &TableDef) -> Result<bool, anyhow::Error> {
+        match fs::read_to_string(Self::sort_marker_path(table_def)) {
+            Ok(marker) => {
+                Ok(table_def.sorted_path().is_file() && marker == Self::sort_marker(table_def)?)
+            }
+            Err(_) => {
+                Ok(false)
+            }
+        }
+    }
+
+    fn sort_table(table_name: &str, table_def: &TableDef, resume: bool) -> Result<(), anyhow::Error> {
+        if resume && Self::is_sorted(table_def)? {
+            log::info!("Reuse sorted {} table data", table_name);
+            return Ok(());
+        }
+        log::info!("Sort {} table data", table_name);
         let ignore_regex = Regex::new("^\\\\\\.$")?;
-        for (table_name, table_def) in tables {
-            log::info!("Sort {} table data", table_name);
-            fs::create_dir_all(table_def.tmp_path())?;
-            let mut text_file = Sort::new(vec![table_def.path()], table_def.sorted_path());
-            text_file.with_tmp_dir(table_def.tmp_path());
-            text_file.with_intermediate_files(8192);
-            text_file.with_tasks(num_cpus::get());
-            text_file.with_fields(table_def.pkey().key());
-            text_file.with_ignore_empty();
-            text_file.with_ignore_lines(ignore_regex.clone());
-            text_file.sort()?;
+        fs::create_dir_all(table_def.tmp_path())?;
+        let marker_path = Self::sort_marker_path(table_def);
+        if marker_path.exists() {
+            fs::remove_file(&marker_path)?;
         }
+        let mut text_file = Sort::new(vec![table_def.path()], table_def.sorted_path());
+        text_file.with_tmp_dir(table_def.tmp_path());
+        text_file.with_intermediate_files(8192);
+        text_file.with_tasks(num_cpus::get());
+        text_file.with_fields(table_def.pkey().key());
+        text_file.with_ignore_empty();
+        text_file.with_ignore_lines(ignore_regex);
+        text_file.sort()?;
+        fs::write(&marker_path, Self::sort_marker(table_def)?)
+            .with_context(|| anyhow!("path: {}", marker_path.display()))?;
         Ok(())
     }
 
@@ -139,7 +293,7 @@ impl Reader {
     /// distinction that [Element::Sentinel] is produced after completing each type, that is Node,
     /// Way, Relation.
     pub fn elements(&self) -> Result<ElementIterator, anyhow::Error> {
-        ElementIterator::new(self.tables.clone())
+        ElementIterator::new(self.tables.clone(), self.table_set)
     }
 }
 
@@ -0,0 +1,69 @@
+use crate::osm::apidb_dump::read::table_order::TableOrder;
+use crate::osm::apidb_dump::read::table_set::TableSet;
+
+/// Options for [Reader](crate::osm::apidb_dump::read::reader::Reader) and
+/// [DatabaseReader](crate::osm::apidb_dump::read::database_reader::DatabaseReader)
+///
+/// Example:
+/// ```
+/// use osm_io::osm::apidb_dump::read::reader_options::ReaderOptions;
+/// use osm_io::osm::apidb_dump::read::table_order::TableOrder;
+/// use osm_io::osm::apidb_dump::read::table_set::TableSet;
+/// fn example() {
+///     let mut options = ReaderOptions::default();
+///     options.with_table_set(TableSet::Current);
+///     options.with_table_order(TableOrder::Trust);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ReaderOptions {
+    table_set: TableSet,
+    table_order: TableOrder,
+    resume: bool,
+}
+
+impl ReaderOptions {
+    /// Get the set of tables the elements are read from
+    pub fn table_set(&self) -> TableSet {
+        self.table_set
+    }
+
+    /// Set the set of tables the elements are read from
+    pub fn with_table_set(&mut self, table_set: TableSet) {
+        self.table_set = table_set;
+    }
+
+    /// Get how the order of the table data is established
+    pub fn table_order(&self) -> TableOrder {
+        self.table_order
+    }
+
+    /// Set how the order of the table data is established. Applies only to dumps read from files,
+    /// the queries of [DatabaseReader](crate::osm::apidb_dump::read::database_reader::DatabaseReader)
+    /// always order the tables
+    pub fn with_table_order(&mut self, table_order: TableOrder) {
+        self.table_order = table_order;
+    }
+
+    /// Get whether the tables sorted by a previous run are reused
+    pub fn resume(&self) -> bool {
+        self.resume
+    }
+
+    /// Set whether the tables sorted by a previous run into the same tmp_path are reused instead
+    /// of being sorted again. A sorted table is reused only if its sort completed and the table
+    /// data did not change in size since
+    pub fn with_resume(&mut self, resume: bool) {
+        self.resume = resume;
+    }
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        ReaderOptions {
+            table_set: TableSet::History,
+            table_order: TableOrder::Verify,
+            resume: false,
+        }
+    }
+}
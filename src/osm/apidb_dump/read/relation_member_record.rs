@@ -43,7 +43,7 @@ pub(crate) struct RelationMemberRecord {
     member_type: RelationMemberType,
     member_id: i64,
     member_role: String,
-    version: i64,
+    version: Option<i64>,
     sequence_id: i64,
 }
 
@@ -53,7 +53,7 @@ impl RelationMemberRecord {
         member_type: RelationMemberType,
         member_id: i64,
         member_role: String,
-        version: i64,
+        version: Option<i64>,
         sequence_id: i64,
     ) -> RelationMemberRecord {
         RelationMemberRecord {
@@ -87,7 +87,7 @@ impl RelationMemberRecord {
         std::mem::take(&mut self.member_role)
     }
 
-    pub(crate) fn version(&self) -> i64 {
+    pub(crate) fn version(&self) -> Option<i64> {
         self.version
     }
 
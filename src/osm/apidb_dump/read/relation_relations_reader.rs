@@ -77,11 +77,11 @@ impl Iterator for RelationRelationsIterator {
             if let TableRecord::Relation { relation_record } = relation {
                 let mut current_relation_tags = Vec::<RelationTagRecord>::new();
                 if let Some(relation_tag_record) = self.next_relation_tag_record.take() {
-                    if relation_tag_record.relation_id() == relation_record.relation_id() && relation_tag_record.version() == relation_record.version() {
+                    if relation_tag_record.relation_id() == relation_record.relation_id() && relation_tag_record.version().is_none_or(|version| version == relation_record.version()) {
                         current_relation_tags.push(relation_tag_record);
                         for relation_tag in self.relation_tags_iterator.by_ref() {
                             if let TableRecord::RelationTag { relation_tag_record } = relation_tag {
-                                if relation_tag_record.relation_id() == relation_record.relation_id() && relation_tag_record.version() == relation_record.version() {
+                                if relation_tag_record.relation_id() == relation_record.relation_id() && relation_tag_record.version().is_none_or(|version| version == relation_record.version()) {
                                     current_relation_tags.push(relation_tag_record)
                                 } else {
                                     self.next_relation_tag_record = Some(relation_tag_record);
@@ -97,7 +97,7 @@ impl Iterator for RelationRelationsIterator {
                 } else {
                     for relation_tag in self.relation_tags_iterator.by_ref() {
                         if let TableRecord::RelationTag { relation_tag_record } = relation_tag {
-                            if relation_tag_record.relation_id() == relation_record.relation_id() && relation_tag_record.version() == relation_record.version() {
+                            if relation_tag_record.relation_id() == relation_record.relation_id() && relation_tag_record.version().is_none_or(|version| version == relation_record.version()) {
                                 current_relation_tags.push(relation_tag_record)
                             } else {
                                 self.next_relation_tag_record = Some(relation_tag_record);
@@ -111,11 +111,11 @@ impl Iterator for RelationRelationsIterator {
 
                 let mut current_relation_members = Vec::<RelationMemberRecord>::new();
                 if let Some(relation_member_record) = self.next_relation_member_record.take() {
-                    if relation_member_record.relation_id() == relation_record.relation_id() && relation_member_record.version() == relation_record.version() {
+                    if relation_member_record.relation_id() == relation_record.relation_id() && relation_member_record.version().is_none_or(|version| version == relation_record.version()) {
                         current_relation_members.push(relation_member_record);
                         for relation_member in self.relation_members_iterator.by_ref() {
                             if let TableRecord::RelationMember { relation_member_record } = relation_member {
-                                if relation_member_record.relation_id() == relation_record.relation_id() && relation_member_record.version() == relation_record.version() {
+                                if relation_member_record.relation_id() == relation_record.relation_id() && relation_member_record.version().is_none_or(|version| version == relation_record.version()) {
                                     current_relation_members.push(relation_member_record)
                                 } else {
                                     self.next_relation_member_record = Some(relation_member_record);
@@ -131,7 +131,7 @@ impl Iterator for RelationRelationsIterator {
                 } else {
                     for relation_member in self.relation_members_iterator.by_ref() {
                         if let TableRecord::RelationMember { relation_member_record } = relation_member {
-                            if relation_member_record.relation_id() == relation_record.relation_id() && relation_member_record.version() == relation_record.version() {
+                            if relation_member_record.relation_id() == relation_record.relation_id() && relation_member_record.version().is_none_or(|version| version == relation_record.version()) {
                                 current_relation_members.push(relation_member_record)
                             } else {
                                 self.next_relation_member_record = Some(relation_member_record);
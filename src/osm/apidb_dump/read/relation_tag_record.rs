@@ -1,7 +1,7 @@
 #[derive(Debug)]
 pub(crate) struct RelationTagRecord {
     relation_id: i64,
-    version: i64,
+    version: Option<i64>,
     k: String,
     v: String,
 }
@@ -9,7 +9,7 @@ pub(crate) struct RelationTagRecord {
 impl RelationTagRecord {
     pub(crate) fn new(
         relation_id: i64,
-        version: i64,
+        version: Option<i64>,
         k: String,
         v: String,
     ) -> RelationTagRecord {
@@ -25,7 +25,7 @@ impl RelationTagRecord {
         self.relation_id
     }
 
-    pub(crate) fn version(&self) -> i64 {
+    pub(crate) fn version(&self) -> Option<i64> {
         self.version
     }
 
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 
 use crate::osm::apidb_dump::read::table_fields::TableFields;
 use crate::osm::apidb_dump::read::table_pkey::TablePkey;
+use crate::osm::apidb_dump::read::table_source::TableSource;
 
 #[derive(Debug, Clone)]
 pub(crate) struct TableDef {
@@ -11,6 +12,7 @@ pub(crate) struct TableDef {
     tmp_path: PathBuf,
     fields: TableFields,
     pkey: TablePkey,
+    source: TableSource,
 }
 
 impl TableDef {
@@ -27,6 +29,29 @@ impl TableDef {
             tmp_path,
             fields: TableFields::new(name.clone(), fields.clone())?,
             pkey: TablePkey::new(name.clone(), fields.clone())?,
+            source: TableSource::File,
+        };
+        Ok(table_def)
+    }
+
+    /// Create a definition of a table read with a query from a live database
+    ///
+    /// * fields - the quoted column names of the table
+    pub(crate) fn from_database(name: String, fields: Vec<String>, connection_params: String, snapshot: Option<String>) -> Result<TableDef, anyhow::Error> {
+        let pkey = TablePkey::new(name.clone(), fields.clone())?;
+        let query = format!("COPY (SELECT {} FROM {} ORDER BY {}) TO STDOUT", fields.join(", "), name, pkey.order_by());
+        let table_def = TableDef {
+            name: name.clone(),
+            path: PathBuf::new(),
+            sorted_path: PathBuf::new(),
+            tmp_path: PathBuf::new(),
+            fields: TableFields::new(name.clone(), fields.clone())?,
+            pkey,
+            source: TableSource::Database {
+                connection_params,
+                snapshot,
+                query,
+            },
         };
         Ok(table_def)
     }
@@ -43,6 +68,12 @@ impl TableDef {
         self.sorted_path.clone()
     }
 
+    /// Read the table from sorted_path instead of the sorted copy in tmp_path, for example the
+    /// input itself when it is already ordered
+    pub(crate) fn with_sorted_path(&mut self, sorted_path: PathBuf) {
+        self.sorted_path = sorted_path;
+    }
+
     pub(crate) fn tmp_path(&self) -> PathBuf {
         self.tmp_path.clone()
     }
@@ -59,4 +90,8 @@ impl TableDef {
     pub(crate) fn pkey(&self) -> TablePkey {
         self.pkey.clone()
     }
+
+    pub(crate) fn source(&self) -> &TableSource {
+        &self.source
+    }
 }
@@ -1,5 +1,38 @@
 use anyhow::anyhow;
 
+/// The tables read to produce elements
+pub(crate) const TABLES_OF_INTEREST: [&str; 10] = [
+    "public.nodes",
+    "public.node_tags",
+    "public.ways",
+    "public.way_nodes",
+    "public.way_tags",
+    "public.relations",
+    "public.relation_members",
+    "public.relation_tags",
+    "public.changesets",
+    "public.users",
+];
+
+/// The tables read to produce the latest visible elements, in the order of [TABLES_OF_INTEREST]
+pub(crate) const CURRENT_TABLES_OF_INTEREST: [&str; 10] = [
+    "public.current_nodes",
+    "public.current_node_tags",
+    "public.current_ways",
+    "public.current_way_nodes",
+    "public.current_way_tags",
+    "public.current_relations",
+    "public.current_relation_members",
+    "public.current_relation_tags",
+    "public.changesets",
+    "public.users",
+];
+
+/// Column indices of the fields read from each table, looked up by column name, so the columns
+/// may be in any order and columns not read are ignored. The current_* tables map to the same
+/// variants as their history counterparts; they have no redaction_id and their tags, way nodes
+/// and members carry no version, hence the optional indices. The redaction_id column is optional
+/// in the history tables as well.
 #[derive(Debug, Copy, Clone)]
 pub(crate) enum TableFields {
     Nodes {
@@ -11,11 +44,11 @@ pub(crate) enum TableFields {
         timestamp: usize,
         tile: usize,
         version: usize,
-        redaction_id: usize,
+        redaction_id: Option<usize>,
     },
     NodeTags {
         node_id: usize,
-        version: usize,
+        version: Option<usize>,
         k: usize,
         v: usize,
     },
@@ -25,18 +58,18 @@ pub(crate) enum TableFields {
         timestamp: usize,
         version: usize,
         visible: usize,
-        redaction_id: usize,
+        redaction_id: Option<usize>,
     },
     WayTags {
         way_id: usize,
         k: usize,
         v: usize,
-        version: usize,
+        version: Option<usize>,
     },
     WayNodes {
         way_id: usize,
         node_id: usize,
-        version: usize,
+        version: Option<usize>,
         sequence_id: usize,
     },
     Relations {
@@ -45,20 +78,20 @@ pub(crate) enum TableFields {
         timestamp: usize,
         version: usize,
         visible: usize,
-        redaction_id: usize,
+        redaction_id: Option<usize>,
     },
     RelationTags {
         relation_id: usize,
         k: usize,
         v: usize,
-        version: usize,
+        version: Option<usize>,
     },
     RelationMembers {
         relation_id: usize,
         member_type: usize,
         member_id: usize,
         member_role: usize,
-        version: usize,
+        version: Option<usize>,
         sequence_id: usize,
     },
     Changesets {
@@ -117,21 +150,6 @@ impl TableFields {
         }
     }
 
-    pub(crate) fn is_of_interest(name: &str) -> bool {
-        let tables_of_interest = ["public.nodes",
-            "public.node_tags",
-            "public.ways",
-            "public.way_nodes",
-            "public.way_tags",
-            "public.relations",
-            "public.relation_members",
-            "public.relation_tags",
-            "public.changesets",
-            "public.users"];
-
-        tables_of_interest.contains(&name)
-    }
-
     pub(crate) fn new(name: String, fields: Vec<String>) -> Result<TableFields, anyhow::Error> {
         match name.as_str() {
             "public.nodes" => {
@@ -145,7 +163,7 @@ impl TableFields {
                         timestamp: Self::index("\"timestamp\"", &fields)?,
                         tile: Self::index("tile", &fields)?,
                         version: Self::index("version", &fields)?,
-                        redaction_id: Self::index("redaction_id", &fields)?,
+                        redaction_id: Self::index("redaction_id", &fields).ok(),
                     }
                 )
             }
@@ -153,7 +171,7 @@ impl TableFields {
                 Ok(
                     TableFields::NodeTags {
                         node_id: Self::index("node_id", &fields)?,
-                        version: Self::index("version", &fields)?,
+                        version: Some(Self::index("version", &fields)?),
                         k: Self::index("k", &fields)?,
                         v: Self::index("v", &fields)?,
                     }
@@ -167,7 +185,7 @@ impl TableFields {
                         timestamp: Self::index("\"timestamp\"", &fields)?,
                         version: Self::index("version", &fields)?,
                         visible: Self::index("visible", &fields)?,
-                        redaction_id: Self::index("redaction_id", &fields)?,
+                        redaction_id: Self::index("redaction_id", &fields).ok(),
                     }
                 )
             }
@@ -176,7 +194,7 @@ impl TableFields {
                     TableFields::WayNodes {
                         way_id: Self::index("way_id", &fields)?,
                         node_id: Self::index("node_id", &fields)?,
-                        version: Self::index("version", &fields)?,
+                        version: Some(Self::index("version", &fields)?),
                         sequence_id: Self::index("sequence_id", &fields)?,
                     }
                 )
@@ -187,7 +205,7 @@ impl TableFields {
                         way_id: Self::index("way_id", &fields)?,
                         k: Self::index("k", &fields)?,
                         v: Self::index("v", &fields)?,
-                        version: Self::index("version", &fields)?,
+                        version: Some(Self::index("version", &fields)?),
                     }
                 )
             }
@@ -199,7 +217,7 @@ impl TableFields {
                         timestamp: Self::index("\"timestamp\"", &fields)?,
                         version: Self::index("version", &fields)?,
                         visible: Self::index("visible", &fields)?,
-                        redaction_id: Self::index("redaction_id", &fields)?,
+                        redaction_id: Self::index("redaction_id", &fields).ok(),
                     }
                 )
             }
@@ -210,7 +228,7 @@ impl TableFields {
                         member_type: Self::index("member_type", &fields)?,
                         member_id: Self::index("member_id", &fields)?,
                         member_role: Self::index("member_role", &fields)?,
-                        version: Self::index("version", &fields)?,
+                        version: Some(Self::index("version", &fields)?),
                         sequence_id: Self::index("sequence_id", &fields)?,
                     }
                 )
@@ -221,7 +239,98 @@ impl TableFields {
                         relation_id: Self::index("relation_id", &fields)?,
                         k: Self::index("k", &fields)?,
                         v: Self::index("v", &fields)?,
+                        version: Some(Self::index("version", &fields)?),
+                    }
+                )
+            }
+            "public.current_nodes" => {
+                Ok(
+                    TableFields::Nodes {
+                        node_id: Self::index("id", &fields)?,
+                        latitude: Self::index("latitude", &fields)?,
+                        longitude: Self::index("longitude", &fields)?,
+                        changeset_id: Self::index("changeset_id", &fields)?,
+                        visible: Self::index("visible", &fields)?,
+                        timestamp: Self::index("\"timestamp\"", &fields)?,
+                        tile: Self::index("tile", &fields)?,
                         version: Self::index("version", &fields)?,
+                        redaction_id: None,
+                    }
+                )
+            }
+            "public.current_node_tags" => {
+                Ok(
+                    TableFields::NodeTags {
+                        node_id: Self::index("node_id", &fields)?,
+                        version: None,
+                        k: Self::index("k", &fields)?,
+                        v: Self::index("v", &fields)?,
+                    }
+                )
+            }
+            "public.current_ways" => {
+                Ok(
+                    TableFields::Ways {
+                        way_id: Self::index("id", &fields)?,
+                        changeset_id: Self::index("changeset_id", &fields)?,
+                        timestamp: Self::index("\"timestamp\"", &fields)?,
+                        version: Self::index("version", &fields)?,
+                        visible: Self::index("visible", &fields)?,
+                        redaction_id: None,
+                    }
+                )
+            }
+            "public.current_way_nodes" => {
+                Ok(
+                    TableFields::WayNodes {
+                        way_id: Self::index("way_id", &fields)?,
+                        node_id: Self::index("node_id", &fields)?,
+                        version: None,
+                        sequence_id: Self::index("sequence_id", &fields)?,
+                    }
+                )
+            }
+            "public.current_way_tags" => {
+                Ok(
+                    TableFields::WayTags {
+                        way_id: Self::index("way_id", &fields)?,
+                        k: Self::index("k", &fields)?,
+                        v: Self::index("v", &fields)?,
+                        version: None,
+                    }
+                )
+            }
+            "public.current_relations" => {
+                Ok(
+                    TableFields::Relations {
+                        relation_id: Self::index("id", &fields)?,
+                        changeset_id: Self::index("changeset_id", &fields)?,
+                        timestamp: Self::index("\"timestamp\"", &fields)?,
+                        version: Self::index("version", &fields)?,
+                        visible: Self::index("visible", &fields)?,
+                        redaction_id: None,
+                    }
+                )
+            }
+            "public.current_relation_members" => {
+                Ok(
+                    TableFields::RelationMembers {
+                        relation_id: Self::index("relation_id", &fields)?,
+                        member_type: Self::index("member_type", &fields)?,
+                        member_id: Self::index("member_id", &fields)?,
+                        member_role: Self::index("member_role", &fields)?,
+                        version: None,
+                        sequence_id: Self::index("sequence_id", &fields)?,
+                    }
+                )
+            }
+            "public.current_relation_tags" => {
+                Ok(
+                    TableFields::RelationTags {
+                        relation_id: Self::index("relation_id", &fields)?,
+                        k: Self::index("k", &fields)?,
+                        v: Self::index("v", &fields)?,
+                        version: None,
                     }
                 )
             }
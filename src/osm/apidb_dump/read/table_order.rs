@@ -0,0 +1,14 @@
+/// How [Reader](crate::osm::apidb_dump::read::reader::Reader) establishes the order of the table
+/// data required by the merge joins of the tables
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableOrder {
+    /// Sort every table by its primary key. Takes a long time for a large dump and requires space
+    /// for 2.2 * input size in tmp_path
+    Sort,
+    /// Read each table once to verify its order and sort only the tables that are not ordered.
+    /// The tables written by [Writer](crate::osm::apidb_dump::write::writer::Writer) are ordered
+    Verify,
+    /// Read the tables as they are, without verification. An unordered table produces incomplete
+    /// elements
+    Trust,
+}
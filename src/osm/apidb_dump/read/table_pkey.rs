@@ -1,7 +1,16 @@
+use std::str::FromStr;
+
 use anyhow::anyhow;
 use text_file_sort::field::Field;
 use text_file_sort::field_type::FieldType;
 
+/// The value of a key field of a table data line, compared as by the sort
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum KeyValue {
+    Integer(i64),
+    String(String),
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct TablePkey {
     name: String,
@@ -61,6 +70,44 @@ impl TablePkey {
                 key.push(Field::new(Self::index("version", &fields)?, FieldType::Integer).with_str_name("version"));
                 key.push(Field::new(Self::index("k", &fields)?, FieldType::String).with_str_name("k"));
             }
+            "public.current_nodes" => {
+                // ADD CONSTRAINT current_nodes_pkey1 PRIMARY KEY (id);
+                key.push(Field::new(Self::index("id", &fields)?, FieldType::Integer).with_str_name("id"));
+            }
+            "public.current_node_tags" => {
+                // ADD CONSTRAINT current_node_tags_pkey PRIMARY KEY (node_id, k);
+                key.push(Field::new(Self::index("node_id", &fields)?, FieldType::Integer).with_str_name("node_id"));
+                key.push(Field::new(Self::index("k", &fields)?, FieldType::String).with_str_name("k"));
+            }
+            "public.current_ways" => {
+                // ADD CONSTRAINT current_ways_pkey PRIMARY KEY (id);
+                key.push(Field::new(Self::index("id", &fields)?, FieldType::Integer).with_str_name("id"));
+            }
+            "public.current_way_nodes" => {
+                // ADD CONSTRAINT current_way_nodes_pkey PRIMARY KEY (way_id, sequence_id);
+                key.push(Field::new(Self::index("way_id", &fields)?, FieldType::Integer).with_str_name("way_id"));
+                key.push(Field::new(Self::index("sequence_id", &fields)?, FieldType::Integer).with_str_name("sequence_id"));
+            }
+            "public.current_way_tags" => {
+                // ADD CONSTRAINT current_way_tags_pkey PRIMARY KEY (way_id, k);
+                key.push(Field::new(Self::index("way_id", &fields)?, FieldType::Integer).with_str_name("way_id"));
+                key.push(Field::new(Self::index("k", &fields)?, FieldType::String).with_str_name("k"));
+            }
+            "public.current_relations" => {
+                // ADD CONSTRAINT current_relations_pkey PRIMARY KEY (id);
+                key.push(Field::new(Self::index("id", &fields)?, FieldType::Integer).with_str_name("id"));
+            }
+            "public.current_relation_members" => {
+                // ADD CONSTRAINT current_relation_members_pkey PRIMARY KEY (relation_id, member_type, member_id, member_role, sequence_id);
+                // sorted by (relation_id, sequence_id) instead to keep the members in relation order
+                key.push(Field::new(Self::index("relation_id", &fields)?, FieldType::Integer).with_str_name("relation_id"));
+                key.push(Field::new(Self::index("sequence_id", &fields)?, FieldType::Integer).with_str_name("sequence_id"));
+            }
+            "public.current_relation_tags" => {
+                // ADD CONSTRAINT current_relation_tags_pkey PRIMARY KEY (relation_id, k);
+                key.push(Field::new(Self::index("relation_id", &fields)?, FieldType::Integer).with_str_name("relation_id"));
+                key.push(Field::new(Self::index("k", &fields)?, FieldType::String).with_str_name("k"));
+            }
             "public.changesets" => {
                 // ADD CONSTRAINT changesets_pkey PRIMARY KEY (id);
                 key.push(Field::new(Self::index("id", &fields)?, FieldType::Integer).with_str_name("id"));
@@ -107,4 +154,48 @@ impl TablePkey {
     pub(crate) fn key(&self) -> Vec<Field> {
         self.key.clone()
     }
+
+    /// The key fields the merge joins depend on. Tags and relation members are joined to their
+    /// element in any order, so the fields ordering them within the element are left out
+    pub(crate) fn merge_key(&self) -> Vec<Field> {
+        self.key.iter()
+            .filter(|field| !["k", "member_type", "member_id", "member_role"].contains(&field.name().as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Parse the values of the merge key fields of a table data line
+    pub(crate) fn merge_key_values(&self, line: &str) -> Result<Vec<KeyValue>, anyhow::Error> {
+        let columns: Vec<&str> = line.trim_end_matches('\n').split('\t').collect();
+        let mut values = Vec::new();
+        for field in self.merge_key() {
+            let column = columns.get(field.index() - 1)
+                .ok_or(anyhow!("Field {} is missing in {}", field.name(), self.name))?;
+            match field.field_type() {
+                FieldType::Integer => {
+                    values.push(KeyValue::Integer(i64::from_str(column.trim())?));
+                }
+                _ => {
+                    values.push(KeyValue::String(column.to_string()));
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// The ORDER BY clause of a query that returns the table in the order expected by the merge
+    /// joins. Relation members are ordered by their sequence in the relation
+    pub(crate) fn order_by(&self) -> String {
+        match self.name.as_str() {
+            "public.relation_members" => {
+                "relation_id, version, sequence_id".to_string()
+            }
+            _ => {
+                self.key.iter()
+                    .map(|field| field.name().clone())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            }
+        }
+    }
 }
\ No newline at end of file
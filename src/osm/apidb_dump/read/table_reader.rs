@@ -7,6 +7,7 @@ use anyhow::anyhow;
 use unescape::unescape;
 
 use crate::osm::apidb_dump::read::changeset_record::ChangesetRecord;
+use crate::osm::apidb_dump::read::copy_out_stream::CopyOutStream;
 use crate::osm::apidb_dump::read::node_record::NodeRecord;
 use crate::osm::apidb_dump::read::node_tag_record::NodeTagRecord;
 use crate::osm::apidb_dump::read::relation_member_record::{RelationMemberRecord, RelationMemberType};
@@ -15,6 +16,7 @@ use crate::osm::apidb_dump::read::relation_tag_record::RelationTagRecord;
 use crate::osm::apidb_dump::read::table_def::TableDef;
 use crate::osm::apidb_dump::read::table_fields::TableFields;
 use crate::osm::apidb_dump::read::table_record::TableRecord;
+use crate::osm::apidb_dump::read::table_source::TableSource;
 use crate::osm::apidb_dump::read::user_record::{FormatEnum, UserRecord, UserStatus};
 use crate::osm::apidb_dump::read::way_node_record::WayNodeRecord;
 use crate::osm::apidb_dump::read::way_record::WayRecord;
@@ -48,7 +50,7 @@ impl TableReader {
 
     fn create_record_builder(&self) -> Result<RecordBuilder, anyhow::Error> {
         match self.table_def.name().as_str() {
-            "public.nodes" => {
+            "public.nodes" | "public.current_nodes" => {
                 Ok(
                     RecordBuilder {
                         f: Self::create_node,
@@ -56,7 +58,7 @@ impl TableReader {
                     }
                 )
             }
-            "public.node_tags" => {
+            "public.node_tags" | "public.current_node_tags" => {
                 Ok(
                     RecordBuilder {
                         f: Self::create_node_tag,
@@ -64,7 +66,7 @@ impl TableReader {
                     }
                 )
             }
-            "public.ways" => {
+            "public.ways" | "public.current_ways" => {
                 Ok(
                     RecordBuilder {
                         f: Self::create_way,
@@ -72,7 +74,7 @@ impl TableReader {
                     }
                 )
             }
-            "public.way_nodes" => {
+            "public.way_nodes" | "public.current_way_nodes" => {
                 Ok(
                     RecordBuilder {
                         f: Self::create_way_node,
@@ -80,7 +82,7 @@ impl TableReader {
                     }
                 )
             }
-            "public.way_tags" => {
+            "public.way_tags" | "public.current_way_tags" => {
                 Ok(
                     RecordBuilder {
                         f: Self::create_way_tag,
@@ -88,7 +90,7 @@ impl TableReader {
                     }
                 )
             }
-            "public.relations" => {
+            "public.relations" | "public.current_relations" => {
                 Ok(
                     RecordBuilder {
                         f: Self::create_relation,
@@ -96,7 +98,7 @@ impl TableReader {
                     }
                 )
             }
-            "public.relation_members" => {
+            "public.relation_members" | "public.current_relation_members" => {
                 Ok(
                     RecordBuilder {
                         f: Self::create_relation_member,
@@ -104,7 +106,7 @@ impl TableReader {
                     }
                 )
             }
-            "public.relation_tags" => {
+            "public.relation_tags" | "public.current_relation_tags" => {
                 Ok(
                     RecordBuilder {
                         f: Self::create_relation_tag,
@@ -146,7 +148,7 @@ impl TableReader {
                 assert!(*timestamp < columns.len(), "column {} for field (timestamp) is missing in {}:{}", *timestamp + 1, table_def.path().display(), line_number);
                 assert!(*tile < columns.len(), "column {} for field (tile) is missing in {}:{}", *tile + 1, table_def.path().display(), line_number);
                 assert!(*version < columns.len(), "column {} for field (version) is missing in {}:{}", *version + 1, table_def.path().display(), line_number);
-                assert!(*redaction_id < columns.len(), "column {} for field (redaction_id) is missing in {}:{}", *redaction_id + 1, table_def.path().display(), line_number);
+                assert!(redaction_id.is_none_or(|redaction_id| redaction_id < columns.len()), "column {} for field (redaction_id) is missing in {}:{}", redaction_id.unwrap_or_default() + 1, table_def.path().display(), line_number);
                 Some(
                     TableRecord::Node {
                         node_record: NodeRecord::new(
@@ -158,7 +160,7 @@ impl TableReader {
                             parse_sql_time(columns[*timestamp]).unwrap(),
                             i64::from_str(columns[*tile]).unwrap(),
                             i64::from_str(columns[*version]).unwrap(),
-                            i32::from_str(columns[*redaction_id]).ok(),
+                            redaction_id.and_then(|redaction_id| i32::from_str(columns[redaction_id]).ok()),
                         )
                     }
                 )
@@ -174,14 +176,14 @@ impl TableReader {
         match table_def.fields_ref() {
             TableFields::NodeTags { node_id, version, k, v } => {
                 assert!(*node_id < columns.len(), "column {} for field (node_id) is missing in {}:{}", *node_id + 1, table_def.path().display(), line_number);
-                assert!(*version < columns.len(), "column {} for field (version) is missing in {}:{}", *version + 1, table_def.path().display(), line_number);
+                assert!(version.is_none_or(|version| version < columns.len()), "column {} for field (version) is missing in {}:{}", version.unwrap_or_default() + 1, table_def.path().display(), line_number);
                 assert!(*k < columns.len(), "column {} for field (k) is missing in {}:{}", *k + 1, table_def.path().display(), line_number);
                 assert!(*v < columns.len(), "column {} for field (v) is missing in {}:{}", *v + 1, table_def.path().display(), line_number);
                 Some(
                     TableRecord::NodeTag {
                         node_tag_record: NodeTagRecord::new(
                             i64::from_str(columns[*node_id]).unwrap(),
-                            i64::from_str(columns[*version]).unwrap(),
+                            version.map(|version| i64::from_str(columns[version]).unwrap()),
                             unescape(columns[*k]).unwrap_or(columns[*k].to_string()),
                             unescape(columns[*v]).unwrap_or(columns[*v].to_string()),
                         )
@@ -203,7 +205,7 @@ impl TableReader {
                 assert!(*timestamp < columns.len(), "column {} for field (timestamp) is missing in {}:{}", *timestamp + 1, table_def.path().display(), line_number);
                 assert!(*version < columns.len(), "column {} for field (version) is missing in {}:{}", *version + 1, table_def.path().display(), line_number);
                 assert!(*visible < columns.len(), "column {} for field (visible) is missing in {}:{}", *visible + 1, table_def.path().display(), line_number);
-                assert!(*redaction_id < columns.len(), "column {} for field (redaction_id) is missing in {}:{}", *redaction_id + 1, table_def.path().display(), line_number);
+                assert!(redaction_id.is_none_or(|redaction_id| redaction_id < columns.len()), "column {} for field (redaction_id) is missing in {}:{}", redaction_id.unwrap_or_default() + 1, table_def.path().display(), line_number);
                 Some(
                     TableRecord::Way {
                         way_record: WayRecord::new(
@@ -212,7 +214,7 @@ impl TableReader {
                             parse_sql_time(columns[*timestamp]).unwrap(),
                             i64::from_str(columns[*version]).unwrap(),
                             parse_sql_bool(columns[*visible]).unwrap(),
-                            i32::from_str(columns[*redaction_id]).ok(),
+                            redaction_id.and_then(|redaction_id| i32::from_str(columns[redaction_id]).ok()),
                         )
                     }
                 )
@@ -229,14 +231,14 @@ impl TableReader {
             TableFields::WayNodes { way_id, node_id, version, sequence_id } => {
                 assert!(*way_id < columns.len(), "column {} for field (way_id) is missing in {}:{}", *way_id + 1, table_def.path().display(), line_number);
                 assert!(*node_id < columns.len(), "column {} for field (node_id) is missing in {}:{}", *node_id + 1, table_def.path().display(), line_number);
-                assert!(*version < columns.len(), "column {} for field (version) is missing in {}:{}", *version + 1, table_def.path().display(), line_number);
+                assert!(version.is_none_or(|version| version < columns.len()), "column {} for field (version) is missing in {}:{}", version.unwrap_or_default() + 1, table_def.path().display(), line_number);
                 assert!(*sequence_id < columns.len(), "column {} for field (sequence_id) is missing in {}:{}", *sequence_id + 1, table_def.path().display(), line_number);
                 Some(
                     TableRecord::WayNode {
                         way_node_record: WayNodeRecord::new(
                             i64::from_str(columns[*way_id]).unwrap(),
                             i64::from_str(columns[*node_id]).unwrap(),
-                            i64::from_str(columns[*version]).unwrap(),
+                            version.map(|version| i64::from_str(columns[version]).unwrap()),
                             i64::from_str(columns[*sequence_id]).unwrap(),
                         )
                     }
@@ -253,14 +255,14 @@ impl TableReader {
         match table_def.fields_ref() {
             TableFields::WayTags { way_id, k, v, version } => {
                 assert!(*way_id < columns.len(), "column {} for field (way_id) is missing in {}:{}", *way_id + 1, table_def.path().display(), line_number);
-                assert!(*version < columns.len(), "column {} for field (version) is missing in {}:{}", *version + 1, table_def.path().display(), line_number);
+                assert!(version.is_none_or(|version| version < columns.len()), "column {} for field (version) is missing in {}:{}", version.unwrap_or_default() + 1, table_def.path().display(), line_number);
                 assert!(*k < columns.len(), "column {} for field (k) is missing in {}:{}", *k + 1, table_def.path().display(), line_number);
                 assert!(*v < columns.len(), "column {} for field (v) is missing in {}:{}", *v + 1, table_def.path().display(), line_number);
                 Some(
                     TableRecord::WayTag {
                         way_tag_record: WayTagRecord::new(
                             i64::from_str(columns[*way_id]).unwrap(),
-                            i64::from_str(columns[*version]).unwrap(),
+                            version.map(|version| i64::from_str(columns[version]).unwrap()),
                             unescape(columns[*k]).unwrap_or(columns[*k].to_string()),
                             unescape(columns[*v]).unwrap_or(columns[*v].to_string()),
                         )
@@ -282,7 +284,7 @@ impl TableReader {
                 assert!(*timestamp < columns.len(), "column {} for field (timestamp) is missing in {}:{}", *timestamp + 1, table_def.path().display(), line_number);
                 assert!(*version < columns.len(), "column {} for field (version) is missing in {}:{}", *version + 1, table_def.path().display(), line_number);
                 assert!(*visible < columns.len(), "column {} for field (visible) is missing in {}:{}", *visible + 1, table_def.path().display(), line_number);
-                assert!(*redaction_id < columns.len(), "column {} for field (redaction_id) is missing in {}:{}", *redaction_id + 1, table_def.path().display(), line_number);
+                assert!(redaction_id.is_none_or(|redaction_id| redaction_id < columns.len()), "column {} for field (redaction_id) is missing in {}:{}", redaction_id.unwrap_or_default() + 1, table_def.path().display(), line_number);
                 Some(
                     TableRecord::Relation {
                         relation_record: RelationRecord::new(
@@ -291,7 +293,7 @@ impl TableReader {
                             parse_sql_time(columns[*timestamp]).unwrap(),
                             i64::from_str(columns[*version]).unwrap(),
                             parse_sql_bool(columns[*visible]).unwrap(),
-                            i32::from_str(columns[*redaction_id]).ok(),
+                            redaction_id.and_then(|redaction_id| i32::from_str(columns[redaction_id]).ok()),
                         )
                     }
                 )
@@ -310,7 +312,7 @@ impl TableReader {
                 assert!(*member_type < columns.len(), "column {} for field (member_type) is missing in {}:{}", *member_type + 1, table_def.path().display(), line_number);
                 assert!(*member_id < columns.len(), "column {} for field (member_id) is missing in {}:{}", *member_id + 1, table_def.path().display(), line_number);
                 assert!(*member_role < columns.len(), "column {} for field (member_role) is missing in {}:{}", *member_role + 1, table_def.path().display(), line_number);
-                assert!(*version < columns.len(), "column {} for field (version) is missing in {}:{}", *version + 1, table_def.path().display(), line_number);
+                assert!(version.is_none_or(|version| version < columns.len()), "column {} for field (version) is missing in {}:{}", version.unwrap_or_default() + 1, table_def.path().display(), line_number);
                 assert!(*sequence_id < columns.len(), "column {} for field (sequence_id) is missing in {}:{}", *sequence_id + 1, table_def.path().display(), line_number);
                 Some(
                     TableRecord::RelationMember {
@@ -319,7 +321,7 @@ impl TableReader {
                             RelationMemberType::try_from(columns[*member_type]).unwrap(),
                             i64::from_str(columns[*member_id]).unwrap(),
                             unescape(columns[*member_role]).unwrap_or(columns[*member_role].to_string()),
-                            i64::from_str(columns[*version]).unwrap(),
+                            version.map(|version| i64::from_str(columns[version]).unwrap()),
                             i64::from_str(columns[*sequence_id]).unwrap(),
                         )
                     }
@@ -336,14 +338,14 @@ impl TableReader {
         match table_def.fields_ref() {
             TableFields::RelationTags { relation_id, k, v, version } => {
                 assert!(*relation_id < columns.len(), "column {} for field (relation_id) is missing in {}:{}", *relation_id + 1, table_def.path().display(), line_number);
-                assert!(*version < columns.len(), "column {} for field (version) is missing in {}:{}", *version + 1, table_def.path().display(), line_number);
+                assert!(version.is_none_or(|version| version < columns.len()), "column {} for field (version) is missing in {}:{}", version.unwrap_or_default() + 1, table_def.path().display(), line_number);
                 assert!(*k < columns.len(), "column {} for field (k) is missing in {}:{}", *k + 1, table_def.path().display(), line_number);
                 assert!(*v < columns.len(), "column {} for field (v) is missing in {}:{}", *v + 1, table_def.path().display(), line_number);
                 Some(
                     TableRecord::RelationTag {
                         relation_tag_record: RelationTagRecord::new(
                             i64::from_str(columns[*relation_id]).unwrap(),
-                            i64::from_str(columns[*version]).unwrap(),
+                            version.map(|version| i64::from_str(columns[version]).unwrap()),
                             unescape(columns[*k]).unwrap_or(columns[*k].to_string()),
                             unescape(columns[*v]).unwrap_or(columns[*v].to_string()),
                         )
@@ -375,10 +377,10 @@ impl TableReader {
                             i64::from_str(columns[*id]).unwrap(),
                             i64::from_str(columns[*user_id]).unwrap(),
                             parse_sql_time(columns[*created_at]).unwrap(),
-                            i32::from_str(columns[*min_lat]).unwrap(),
-                            i32::from_str(columns[*max_lat]).unwrap(),
-                            i32::from_str(columns[*min_lon]).unwrap(),
-                            i32::from_str(columns[*max_lon]).unwrap(),
+                            i32::from_str(columns[*min_lat]).ok(),
+                            i32::from_str(columns[*max_lat]).ok(),
+                            i32::from_str(columns[*min_lon]).ok(),
+                            i32::from_str(columns[*max_lon]).ok(),
                             parse_sql_time(columns[*closed_at]).unwrap(),
                             i32::from_str(columns[*num_changes]).unwrap(),
                         )
@@ -477,20 +479,31 @@ impl IntoIterator for TableReader {
 }
 
 pub(crate) struct TableIterator {
-    reader: BufReader<File>,
+    table_name: String,
+    reader: BufReader<Box<dyn Read + Send>>,
     record_builder: RecordBuilder,
     line_number: usize,
 }
 
 impl TableIterator {
     pub(crate) fn new(table_reader: &TableReader) -> Result<TableIterator, anyhow::Error> {
-        log::info!("Source data for {} is in {}", table_reader.table_def.name(), table_reader.table_def.path().display());
-        log::info!("Create iterator for {} from {}", table_reader.table_def.name(), table_reader.table_def.sorted_path().display());
-        let f = File::open(table_reader.table_def.sorted_path())?;
-        let reader = BufReader::new(f);
+        let table_def = &table_reader.table_def;
+        let source: Box<dyn Read + Send> = match table_def.source() {
+            TableSource::File => {
+                log::info!("Source data for {} is in {}", table_def.name(), table_def.path().display());
+                log::info!("Create iterator for {} from {}", table_def.name(), table_def.sorted_path().display());
+                Box::new(File::open(table_def.sorted_path())?)
+            }
+            TableSource::Database { connection_params, snapshot, query } => {
+                log::info!("Create iterator for {} from {}", table_def.name(), query);
+                Box::new(CopyOutStream::start(connection_params, snapshot.as_deref(), query)?)
+            }
+        };
+        let reader = BufReader::new(source);
         let record_builder = table_reader.create_record_builder()?;
         Ok(
             TableIterator {
+                table_name: table_def.name(),
                 reader,
                 record_builder,
                 line_number: 0,
@@ -519,8 +532,8 @@ impl Iterator for TableIterator {
                     }
                 }
             }
-            Err(_) => {
-                None
+            Err(e) => {
+                panic!("Failed to read {} at line {}: {}", self.table_name, self.line_number, e);
             }
         }
     }
@@ -0,0 +1,39 @@
+use crate::osm::apidb_dump::read::table_fields::{CURRENT_TABLES_OF_INTEREST, TABLES_OF_INTEREST};
+
+/// The set of apidb tables the elements are read from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableSet {
+    /// Read all versions of each element from the history tables: nodes, node_tags, ways, etc.
+    History,
+    /// Read the latest version of each element from current_nodes, current_node_tags,
+    /// current_ways, etc., skipping the deleted elements. The result is equivalent to a
+    /// non-history planet file
+    Current,
+}
+
+impl TableSet {
+    /// The names of the tables read by this table set
+    pub(crate) fn table_names(&self) -> [&'static str; 10] {
+        match self {
+            TableSet::History => {
+                TABLES_OF_INTEREST
+            }
+            TableSet::Current => {
+                CURRENT_TABLES_OF_INTEREST
+            }
+        }
+    }
+
+    /// The name of the history table that the given table of this set stands for, or None if the
+    /// table is not read by this set. Users and changesets are shared by both sets
+    pub(crate) fn history_table_name(&self, name: &str) -> Option<&'static str> {
+        self.table_names().iter()
+            .position(|table_name| *table_name == name)
+            .map(|i| TABLES_OF_INTEREST[i])
+    }
+
+    /// True if only the visible elements are produced
+    pub(crate) fn visible_only(&self) -> bool {
+        *self == TableSet::Current
+    }
+}
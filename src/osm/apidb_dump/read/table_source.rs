@@ -0,0 +1,12 @@
+/// Where the rows of a table are read from
+#[derive(Debug, Clone)]
+pub(crate) enum TableSource {
+    /// The sorted table data file of a pg_dump directory
+    File,
+    /// A `COPY (SELECT ...) TO STDOUT` query on a live database, optionally in an exported snapshot
+    Database {
+        connection_params: String,
+        snapshot: Option<String>,
+        query: String,
+    },
+}
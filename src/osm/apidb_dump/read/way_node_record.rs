@@ -2,7 +2,7 @@
 pub(crate) struct WayNodeRecord {
     way_id: i64,
     node_id: i64,
-    version: i64,
+    version: Option<i64>,
     sequence_id: i64,
 }
 
@@ -10,7 +10,7 @@ impl WayNodeRecord {
     pub(crate) fn new(
         way_id: i64,
         node_id: i64,
-        version: i64,
+        version: Option<i64>,
         sequence_id: i64,
     ) -> WayNodeRecord {
         WayNodeRecord {
@@ -29,7 +29,7 @@ impl WayNodeRecord {
         self.node_id
     }
 
-    pub(crate) fn version(&self) -> i64 {
+    pub(crate) fn version(&self) -> Option<i64> {
         self.version
     }
 
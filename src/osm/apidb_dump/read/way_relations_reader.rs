@@ -77,11 +77,11 @@ impl Iterator for WayRelationsIterator {
             if let TableRecord::Way { way_record } = way {
                 let mut current_way_tags = Vec::<WayTagRecord>::new();
                 if let Some(way_tag_record) = self.next_way_tag_record.take() {
-                    if way_tag_record.way_id() == way_record.way_id() && way_tag_record.version() == way_record.version() {
+                    if way_tag_record.way_id() == way_record.way_id() && way_tag_record.version().is_none_or(|version| version == way_record.version()) {
                         current_way_tags.push(way_tag_record);
                         for way_tag in self.way_tags_iterator.by_ref() {
                             if let TableRecord::WayTag { way_tag_record } = way_tag {
-                                if way_tag_record.way_id() == way_record.way_id() && way_tag_record.version() == way_record.version() {
+                                if way_tag_record.way_id() == way_record.way_id() && way_tag_record.version().is_none_or(|version| version == way_record.version()) {
                                     current_way_tags.push(way_tag_record)
                                 } else {
                                     self.next_way_tag_record = Some(way_tag_record);
@@ -97,7 +97,7 @@ impl Iterator for WayRelationsIterator {
                 } else {
                     for way_tag in self.way_tags_iterator.by_ref() {
                         if let TableRecord::WayTag { way_tag_record } = way_tag {
-                            if way_tag_record.way_id() == way_record.way_id() && way_tag_record.version() == way_record.version() {
+                            if way_tag_record.way_id() == way_record.way_id() && way_tag_record.version().is_none_or(|version| version == way_record.version()) {
                                 current_way_tags.push(way_tag_record)
                             } else {
                                 self.next_way_tag_record = Some(way_tag_record);
@@ -111,11 +111,11 @@ impl Iterator for WayRelationsIterator {
 
                 let mut current_way_nodes = Vec::<WayNodeRecord>::new();
                 if let Some(way_node_record) = self.next_way_node_record.take() {
-                    if way_node_record.way_id() == way_record.way_id() && way_node_record.version() == way_record.version() {
+                    if way_node_record.way_id() == way_record.way_id() && way_node_record.version().is_none_or(|version| version == way_record.version()) {
                         current_way_nodes.push(way_node_record);
                         for way_node in self.way_nodes_iterator.by_ref() {
                             if let TableRecord::WayNode { way_node_record } = way_node {
-                                if way_node_record.way_id() == way_record.way_id() && way_node_record.version() == way_record.version() {
+                                if way_node_record.way_id() == way_record.way_id() && way_node_record.version().is_none_or(|version| version == way_record.version()) {
                                     current_way_nodes.push(way_node_record)
                                 } else {
                                     self.next_way_node_record = Some(way_node_record);
@@ -131,7 +131,7 @@ impl Iterator for WayRelationsIterator {
                 } else {
                     for way_node in self.way_nodes_iterator.by_ref() {
                         if let TableRecord::WayNode { way_node_record } = way_node {
-                            if way_node_record.way_id() == way_record.way_id() && way_node_record.version() == way_record.version() {
+                            if way_node_record.way_id() == way_record.way_id() && way_node_record.version().is_none_or(|version| version == way_record.version()) {
                                 current_way_nodes.push(way_node_record)
                             } else {
                                 self.next_way_node_record = Some(way_node_record);
@@ -1,7 +1,7 @@
 #[derive(Debug)]
 pub(crate) struct WayTagRecord {
     way_id: i64,
-    version: i64,
+    version: Option<i64>,
     k: String,
     v: String,
 }
@@ -9,7 +9,7 @@ pub(crate) struct WayTagRecord {
 impl WayTagRecord {
     pub(crate) fn new(
         way_id: i64,
-        version: i64,
+        version: Option<i64>,
         k: String,
         v: String,
     ) -> WayTagRecord {
@@ -25,7 +25,7 @@ impl WayTagRecord {
         self.way_id
     }
 
-    pub(crate) fn version(&self) -> i64 {
+    pub(crate) fn version(&self) -> Option<i64> {
         self.version
     }
 
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::osm::model::coordinate::Coordinate;
+
+/// Changeset metadata accumulated from the elements written in the changeset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct ChangesetSummary {
+    user_id: i64,
+    min_timestamp: i64,
+    max_timestamp: i64,
+    // (min_lat, max_lat, min_lon, max_lon) * 10^7 of the visible nodes, None if there are none
+    bounding_box: Option<(i64, i64, i64, i64)>,
+    num_changes: i64,
+}
+
+impl ChangesetSummary {
+    pub(crate) fn new(user_id: i64, timestamp: i64) -> ChangesetSummary {
+        ChangesetSummary {
+            user_id,
+            min_timestamp: timestamp,
+            max_timestamp: timestamp,
+            bounding_box: None,
+            num_changes: 0,
+        }
+    }
+
+    /// Count an element version and extend the time range
+    pub(crate) fn add_change(&mut self, timestamp: i64) {
+        self.min_timestamp = self.min_timestamp.min(timestamp);
+        self.max_timestamp = self.max_timestamp.max(timestamp);
+        self.num_changes += 1;
+    }
+
+    pub(crate) fn add_coordinate(&mut self, coordinate: &Coordinate) {
+        let (lat, lon) = (coordinate.lat7(), coordinate.lon7());
+        self.bounding_box = match self.bounding_box {
+            None => {
+                Some((lat, lat, lon, lon))
+            }
+            Some((min_lat, max_lat, min_lon, max_lon)) => {
+                Some((min_lat.min(lat), max_lat.max(lat), min_lon.min(lon), max_lon.max(lon)))
+            }
+        };
+    }
+
+    pub(crate) fn merge(&mut self, other: &ChangesetSummary) {
+        self.min_timestamp = self.min_timestamp.min(other.min_timestamp);
+        self.max_timestamp = self.max_timestamp.max(other.max_timestamp);
+        self.num_changes += other.num_changes;
+        self.bounding_box = match (self.bounding_box, other.bounding_box) {
+            (None, other_bounding_box) => {
+                other_bounding_box
+            }
+            (bounding_box, None) => {
+                bounding_box
+            }
+            (Some((min_lat, max_lat, min_lon, max_lon)), Some((other_min_lat, other_max_lat, other_min_lon, other_max_lon))) => {
+                Some((min_lat.min(other_min_lat), max_lat.max(other_max_lat), min_lon.min(other_min_lon), max_lon.max(other_max_lon)))
+            }
+        };
+    }
+
+    pub(crate) fn user_id(&self) -> i64 {
+        self.user_id
+    }
+
+    pub(crate) fn min_timestamp(&self) -> i64 {
+        self.min_timestamp
+    }
+
+    pub(crate) fn max_timestamp(&self) -> i64 {
+        self.max_timestamp
+    }
+
+    pub(crate) fn bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        self.bounding_box
+    }
+
+    pub(crate) fn num_changes(&self) -> i64 {
+        self.num_changes
+    }
+}
@@ -0,0 +1,97 @@
+use std::io::Write;
+
+/// Write table rows with only the selected columns, in the selected order
+///
+/// The rows may be split between writes. The end of data marker is written as is.
+pub(crate) struct ColumnProjection<W: Write> {
+    columns: Vec<usize>,
+    inner: W,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> ColumnProjection<W> {
+    pub(crate) fn new(columns: Vec<usize>, inner: W) -> ColumnProjection<W> {
+        ColumnProjection {
+            columns,
+            inner,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Write the last row even if it is not terminated and return the inner writer
+    pub(crate) fn into_inner(mut self) -> std::io::Result<W> {
+        if !self.pending.is_empty() {
+            let line = std::mem::take(&mut self.pending);
+            self.write_line(&line)?;
+        }
+        Ok(self.inner)
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> std::io::Result<()> {
+        let (row, end) = match line.strip_suffix(b"\n") {
+            None => {
+                (line, &b""[..])
+            }
+            Some(row) => {
+                (row, &b"\n"[..])
+            }
+        };
+        if row == b"\\." {
+            return self.inner.write_all(line);
+        }
+        let values: Vec<&[u8]> = row.split(|b| *b == b'\t').collect();
+        for (i, column) in self.columns.iter().enumerate() {
+            if i > 0 {
+                self.inner.write_all(b"\t")?;
+            }
+            let value = values.get(*column)
+                .ok_or(std::io::Error::other(format!("Column {} is missing in row: {}", column, String::from_utf8_lossy(row))))?;
+            self.inner.write_all(value)?;
+        }
+        self.inner.write_all(end)
+    }
+}
+
+impl<W: Write> Write for ColumnProjection<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut start = 0;
+        for (i, b) in buf.iter().enumerate() {
+            if *b == b'\n' {
+                let line = match self.pending.is_empty() {
+                    true => {
+                        buf[start..=i].to_vec()
+                    }
+                    false => {
+                        let mut line = std::mem::take(&mut self.pending);
+                        line.extend_from_slice(&buf[start..=i]);
+                        line
+                    }
+                };
+                self.write_line(&line)?;
+                start = i + 1;
+            }
+        }
+        self.pending.extend_from_slice(&buf[start..]);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::osm::apidb_dump::write::column_projection::ColumnProjection;
+
+    #[test]
+    fn test_column_projection() {
+        let mut projection = ColumnProjection::new(vec![2, 0], Vec::new());
+        projection.write_all(b"1\ta\tx\n2\tb").unwrap();
+        projection.write_all(b"\ty\n\\.\n").unwrap();
+        let written = projection.into_inner().unwrap();
+        assert_eq!(String::from_utf8(written).unwrap(), "x\t1\ny\t2\n\\.\n");
+    }
+}
@@ -0,0 +1,12 @@
+/// When the rows loaded by [DatabaseWriter](crate::osm::apidb_dump::write::database_writer::DatabaseWriter)
+/// are committed
+///
+/// Each table is loaded over its own connection, so the transactions are per table and a failure
+/// while loading one table does not roll back the other tables.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommitPolicy {
+    /// Load each table in a single transaction, committed when the writer is closed
+    PerTable,
+    /// Commit each table after every given number of rows, limiting the size of the transactions
+    EveryRows(usize),
+}
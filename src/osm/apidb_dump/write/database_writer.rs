@@ -0,0 +1,147 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Error};
+use postgres::{Client, NoTls};
+
+use crate::osm::apidb_dump::write::database_writer_options::DatabaseWriterOptions;
+use crate::osm::apidb_dump::write::post_load_options::PostLoadOptions;
+use crate::osm::apidb_dump::write::schema::Schema;
+use crate::osm::apidb_dump::write::table_constraints::TableConstraints;
+use crate::osm::apidb_dump::write::table_data_writer::TableDataWriter;
+use crate::osm::apidb_dump::write::table_data_writers::TableDataWriters;
+use crate::osm::apidb_dump::write::table_layout::{POPULATED_TABLES, TableLayout};
+use crate::osm::apidb_dump::write::table_loader::TableLoader;
+use crate::osm::apidb_dump::write::writer::Writer;
+use crate::osm::model::changeset::Changeset;
+use crate::osm::model::element::Element;
+
+/// Writer that loads elements directly into a Postgresql database with the apidb schema
+///
+/// Produces the same table rows as [Writer], but instead of writing a pg_restore directory
+/// the rows are streamed over `COPY ... FROM STDIN`, so no intermediate disk space is required.
+/// Each table is loaded on its own connection and thread, which are opened when the first row of
+/// the table is written. The database must contain the apidb schema and no data in the loaded
+/// tables. See [DatabaseWriterOptions] for the commit policy and for dropping and rebuilding the
+/// indexes. If the load fails, the dropped constraints and indexes are not restored.
+/// The rows are adapted to the columns of the database schema, as with [Writer::from_schema].
+///
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::apidb_dump::write::database_writer::DatabaseWriter;
+/// use osm_io::osm::pbf;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+///     let reader = pbf::reader::Reader::new(&input_path)?;
+///     let mut writer = DatabaseWriter::new("postgresql://openstreetmap@localhost:5432/openstreetmap")?;
+///     for element in reader.elements()? {
+///         writer.write_element(element)?;
+///     }
+///     writer.close()?;
+///     Ok(())
+/// }
+/// ```
+pub struct DatabaseWriter {
+    client: Client,
+    writer: Option<Writer>,
+    loaders: Vec<TableLoader>,
+    table_constraints: TableConstraints,
+    post_load_options: PostLoadOptions,
+}
+
+impl DatabaseWriter {
+    /// Create a new [DatabaseWriter]
+    ///
+    /// * connection_params - connection string either in the key=value format or as a URL, as
+    ///   accepted by [postgres::Config]
+    pub fn new(connection_params: &str) -> Result<DatabaseWriter, Error> {
+        Self::from_options(connection_params, DatabaseWriterOptions::default())
+    }
+
+    /// Create a new [DatabaseWriter] with custom [DatabaseWriterOptions]
+    pub fn from_options(connection_params: &str, options: DatabaseWriterOptions) -> Result<DatabaseWriter, Error> {
+        let schema = Schema::from_database(connection_params)?;
+        log::info!("Load apidb schema version {}", schema.version().unwrap_or("unknown"));
+        let layouts = TableLayout::for_schema(&schema)?;
+        let mut client = Client::connect(connection_params, NoTls)?;
+        let table_constraints = TableConstraints::drop_constraints(&mut client, &POPULATED_TABLES, options.drop_indexes())?;
+
+        let mut loaders = Vec::new();
+        let writers = TableDataWriters::from_factory(|table_name| {
+            match POPULATED_TABLES.contains(&table_name) {
+                true => {
+                    let layout = layouts.get(table_name)
+                        .ok_or(anyhow!("Missing table layout for {}", table_name))?;
+                    let copy_statement = layout.copy_statement();
+                    let (loader, sink) = TableLoader::start(connection_params, table_name, &copy_statement, options.commit_policy().clone())?;
+                    loaders.push(loader);
+                    let mut writer = TableDataWriter::from_sink(table_name.to_string(), copy_statement, Box::new(sink), options.buffer_size());
+                    if let Some(projection) = layout.projection() {
+                        writer.project(projection.clone())?;
+                    }
+                    Ok(writer)
+                }
+                false => {
+                    Ok(TableDataWriter::from_sink(table_name.to_string(), "none".to_string(), Box::new(std::io::sink()) as Box<dyn Write + Send>, 0))
+                }
+            }
+        })?;
+
+        Ok(
+            DatabaseWriter {
+                client,
+                writer: Some(Writer::from_table_data_writers(PathBuf::new(), 0, writers)),
+                loaders,
+                table_constraints,
+                post_load_options: options.post_load_options().clone(),
+            }
+        )
+    }
+
+    /// Write an element
+    pub fn write_element(&mut self, element: Element) -> Result<(), Error> {
+        self.writer.as_mut()
+            .ok_or(anyhow!("Writer is closed"))?
+            .write_element(element)
+    }
+
+    /// Use the supplied changeset metadata for the changesets and changeset_tags tables, see
+    /// [Writer::add_changesets]
+    pub fn add_changesets(&mut self, changesets: impl IntoIterator<Item = Changeset>) -> Result<(), Error> {
+        self.writer.as_mut()
+            .ok_or(anyhow!("Writer is closed"))?
+            .add_changesets(changesets)
+    }
+
+    /// Complete the load
+    ///
+    /// Writes the remaining rows, waits for all tables to be loaded and committed, rebuilds the
+    /// dropped constraints and indexes, sets the sequences of the id columns past the loaded ids
+    /// and runs the post load steps.
+    pub fn close(&mut self) -> Result<(), Error> {
+        let mut writer = self.writer.take().ok_or(anyhow!("Writer is closed"))?;
+        let result = writer.close();
+        let sequence_values = writer.sequence_values().clone();
+        // release the sinks, which ends the COPY streams
+        drop(writer);
+        // join the loaders even if writing failed, their errors tell why
+        let mut errors = Vec::new();
+        for loader in self.loaders.drain(..) {
+            if let Err(e) = loader.join(result.is_ok()) {
+                errors.push(format!("{:?}", e));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(anyhow!("Failed to load tables:\n{}", errors.join("\n")));
+        }
+        result?;
+        self.table_constraints.rebuild(&mut self.client)?;
+        self.client.batch_execute(&sequence_values.setval_statements().concat())?;
+        for statement in self.post_load_options.statements(&POPULATED_TABLES) {
+            log::info!("Post load: {}", statement.trim_end());
+            self.client.batch_execute(&statement)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,84 @@
+use crate::osm::apidb_dump::write::commit_policy::CommitPolicy;
+use crate::osm::apidb_dump::write::post_load_options::PostLoadOptions;
+
+/// Options for [DatabaseWriter](crate::osm::apidb_dump::write::database_writer::DatabaseWriter)
+///
+/// The tables are loaded concurrently and in an order unrelated to the dependencies between them,
+/// so the foreign keys of the loaded tables are always dropped before the load and rebuilt after
+/// it. With `drop_indexes` the indexes, the primary keys and the unique constraints of the loaded
+/// tables are dropped as well, which is much faster for large loads than maintaining them row by
+/// row, and rebuilt when the writer is closed. The post load steps run after the rebuild, see
+/// [PostLoadOptions].
+///
+/// Example:
+/// ```
+/// use osm_io::osm::apidb_dump::write::commit_policy::CommitPolicy;
+/// use osm_io::osm::apidb_dump::write::database_writer_options::DatabaseWriterOptions;
+/// fn example() {
+///     let mut options = DatabaseWriterOptions::default();
+///     options.with_commit_policy(CommitPolicy::EveryRows(10_000_000));
+///     options.with_drop_indexes(true);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DatabaseWriterOptions {
+    commit_policy: CommitPolicy,
+    drop_indexes: bool,
+    buffer_size: usize,
+    post_load_options: PostLoadOptions,
+}
+
+impl DatabaseWriterOptions {
+    /// Get the commit policy
+    pub fn commit_policy(&self) -> &CommitPolicy {
+        &self.commit_policy
+    }
+
+    /// Set the commit policy
+    pub fn with_commit_policy(&mut self, commit_policy: CommitPolicy) {
+        self.commit_policy = commit_policy;
+    }
+
+    /// True if the indexes and the key constraints are dropped before the load and rebuilt after it
+    pub fn drop_indexes(&self) -> bool {
+        self.drop_indexes
+    }
+
+    /// Drop the indexes and the key constraints of the loaded tables before the load and rebuild
+    /// them after it
+    pub fn with_drop_indexes(&mut self, drop_indexes: bool) {
+        self.drop_indexes = drop_indexes;
+    }
+
+    /// Get the size in bytes of the buffer of each table
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Set the size in bytes of the buffer of each table. Each buffer is sent to the database as a
+    /// single chunk of COPY data
+    pub fn with_buffer_size(&mut self, buffer_size: usize) {
+        self.buffer_size = buffer_size;
+    }
+
+    /// Get the post load options
+    pub fn post_load_options(&self) -> &PostLoadOptions {
+        &self.post_load_options
+    }
+
+    /// Set the post load options
+    pub fn with_post_load_options(&mut self, post_load_options: PostLoadOptions) {
+        self.post_load_options = post_load_options;
+    }
+}
+
+impl Default for DatabaseWriterOptions {
+    fn default() -> Self {
+        DatabaseWriterOptions {
+            commit_policy: CommitPolicy::PerTable,
+            drop_indexes: false,
+            buffer_size: 256 * 1024,
+            post_load_options: PostLoadOptions::default(),
+        }
+    }
+}
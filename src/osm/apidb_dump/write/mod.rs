@@ -1,6 +1,21 @@
 pub mod writer;
+pub mod database_writer;
+pub mod database_writer_options;
+pub mod commit_policy;
+pub mod parallel_writer;
+pub mod parallel_writer_options;
+pub mod schema;
+pub mod post_load_options;
 
 mod toc;
 mod table_data_writers;
 mod table_data_writer;
 mod current_object;
+mod changeset_summary;
+mod table_loader;
+mod table_constraints;
+mod table_file_writer;
+mod table_layout;
+mod column_projection;
+mod toc_entry;
+mod sequence_values;
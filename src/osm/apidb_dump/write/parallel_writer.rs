@@ -0,0 +1,371 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, sync_channel, SyncSender};
+use std::thread;
+use std::thread::JoinHandle;
+
+use anyhow::{anyhow, Error};
+
+use crate::osm::apidb_dump::write::parallel_writer_options::ParallelWriterOptions;
+use crate::osm::apidb_dump::write::table_data_writer::TableDataWriter;
+use crate::osm::apidb_dump::write::table_data_writers::TableDataWriters;
+use crate::osm::apidb_dump::write::table_file_writer::{TableFileSink, TableFileWriter};
+use crate::osm::apidb_dump::write::sequence_values::SequenceValues;
+use crate::osm::apidb_dump::write::toc::{DumpToc, load_template_mapping};
+use crate::osm::apidb_dump::write::writer::Writer;
+use crate::osm::model::changeset::Changeset;
+use crate::osm::model::element::Element;
+use crate::osm::model::element_kind::ElementKind;
+
+/// The tables populated from each kind of elements
+fn element_tables(kind: ElementKind) -> &'static [&'static str] {
+    match kind {
+        ElementKind::Node => {
+            &[
+                "public.current_node_tags",
+                "public.current_nodes",
+                "public.node_tags",
+                "public.nodes",
+            ]
+        }
+        ElementKind::Way => {
+            &[
+                "public.current_way_nodes",
+                "public.current_way_tags",
+                "public.current_ways",
+                "public.way_nodes",
+                "public.way_tags",
+                "public.ways",
+            ]
+        }
+        ElementKind::Relation => {
+            &[
+                "public.current_relation_members",
+                "public.current_relation_tags",
+                "public.current_relations",
+                "public.relation_members",
+                "public.relation_tags",
+                "public.relations",
+            ]
+        }
+    }
+}
+
+fn is_element_table(table_name: &str) -> bool {
+    ElementKind::ALL.iter().any(|kind| element_tables(*kind).contains(&table_name))
+}
+
+fn null_table_data_writer(table_name: &str) -> TableDataWriter {
+    TableDataWriter::from_sink(table_name.to_string(), "none".to_string(), Box::new(std::io::sink()) as Box<dyn Write + Send>, 0)
+}
+
+/// State of the formatting stage of one kind of elements, accessed only from its formatting thread
+///
+/// Restores the order of the elements and formats them with a [Writer] that writes only the
+/// tables of that kind, so the current object lines are maintained exactly as by a sequential
+/// [Writer].
+struct ElementFormatting {
+    kind: ElementKind,
+    writer: Writer,
+    buffer: VecDeque<Element>,
+    buffer_size: usize,
+    batch_size: usize,
+    last_formatted_element: Option<Element>,
+}
+
+impl ElementFormatting {
+    fn new(kind: ElementKind, writer: Writer, buffer_size: usize, batch_size: usize) -> ElementFormatting {
+        ElementFormatting {
+            kind,
+            writer,
+            buffer: VecDeque::new(),
+            buffer_size,
+            batch_size,
+            last_formatted_element: None,
+        }
+    }
+
+    fn run(mut self, receiver: Receiver<Vec<Element>>) -> Result<Writer, Error> {
+        for elements in receiver {
+            self.add(elements)?;
+        }
+        self.buffer.make_contiguous().sort();
+        self.format(self.buffer.len())?;
+        self.writer.close_elements()?;
+        Ok(self.writer)
+    }
+
+    fn add(&mut self, elements: Vec<Element>) -> Result<(), Error> {
+        for element in elements {
+            self.verify_order(&element)?;
+            self.buffer.push_back(element);
+        }
+        if self.buffer.len() > self.buffer_size {
+            self.buffer.make_contiguous().sort();
+            let count = self.buffer.len() - self.buffer_size.saturating_sub(self.batch_size);
+            self.format(count)?;
+        }
+        Ok(())
+    }
+
+    /// Format the first `count` elements of the sorted buffer
+    fn format(&mut self, count: usize) -> Result<(), Error> {
+        let elements: Vec<Element> = self.buffer.drain(..count).collect();
+        if let Some(element) = elements.last() {
+            self.last_formatted_element.replace(element.clone());
+        }
+        for element in elements {
+            self.writer.write_element(element)?;
+        }
+        Ok(())
+    }
+
+    fn verify_order(&self, element: &Element) -> Result<(), Error> {
+        if let Some(last_formatted_element) = &self.last_formatted_element {
+            if element < last_formatted_element {
+                return Err(
+                    anyhow!(
+                        "{:?} order, required to maintain the current tables, is lost. \
+                        Possible cause is that the length of the ordering buffer ({}) is too short \
+                        to compensate for the loss of order caused by concurrent processing. \
+                        Recommended: reader_tasks * 8000 * n",
+                        self.kind,
+                        self.buffer_size
+                    )
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The formatting thread of one kind of elements and the batch being accumulated for it
+struct FormattingWorker {
+    kind: ElementKind,
+    batch: Vec<Element>,
+    sender: Option<SyncSender<Vec<Element>>>,
+    handle: JoinHandle<Result<Writer, Error>>,
+}
+
+impl FormattingWorker {
+    fn send_batch(&mut self) -> Result<(), Error> {
+        let batch = std::mem::take(&mut self.batch);
+        self.sender.as_ref()
+            .ok_or(anyhow!("Writer is closed"))?
+            .send(batch)
+            .map_err(|_| anyhow!("Formatting of {:?} elements stopped, the cause is returned by close", self.kind))
+    }
+}
+
+/// Writer of apidb schema dump that formats and writes the tables concurrently
+///
+/// Produces the same output as [Writer]. The elements of each kind are ordered and formatted into
+/// table rows on a separate thread, and each table data file, including its compression, is
+/// written on a separate thread. The users and changesets accumulated from the elements are
+/// written on close, with the sequence values and the post load steps.
+///
+/// The writer accepts a somewhat unordered stream of elements, as produced by
+/// [Reader::parallel_for_each](crate::osm::pbf::reader::Reader::parallel_for_each), and restores
+/// the order of each kind of elements in a buffer of `element_ordering_buffer_size` elements. See
+/// [ParallelWriterOptions]. If the order cannot be restored the writing fails.
+///
+/// Errors that occur in the formatting and writing threads are returned by `close`.
+///
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use std::sync::{Arc, Mutex};
+/// use osm_io::osm::apidb_dump::write::parallel_writer::ParallelWriter;
+/// use osm_io::osm::pbf::reader::Reader;
+/// use osm_io::osm::pbf::thread_local_accumulator::ThreadLocalAccumulator;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+///     let output_path = PathBuf::from("./target/results/history-niue-230109");
+///     let reader = Reader::new(&input_path)?;
+///     let writer = Arc::new(Mutex::new(ParallelWriter::new(output_path, 0)?));
+///     let writer_clone = writer.clone();
+///     let tl_acc = ThreadLocalAccumulator::new(8000);
+///     reader.parallel_for_each(4, move |element| {
+///         if !element.is_sentinel() {
+///             tl_acc.add(element);
+///         } else {
+///             writer.lock().unwrap().write_elements(tl_acc.elements())?;
+///         }
+///         Ok(())
+///     })?;
+///     writer_clone.lock().unwrap().close()?;
+///     Ok(())
+/// }
+/// ```
+pub struct ParallelWriter {
+    writer: Option<Writer>,
+    workers: Vec<FormattingWorker>,
+    file_writers: Vec<TableFileWriter>,
+    batch_size: usize,
+}
+
+impl ParallelWriter {
+    /// Create a new [ParallelWriter]
+    ///
+    /// * output_path - directory to write the output to, see [Writer::new]
+    /// * compression_level - the compression of the table data files, see [Writer::new]
+    pub fn new(output_path: PathBuf, compression_level: i8) -> Result<ParallelWriter, Error> {
+        Self::from_options(output_path, compression_level, ParallelWriterOptions::default())
+    }
+
+    /// Create a new [ParallelWriter] with custom [ParallelWriterOptions]
+    pub fn from_options(output_path: PathBuf, compression_level: i8, options: ParallelWriterOptions) -> Result<ParallelWriter, Error> {
+        if options.batch_size() == 0 || options.queue_size() == 0 {
+            return Err(anyhow!("Batch size and queue size must be positive"));
+        }
+        Writer::create_result_dir(&output_path, compression_level)?;
+        let toc = DumpToc::Template;
+        toc.write(&output_path, compression_level, &SequenceValues::default())?;
+        let template_mapping = load_template_mapping()?;
+
+        let mut file_writers = Vec::new();
+        let mut sinks = HashMap::<String, (String, TableFileSink)>::new();
+        for kind in ElementKind::ALL {
+            for table_name in element_tables(kind) {
+                let table_data_writer = TableDataWriter::new(table_name.to_string(), template_mapping[*table_name].to_string(), &output_path, compression_level)?;
+                let sink_name = table_data_writer.sink_name().to_string();
+                let (file_writer, sink) = TableFileWriter::start(table_data_writer, options.queue_size())?;
+                file_writers.push(file_writer);
+                sinks.insert(table_name.to_string(), (sink_name, sink));
+            }
+        }
+
+        let mut workers = Vec::new();
+        for kind in ElementKind::ALL {
+            let writers = TableDataWriters::from_factory(|table_name| {
+                match element_tables(kind).contains(&table_name) {
+                    true => {
+                        let (sink_name, sink) = sinks.remove(table_name)
+                            .ok_or(anyhow!("Missing table data file writer for {}", table_name))?;
+                        Ok(TableDataWriter::from_sink(table_name.to_string(), sink_name, Box::new(sink), options.buffer_size()))
+                    }
+                    false => {
+                        Ok(null_table_data_writer(table_name))
+                    }
+                }
+            })?;
+            let formatting = ElementFormatting::new(
+                kind,
+                Writer::from_table_data_writers(output_path.clone(), compression_level, writers),
+                options.element_ordering_buffer_size(),
+                options.batch_size(),
+            );
+            let (sender, receiver) = sync_channel::<Vec<Element>>(options.queue_size());
+            let handle = thread::Builder::new()
+                .name(format!("format-{:?}", kind).to_lowercase())
+                .spawn(move || {
+                    formatting.run(receiver)
+                })?;
+            workers.push(
+                FormattingWorker {
+                    kind,
+                    batch: Vec::with_capacity(options.batch_size()),
+                    sender: Some(sender),
+                    handle,
+                }
+            );
+        }
+
+        let writers = TableDataWriters::from_factory(|table_name| {
+            match is_element_table(table_name) {
+                true => {
+                    Ok(null_table_data_writer(table_name))
+                }
+                false => {
+                    TableDataWriter::new(table_name.to_string(), template_mapping[table_name].to_string(), &output_path, compression_level)
+                }
+            }
+        })?;
+
+        let mut writer = Writer::from_table_data_writers(output_path, compression_level, writers);
+        writer.with_toc(toc);
+        writer.with_post_load_options(options.post_load_options().clone());
+        Ok(
+            ParallelWriter {
+                writer: Some(writer),
+                workers,
+                file_writers,
+                batch_size: options.batch_size(),
+            }
+        )
+    }
+
+    /// Write an element
+    ///
+    /// Blocks when the formatting queue of the element kind is full.
+    pub fn write_element(&mut self, element: Element) -> Result<(), Error> {
+        if let Some(kind) = element.kind() {
+            let worker = self.workers.iter_mut()
+                .find(|worker| worker.kind == kind)
+                .ok_or(anyhow!("Writer is closed"))?;
+            worker.batch.push(element);
+            if worker.batch.len() >= self.batch_size {
+                worker.send_batch()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write list of [Element]s
+    ///
+    /// Blocks when the formatting queues are full.
+    pub fn write_elements(&mut self, elements: Vec<Element>) -> Result<(), Error> {
+        for element in elements {
+            self.write_element(element)?;
+        }
+        Ok(())
+    }
+
+    /// Use the supplied changeset metadata for the changesets and changeset_tags tables, see
+    /// [Writer::add_changesets]
+    pub fn add_changesets(&mut self, changesets: impl IntoIterator<Item = Changeset>) -> Result<(), Error> {
+        self.writer.as_mut()
+            .ok_or(anyhow!("Writer is closed"))?
+            .add_changesets(changesets)
+    }
+
+    /// Flush internal buffers and add file terminators
+    ///
+    /// Waits for all tables to be written. Returns the errors that occurred in the formatting and
+    /// writing threads, if any.
+    pub fn close(&mut self) -> Result<(), Error> {
+        let mut writer = self.writer.take().ok_or(anyhow!("Writer is closed"))?;
+        let mut errors = Vec::new();
+        for worker in &mut self.workers {
+            if !worker.batch.is_empty() {
+                // a failed send is reported by joining the worker
+                let _ = worker.send_batch();
+            }
+            // release the sender, which ends the formatting
+            worker.sender.take();
+        }
+        for worker in self.workers.drain(..) {
+            match worker.handle.join() {
+                Ok(Ok(mut element_writer)) => {
+                    writer.merge_accumulated(&mut element_writer);
+                }
+                Ok(Err(e)) => {
+                    errors.push(format!("{:?}", e));
+                }
+                Err(_) => {
+                    errors.push(format!("Formatting of {:?} elements panicked", worker.kind));
+                }
+            }
+        }
+        for file_writer in self.file_writers.drain(..) {
+            if let Err(e) = file_writer.join() {
+                errors.push(format!("{:?}", e));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(anyhow!("Failed to write tables:\n{}", errors.join("\n")));
+        }
+        writer.close()
+    }
+}
@@ -0,0 +1,92 @@
+use crate::osm::apidb_dump::write::post_load_options::PostLoadOptions;
+
+/// Options for [ParallelWriter](crate::osm::apidb_dump::write::parallel_writer::ParallelWriter)
+///
+/// The queues between the pipeline stages are bounded, so the memory used by the writer is
+/// bounded by the queue sizes, the buffers and the element ordering buffers, one for each element
+/// type.
+///
+/// Example:
+/// ```
+/// use osm_io::osm::apidb_dump::write::parallel_writer_options::ParallelWriterOptions;
+/// fn example() {
+///     let mut options = ParallelWriterOptions::default();
+///     options.with_element_ordering_buffer_size(1_000_000);
+///     options.with_queue_size(64);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ParallelWriterOptions {
+    element_ordering_buffer_size: usize,
+    batch_size: usize,
+    queue_size: usize,
+    buffer_size: usize,
+    post_load_options: PostLoadOptions,
+}
+
+impl ParallelWriterOptions {
+    /// Get the number of elements of each type buffered to restore the order of the input
+    pub fn element_ordering_buffer_size(&self) -> usize {
+        self.element_ordering_buffer_size
+    }
+
+    /// Set the number of elements of each type buffered to restore the order of the input.
+    /// Recommended: reader_tasks * 8000 * n, 0 if the input is ordered, as from
+    /// [Reader::elements](crate::osm::pbf::reader::Reader::elements)
+    pub fn with_element_ordering_buffer_size(&mut self, element_ordering_buffer_size: usize) {
+        self.element_ordering_buffer_size = element_ordering_buffer_size;
+    }
+
+    /// Get the number of elements sent to the formatting threads at once
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// Set the number of elements sent to the formatting threads at once
+    pub fn with_batch_size(&mut self, batch_size: usize) {
+        self.batch_size = batch_size;
+    }
+
+    /// Get the capacity of the queues in front of the formatting and the file writing threads
+    pub fn queue_size(&self) -> usize {
+        self.queue_size
+    }
+
+    /// Set the capacity of the queues in front of the formatting and the file writing threads
+    pub fn with_queue_size(&mut self, queue_size: usize) {
+        self.queue_size = queue_size;
+    }
+
+    /// Get the size in bytes of the buffer of each table
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Set the size in bytes of the buffer of each table. Each buffer is sent to the file writing
+    /// thread of the table as a single chunk
+    pub fn with_buffer_size(&mut self, buffer_size: usize) {
+        self.buffer_size = buffer_size;
+    }
+
+    /// Get the post load options
+    pub fn post_load_options(&self) -> &PostLoadOptions {
+        &self.post_load_options
+    }
+
+    /// Set the post load options, see [PostLoadOptions]
+    pub fn with_post_load_options(&mut self, post_load_options: PostLoadOptions) {
+        self.post_load_options = post_load_options;
+    }
+}
+
+impl Default for ParallelWriterOptions {
+    fn default() -> Self {
+        ParallelWriterOptions {
+            element_ordering_buffer_size: 4 * 8000 * 32,
+            batch_size: 8000,
+            queue_size: 16,
+            buffer_size: 256 * 1024,
+            post_load_options: PostLoadOptions::default(),
+        }
+    }
+}
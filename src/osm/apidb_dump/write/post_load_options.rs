@@ -0,0 +1,70 @@
+/// Options for the steps that follow the load of the apidb tables
+///
+/// The sequences of the id columns are always set past the largest loaded ids, so that new edits
+/// don't collide with the loaded ones. With `analyze` the populated tables are analyzed, so the
+/// query planner has statistics from the start, and with `reindex` their indexes are rebuilt, which
+/// is only useful when the data is loaded into tables with existing indexes.
+/// [Writer](crate::osm::apidb_dump::write::writer::Writer) sets the sequences in the TOC, where
+/// pg_restore picks them up, and writes the optional steps to post_load.sql in the output
+/// directory, to be run with psql after pg_restore.
+/// [DatabaseWriter](crate::osm::apidb_dump::write::database_writer::DatabaseWriter) runs all the
+/// steps when it is closed.
+///
+/// Example:
+/// ```
+/// use osm_io::osm::apidb_dump::write::post_load_options::PostLoadOptions;
+/// fn example() {
+///     let mut options = PostLoadOptions::default();
+///     options.with_analyze(false);
+///     options.with_reindex(true);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PostLoadOptions {
+    analyze: bool,
+    reindex: bool,
+}
+
+impl PostLoadOptions {
+    /// True if the populated tables are analyzed after the load
+    pub fn analyze(&self) -> bool {
+        self.analyze
+    }
+
+    /// Analyze the populated tables after the load
+    pub fn with_analyze(&mut self, analyze: bool) {
+        self.analyze = analyze;
+    }
+
+    /// True if the indexes of the populated tables are rebuilt after the load
+    pub fn reindex(&self) -> bool {
+        self.reindex
+    }
+
+    /// Rebuild the indexes of the populated tables after the load
+    pub fn with_reindex(&mut self, reindex: bool) {
+        self.reindex = reindex;
+    }
+
+    /// The optional post load statements for the tables, the indexes are rebuilt before the
+    /// tables are analyzed
+    pub(crate) fn statements(&self, table_names: &[&str]) -> Vec<String> {
+        let mut statements = Vec::new();
+        if self.reindex {
+            statements.extend(table_names.iter().map(|table_name| format!("REINDEX TABLE {};\n", table_name)));
+        }
+        if self.analyze {
+            statements.extend(table_names.iter().map(|table_name| format!("ANALYZE {};\n", table_name)));
+        }
+        statements
+    }
+}
+
+impl Default for PostLoadOptions {
+    fn default() -> Self {
+        PostLoadOptions {
+            analyze: true,
+            reindex: false,
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context};
+use postgres::{Client, NoTls};
+use regex::Regex;
+
+static STRUCTURE_SQL: &str = include_str!("../sql/structure.sql");
+
+/// A column of an apidb table
+#[derive(Clone, Debug)]
+pub(crate) struct SchemaColumn {
+    // quoted as in pg_dump output, for example "timestamp"
+    name: String,
+    not_null: bool,
+    has_default: bool,
+}
+
+impl SchemaColumn {
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// True if a row can't be inserted without a value for this column
+    pub(crate) fn is_required(&self) -> bool {
+        self.not_null && !self.has_default
+    }
+}
+
+/// A database object of a structure.sql file, such as a table, an index or a constraint, with
+/// the statement that creates it
+#[derive(Clone, Debug)]
+pub(crate) struct SchemaObject {
+    name: String,
+    kind: String,
+    namespace: Option<String>,
+    definition: String,
+}
+
+impl SchemaObject {
+    /// The name of the object as in pg_dump, for example "acls acls_pkey" for a constraint
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The type of the object as in pg_dump, for example TABLE, INDEX or FK CONSTRAINT
+    pub(crate) fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub(crate) fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+
+    pub(crate) fn definition(&self) -> &str {
+        &self.definition
+    }
+
+    /// The qualified name of the table of the object, None if the object is not related to a table
+    pub(crate) fn table_name(&self) -> Option<String> {
+        let namespace = self.namespace.as_ref()?;
+        match self.kind.as_str() {
+            "TABLE" => {
+                Some(format!("{}.{}", namespace, self.name))
+            }
+            "DEFAULT" | "CONSTRAINT" | "FK CONSTRAINT" | "TRIGGER" => {
+                // the name is "table object"
+                self.name.split(' ').next().map(|table| format!("{}.{}", namespace, table))
+            }
+            "INDEX" => {
+                let re = Regex::new(" ON (?:ONLY )?([^ ]+) ").unwrap();
+                re.captures(&self.definition).map(|captures| captures[1].to_string())
+            }
+            _ => {
+                None
+            }
+        }
+    }
+
+    /// The qualified name of the table referenced by a foreign key constraint
+    pub(crate) fn referenced_table_name(&self) -> Option<String> {
+        let re = Regex::new(" REFERENCES ([^ (]+)").unwrap();
+        re.captures(&self.definition).map(|captures| captures[1].to_string())
+    }
+}
+
+/// A version of the apidb schema of the openstreetmap-website
+///
+/// The writers use the schema to adapt to versions of the apidb schema other than the one they
+/// were built with: the columns the writers don't produce must be nullable or have a default, and
+/// the columns that are missing in the schema are left out of the written rows. The version is
+/// the latest migration recorded in schema_migrations.
+///
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::apidb_dump::write::schema::Schema;
+/// use osm_io::osm::apidb_dump::write::writer::Writer;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let schema = Schema::from_file(&PathBuf::from("./openstreetmap-website/db/structure.sql"))?;
+///     println!("apidb schema version: {:?}", schema.version());
+///     let mut writer = Writer::from_schema(PathBuf::from("./planet-dump"), 0, &schema)?;
+///     writer.close()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Schema {
+    version: Option<String>,
+    tables: HashMap<String, Vec<SchemaColumn>>,
+    objects: Vec<SchemaObject>,
+}
+
+impl Schema {
+    /// The schema the writers were built with
+    pub fn bundled() -> Result<Schema, anyhow::Error> {
+        Self::from_structure_sql(STRUCTURE_SQL)
+    }
+
+    /// Read the schema from a db/structure.sql file of the openstreetmap-website
+    pub fn from_file(path: &Path) -> Result<Schema, anyhow::Error> {
+        let structure_sql = fs::read_to_string(path)
+            .with_context(|| anyhow!("path: {}", path.display()))?;
+        Self::from_structure_sql(&structure_sql)
+    }
+
+    /// Parse the schema from the contents of db/structure.sql, the output of
+    /// `pg_dump --schema-only` followed by the schema_migrations versions
+    pub fn from_structure_sql(structure_sql: &str) -> Result<Schema, anyhow::Error> {
+        // -- Name: acls acls_pkey; Type: CONSTRAINT; Schema: public; Owner: -
+        let header_re = Regex::new("^-- Name: (.+); Type: (.+); Schema: (.+); Owner: (.*)$")?;
+        let mut objects: Vec<SchemaObject> = Vec::new();
+        let mut definition: Option<Vec<&str>> = None;
+        for line in structure_sql.lines() {
+            if let Some(captures) = header_re.captures(line) {
+                Self::complete_object(&mut objects, definition.take());
+                objects.push(
+                    SchemaObject {
+                        name: captures[1].to_string(),
+                        kind: captures[2].to_string(),
+                        namespace: match &captures[3] {
+                            "-" => {
+                                None
+                            }
+                            namespace => {
+                                Some(namespace.to_string())
+                            }
+                        },
+                        definition: String::new(),
+                    }
+                );
+                definition = Some(Vec::new());
+            } else if let Some(lines) = definition.as_mut() {
+                if line.starts_with("--") {
+                    // the end of the header or the start of the next comment
+                    if !lines.is_empty() {
+                        Self::complete_object(&mut objects, definition.take());
+                    }
+                } else if !(lines.is_empty() && line.is_empty()) {
+                    lines.push(line);
+                }
+            }
+        }
+        Self::complete_object(&mut objects, definition.take());
+
+        let mut tables = HashMap::new();
+        for object in &objects {
+            if object.kind() == "TABLE" {
+                tables.insert(object.table_name().unwrap(), Self::parse_columns(object.definition())?);
+            }
+        }
+        for object in &objects {
+            if object.kind() == "DEFAULT" {
+                // -- Name: acls id; Type: DEFAULT
+                let column_name = object.name().split(' ').nth(1)
+                    .ok_or(anyhow!("Invalid DEFAULT name: {}", object.name()))?;
+                if let Some(column) = tables.get_mut(&object.table_name().unwrap())
+                    .and_then(|columns: &mut Vec<SchemaColumn>| columns.iter_mut().find(|column| column.name == column_name)) {
+                    column.has_default = true;
+                }
+            }
+        }
+
+        Ok(
+            Schema {
+                version: Self::parse_version(structure_sql),
+                tables,
+                objects,
+            }
+        )
+    }
+
+    /// Read the schema of the public tables of a live database. The result contains the table
+    /// columns and the version, but not the statements to create the schema
+    ///
+    /// * connection_params - connection string either in the key=value format or as a URL, as
+    ///   accepted by [postgres::Config]
+    pub fn from_database(connection_params: &str) -> Result<Schema, anyhow::Error> {
+        let mut client = Client::connect(connection_params, NoTls)?;
+        let mut tables: HashMap<String, Vec<SchemaColumn>> = HashMap::new();
+        let rows = client.query(
+            "SELECT table_name::text, quote_ident(column_name), is_nullable = 'NO', column_default IS NOT NULL \
+            FROM information_schema.columns WHERE table_schema = 'public' ORDER BY table_name, ordinal_position",
+            &[],
+        )?;
+        for row in rows {
+            let table_name: String = row.get(0);
+            tables.entry(format!("public.{}", table_name))
+                .or_default()
+                .push(
+                    SchemaColumn {
+                        name: row.get(1),
+                        not_null: row.get(2),
+                        has_default: row.get(3),
+                    }
+                );
+        }
+        let versions: Vec<String> = match tables.contains_key("public.schema_migrations") {
+            true => {
+                client.query("SELECT version::text FROM public.schema_migrations", &[])?
+                    .iter()
+                    .map(|row| row.get(0))
+                    .collect()
+            }
+            false => {
+                Vec::new()
+            }
+        };
+        Ok(
+            Schema {
+                version: Self::latest_version(versions.iter().map(|version| version.as_str())),
+                tables,
+                objects: Vec::new(),
+            }
+        )
+    }
+
+    /// The latest migration applied to the schema, None if there are no migrations
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// The columns of a table in the order of the table definition, None if the table is missing
+    pub(crate) fn columns(&self, table_name: &str) -> Option<&Vec<SchemaColumn>> {
+        self.tables.get(table_name)
+    }
+
+    /// The names of the tables of the schema
+    pub(crate) fn table_names(&self) -> Vec<&String> {
+        let mut table_names: Vec<&String> = self.tables.keys().collect();
+        table_names.sort();
+        table_names
+    }
+
+    /// The objects of the schema in the order of the structure.sql
+    pub(crate) fn objects(&self) -> &Vec<SchemaObject> {
+        &self.objects
+    }
+
+    fn complete_object(objects: &mut [SchemaObject], definition: Option<Vec<&str>>) {
+        if let (Some(object), Some(lines)) = (objects.last_mut(), definition) {
+            if object.definition.is_empty() {
+                object.definition = format!("{}\n", lines.join("\n").trim_end());
+            }
+        }
+    }
+
+    fn parse_columns(definition: &str) -> Result<Vec<SchemaColumn>, anyhow::Error> {
+        //     display_name character varying DEFAULT ''::character varying NOT NULL,
+        let column_re = Regex::new("^    (\"[^\"]+\"|[^ ]+) (.+?),?$")?;
+        let mut columns = Vec::new();
+        for line in definition.lines().skip(1) {
+            if line.starts_with("    CONSTRAINT ") {
+                continue;
+            }
+            if let Some(captures) = column_re.captures(line) {
+                columns.push(
+                    SchemaColumn {
+                        name: captures[1].to_string(),
+                        not_null: captures[2].contains(" NOT NULL"),
+                        has_default: captures[2].contains(" DEFAULT ") || captures[2].starts_with("DEFAULT ")
+                            || captures[2].contains(" GENERATED "),
+                    }
+                );
+            }
+        }
+        Ok(columns)
+    }
+
+    fn parse_version(structure_sql: &str) -> Option<String> {
+        // INSERT INTO "schema_migrations" (version) VALUES
+        // ('1'),
+        let start = structure_sql.find("INSERT INTO \"schema_migrations\"")?;
+        let version_re = Regex::new("\\('([0-9]+)'\\)").unwrap();
+        let versions: Vec<&str> = version_re.captures_iter(&structure_sql[start..])
+            .map(|captures| captures.get(1).unwrap().as_str())
+            .collect();
+        Self::latest_version(versions.into_iter())
+    }
+
+    fn latest_version<'a>(versions: impl Iterator<Item = &'a str>) -> Option<String> {
+        versions
+            .filter_map(|version| version.parse::<u64>().ok().map(|number| (number, version)))
+            .max()
+            .map(|(_, version)| version.to_string())
+    }
+}
@@ -0,0 +1,82 @@
+/// The values of the sequences of the id columns of the populated tables, the largest id written
+/// to each table
+///
+/// The website takes the ids of new nodes, ways, relations, changesets and users from these
+/// sequences, so after a load they must continue past the loaded ids.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SequenceValues {
+    current_nodes: i64,
+    current_ways: i64,
+    current_relations: i64,
+    changesets: i64,
+    users: i64,
+}
+
+impl SequenceValues {
+    pub(crate) fn add_node(&mut self, id: i64) {
+        self.current_nodes = self.current_nodes.max(id);
+    }
+
+    pub(crate) fn add_way(&mut self, id: i64) {
+        self.current_ways = self.current_ways.max(id);
+    }
+
+    pub(crate) fn add_relation(&mut self, id: i64) {
+        self.current_relations = self.current_relations.max(id);
+    }
+
+    pub(crate) fn add_changeset(&mut self, id: i64) {
+        self.changesets = self.changesets.max(id);
+    }
+
+    pub(crate) fn add_user(&mut self, id: i64) {
+        self.users = self.users.max(id);
+    }
+
+    pub(crate) fn merge(&mut self, other: &SequenceValues) {
+        self.add_node(other.current_nodes);
+        self.add_way(other.current_ways);
+        self.add_relation(other.current_relations);
+        self.add_changeset(other.changesets);
+        self.add_user(other.users);
+    }
+
+    /// The qualified sequence names with their values, 0 if no ids were written
+    pub(crate) fn values(&self) -> [(&'static str, i64); 5] {
+        [
+            ("public.changesets_id_seq", self.changesets),
+            ("public.current_nodes_id_seq", self.current_nodes),
+            ("public.current_relations_id_seq", self.current_relations),
+            ("public.current_ways_id_seq", self.current_ways),
+            ("public.users_id_seq", self.users),
+        ]
+    }
+
+    /// The value of a sequence, 0 for the sequences of the tables that are not populated
+    pub(crate) fn value(&self, sequence: &str) -> i64 {
+        self.values().iter()
+            .find(|(name, _)| *name == sequence)
+            .map(|(_, value)| *value)
+            .unwrap_or(0)
+    }
+
+    /// The setval statements of the sequences
+    pub(crate) fn setval_statements(&self) -> Vec<String> {
+        self.values().iter()
+            .map(|(sequence, value)| setval_statement(sequence, *value))
+            .collect()
+    }
+}
+
+/// The statement that sets a sequence, as in pg_dump output. The next value follows value, or is 1
+/// if value is 0
+pub(crate) fn setval_statement(sequence: &str, value: i64) -> String {
+    match value > 0 {
+        true => {
+            format!("SELECT pg_catalog.setval('{}', {}, true);\n", sequence, value)
+        }
+        false => {
+            format!("SELECT pg_catalog.setval('{}', 1, false);\n", sequence)
+        }
+    }
+}
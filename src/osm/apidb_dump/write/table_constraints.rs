@@ -0,0 +1,94 @@
+use anyhow::Context;
+use postgres::Client;
+
+/// Definitions of the constraints and indexes dropped before a load, used to rebuild them after
+#[derive(Debug, Default)]
+pub(crate) struct TableConstraints {
+    // (table, constraint name, constraint definition)
+    key_constraints: Vec<(String, String, String)>,
+    foreign_keys: Vec<(String, String, String)>,
+    // (index name, index definition)
+    indexes: Vec<(String, String)>,
+}
+
+impl TableConstraints {
+    /// Drop the foreign keys from and to the tables and, with `drop_indexes`, the key constraints
+    /// and the indexes of the tables
+    pub(crate) fn drop_constraints(client: &mut Client, tables: &[&str], drop_indexes: bool) -> Result<TableConstraints, anyhow::Error> {
+        let mut table_constraints = TableConstraints::default();
+        let rows = client.query(
+            "SELECT conrelid::regclass::text, quote_ident(conname), pg_get_constraintdef(oid), contype::text \
+             FROM pg_constraint \
+             WHERE contype IN ('f', 'p', 'u', 'x') \
+             AND (conrelid = ANY($1::text[]::regclass[]) OR (contype = 'f' AND confrelid = ANY($1::text[]::regclass[]))) \
+             ORDER BY conrelid::regclass::text, conname",
+            &[&tables],
+        )?;
+        for row in rows {
+            let constraint = (row.get::<_, String>(0), row.get::<_, String>(1), row.get::<_, String>(2));
+            match row.get::<_, String>(3).as_str() {
+                "f" => {
+                    table_constraints.foreign_keys.push(constraint);
+                }
+                _ => {
+                    if drop_indexes {
+                        table_constraints.key_constraints.push(constraint);
+                    }
+                }
+            }
+        }
+
+        if drop_indexes {
+            // indexes that do not back a constraint
+            let rows = client.query(
+                "SELECT indexrelid::regclass::text, pg_get_indexdef(indexrelid) \
+                 FROM pg_index \
+                 WHERE indrelid = ANY($1::text[]::regclass[]) \
+                 AND NOT EXISTS (SELECT 1 FROM pg_constraint WHERE conindid = indexrelid) \
+                 ORDER BY indexrelid::regclass::text",
+                &[&tables],
+            )?;
+            for row in rows {
+                table_constraints.indexes.push((row.get(0), row.get(1)));
+            }
+        }
+
+        let mut statements = String::new();
+        for (table, name, _) in table_constraints.foreign_keys.iter().chain(table_constraints.key_constraints.iter()) {
+            statements.push_str(&format!("ALTER TABLE {} DROP CONSTRAINT {};\n", table, name));
+        }
+        for (name, _) in &table_constraints.indexes {
+            statements.push_str(&format!("DROP INDEX {};\n", name));
+        }
+        log::info!("Drop {} foreign keys, {} key constraints and {} indexes",
+            table_constraints.foreign_keys.len(),
+            table_constraints.key_constraints.len(),
+            table_constraints.indexes.len(),
+        );
+        let mut transaction = client.transaction()?;
+        transaction.batch_execute(&statements)?;
+        transaction.commit()?;
+        Ok(table_constraints)
+    }
+
+    /// Rebuild the dropped indexes, key constraints and foreign keys, in that order
+    pub(crate) fn rebuild(&self, client: &mut Client) -> Result<(), anyhow::Error> {
+        let mut statements = String::new();
+        for (_, definition) in &self.indexes {
+            statements.push_str(&format!("{};\n", definition));
+        }
+        for (table, name, definition) in self.key_constraints.iter().chain(self.foreign_keys.iter()) {
+            statements.push_str(&format!("ALTER TABLE {} ADD CONSTRAINT {} {};\n", table, name, definition));
+        }
+        log::info!("Rebuild {} indexes, {} key constraints and {} foreign keys",
+            self.indexes.len(),
+            self.key_constraints.len(),
+            self.foreign_keys.len(),
+        );
+        let mut transaction = client.transaction()?;
+        transaction.batch_execute(&statements)
+            .with_context(|| format!("Failed to rebuild the constraints and indexes:\n{}", statements))?;
+        transaction.commit()?;
+        Ok(())
+    }
+}
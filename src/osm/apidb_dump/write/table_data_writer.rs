@@ -2,34 +2,151 @@ use std::borrow::BorrowMut;
 use std::fmt::{Debug, Formatter};
 use std::fs::File;
 use std::io::{BufWriter, Write};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
-use anyhow::Context;
+use anyhow::{anyhow, Context};
+use flate2::Compression;
+use flate2::write::GzEncoder;
 
+use crate::osm::apidb_dump::write::column_projection::ColumnProjection;
+
+/// The destination of the table rows. Gzip streams must be finished explicitly to report errors
+/// writing the trailer
+pub(crate) enum TableDataSink {
+    Plain(Box<dyn Write + Send>),
+    Gzip(GzEncoder<File>),
+    Projected(Box<ColumnProjection<TableDataSink>>),
+}
+
+impl TableDataSink {
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            TableDataSink::Plain(mut sink) => {
+                sink.flush()
+            }
+            TableDataSink::Gzip(encoder) => {
+                encoder.finish()?.flush()
+            }
+            TableDataSink::Projected(projection) => {
+                projection.into_inner()?.finish()
+            }
+        }
+    }
+}
+
+impl Write for TableDataSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            TableDataSink::Plain(sink) => {
+                sink.write(buf)
+            }
+            TableDataSink::Gzip(encoder) => {
+                encoder.write(buf)
+            }
+            TableDataSink::Projected(projection) => {
+                projection.write(buf)
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            TableDataSink::Plain(sink) => {
+                sink.flush()
+            }
+            TableDataSink::Gzip(encoder) => {
+                encoder.flush()
+            }
+            TableDataSink::Projected(projection) => {
+                projection.flush()
+            }
+        }
+    }
+}
+
+/// Table rows in the COPY text format written to a sink. The sink is either a table data file of
+/// a pg_restore directory or a COPY FROM STDIN stream into a live database
 pub(crate) struct TableDataWriter {
     table_name: String,
-    file_path: PathBuf,
-    writer: BufWriter<File>,
+    sink_name: String,
+    writer: BufWriter<TableDataSink>,
+    // the end of data marker is required in table data files, but not in COPY streams
+    footer: bool,
 }
 
 impl TableDataWriter {
-    pub(crate) fn new(table_name: String, file_name: String, output_path: &Path) -> Result<TableDataWriter, anyhow::Error> {
-        let file_path = output_path.join(file_name);
-        let writer = BufWriter::new(File::create(&file_path)?);
+    /// Create a writer of a table data file
+    ///
+    /// * compression_level - 0 for a plain file_name, otherwise the gzip compression level of
+    ///   file_name.gz, with -1 for the default level, as in pg_dump
+    pub(crate) fn new(table_name: String, file_name: String, output_path: &Path, compression_level: i8) -> Result<TableDataWriter, anyhow::Error> {
+        let file_path = match compression_level {
+            0 => {
+                output_path.join(file_name)
+            }
+            _ => {
+                output_path.join(format!("{}.gz", file_name))
+            }
+        };
+        let file = File::create(&file_path)
+            .with_context(|| format!("Problem creating table data file {:?}", file_path))?;
+        let sink = match compression_level {
+            0 => {
+                TableDataSink::Plain(Box::new(file))
+            }
+            -1 => {
+                TableDataSink::Gzip(GzEncoder::new(file, Compression::default()))
+            }
+            1..=9 => {
+                TableDataSink::Gzip(GzEncoder::new(file, Compression::new(compression_level as u32)))
+            }
+            _ => {
+                return Err(anyhow!("Invalid compression level: {}", compression_level));
+            }
+        };
         Ok(TableDataWriter {
             table_name,
-            file_path,
-            writer,
+            sink_name: file_path.display().to_string(),
+            writer: BufWriter::new(sink),
+            footer: true,
         })
     }
 
+    pub(crate) fn from_sink(table_name: String, sink_name: String, sink: Box<dyn Write + Send>, capacity: usize) -> TableDataWriter {
+        TableDataWriter {
+            table_name,
+            sink_name,
+            writer: BufWriter::with_capacity(capacity, TableDataSink::Plain(sink)),
+            footer: false,
+        }
+    }
+
+    /// Write only the given columns of the rows, see [ColumnProjection]. Must be called before
+    /// writing the first row
+    pub(crate) fn project(&mut self, columns: Vec<usize>) -> Result<(), anyhow::Error> {
+        let capacity = self.writer.capacity();
+        let writer = std::mem::replace(&mut self.writer, BufWriter::new(TableDataSink::Plain(Box::new(std::io::sink()))));
+        let sink = writer.into_inner()
+            .map_err(|e| e.into_error())
+            .with_context(|| format!("Problem flushing table data {}", self.sink_name))?;
+        self.writer = BufWriter::with_capacity(capacity, TableDataSink::Projected(Box::new(ColumnProjection::new(columns, sink))));
+        Ok(())
+    }
+
     pub(crate) fn close(&mut self) -> Result<(), anyhow::Error> {
-        self.writer.write_all("\\.\n".as_bytes()).with_context(|| format!("Problem writing table data footer: {:?}", self.file_path))?;
-        self.writer.flush().with_context(|| format!("Problem flushing table data file {:?}", self.file_path))?;
+        if self.footer {
+            self.writer.write_all("\\.\n".as_bytes()).with_context(|| format!("Problem writing table data footer: {}", self.sink_name))?;
+        }
+        // release the sink, which signals the end of data to a COPY stream
+        let writer = std::mem::replace(&mut self.writer, BufWriter::new(TableDataSink::Plain(Box::new(std::io::sink()))));
+        writer.into_inner()
+            .map_err(|e| e.into_error())
+            .and_then(|sink| sink.finish())
+            .with_context(|| format!("Problem flushing table data {}", self.sink_name))?;
         Ok(())
     }
 
-    pub(crate) fn writer(&mut self) -> &mut BufWriter<File> {
+    pub(crate) fn writer(&mut self) -> &mut BufWriter<TableDataSink> {
         self.writer.borrow_mut()
     }
 
@@ -37,13 +154,13 @@ impl TableDataWriter {
         &self.table_name
     }
 
-    pub(crate) fn file_path(&self) -> &PathBuf {
-        &self.file_path
+    pub(crate) fn sink_name(&self) -> &str {
+        &self.sink_name
     }
 }
 
 impl Debug for TableDataWriter {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "table: {}, file: {}", self.table_name(), self.file_path().display())
+        write!(f, "table: {}, sink: {}", self.table_name(), self.sink_name())
     }
-}
\ No newline at end of file
+}
@@ -4,6 +4,7 @@ use std::path::Path;
 use json::JsonValue;
 use transient_btree_index::{BtreeConfig, BtreeIndex};
 
+use crate::osm::apidb_dump::write::changeset_summary::ChangesetSummary;
 use crate::osm::apidb_dump::write::table_data_writer::TableDataWriter;
 
 pub(crate) struct TableDataWriters {
@@ -62,78 +63,90 @@ pub(crate) struct TableDataWriters {
     pub(crate) ways: TableDataWriter,
 
     pub(crate) user_index: BtreeIndex<i64, String>,
-    pub(crate) changeset_user_index: BtreeIndex<i64, i64>,
+    pub(crate) changeset_index: BtreeIndex<i64, ChangesetSummary>,
+    // changeset id -> (user id, the changesets columns following user_id, changeset_tags lines) of
+    // the supplied changeset metadata, the user id is -1 if the metadata has no uid
+    pub(crate) changeset_metadata_index: BtreeIndex<i64, (i64, String, String)>,
 
     pub(crate) user_index_buffer: HashMap<i64, String>,
-    pub(crate) changeset_user_index_buffer: HashMap<i64, i64>,
+    pub(crate) changeset_buffer: HashMap<i64, ChangesetSummary>,
 }
 
 impl TableDataWriters {
-    pub(crate) fn new(template_mapping: JsonValue, output_path: &Path) -> Result<Self, anyhow::Error> {
+    pub(crate) fn new(template_mapping: JsonValue, output_path: &Path, compression_level: i8) -> Result<Self, anyhow::Error> {
+        Self::from_factory(|table_name| {
+            TableDataWriter::new(table_name.to_string(), template_mapping[table_name].to_string(), output_path, compression_level)
+        })
+    }
+
+    /// Create the table data writers using `create` to open the sink for each table
+    pub(crate) fn from_factory(mut create: impl FnMut(&str) -> Result<TableDataWriter, anyhow::Error>) -> Result<Self, anyhow::Error> {
         let user_index = BtreeIndex::<i64, String>::with_capacity(BtreeConfig::default(), 0)?;
-        let changeset_user_index = BtreeIndex::<i64, i64>::with_capacity(BtreeConfig::default(), 0)?;
+        let changeset_index = BtreeIndex::<i64, ChangesetSummary>::with_capacity(BtreeConfig::default(), 0)?;
+        let changeset_metadata_index = BtreeIndex::<i64, (i64, String, String)>::with_capacity(BtreeConfig::default(), 0)?;
         let user_index_buffer = HashMap::<i64, String>::new();
-        let changeset_user_index_buffer = HashMap::<i64, i64>::new();
+        let changeset_buffer = HashMap::<i64, ChangesetSummary>::new();
 
         Ok(TableDataWriters {
-            acls: TableDataWriter::new("public.acls".to_string(), template_mapping["public.acls"].to_string(), output_path).unwrap(),
-            active_storage_attachments: TableDataWriter::new("public.active_storage_attachments".to_string(), template_mapping["public.active_storage_attachments"].to_string(), output_path).unwrap(),
-            active_storage_blobs: TableDataWriter::new("public.active_storage_blobs".to_string(), template_mapping["public.active_storage_blobs"].to_string(), output_path).unwrap(),
-            active_storage_variant_records: TableDataWriter::new("public.active_storage_variant_records".to_string(), template_mapping["public.active_storage_variant_records"].to_string(), output_path).unwrap(),
-            ar_internal_metadata: TableDataWriter::new("public.ar_internal_metadata".to_string(), template_mapping["public.ar_internal_metadata"].to_string(), output_path).unwrap(),
-            changeset_comments: TableDataWriter::new("public.changeset_comments".to_string(), template_mapping["public.changeset_comments"].to_string(), output_path).unwrap(),
-            changeset_tags: TableDataWriter::new("public.changeset_tags".to_string(), template_mapping["public.changeset_tags"].to_string(), output_path).unwrap(),
-            changesets: TableDataWriter::new("public.changesets".to_string(), template_mapping["public.changesets"].to_string(), output_path).unwrap(),
-            changesets_subscribers: TableDataWriter::new("public.changesets_subscribers".to_string(), template_mapping["public.changesets_subscribers"].to_string(), output_path).unwrap(),
-            client_applications: TableDataWriter::new("public.client_applications".to_string(), template_mapping["public.client_applications"].to_string(), output_path).unwrap(),
-            current_node_tags: TableDataWriter::new("public.current_node_tags".to_string(), template_mapping["public.current_node_tags"].to_string(), output_path).unwrap(),
-            current_nodes: TableDataWriter::new("public.current_nodes".to_string(), template_mapping["public.current_nodes"].to_string(), output_path).unwrap(),
-            current_relation_members: TableDataWriter::new("public.current_relation_members".to_string(), template_mapping["public.current_relation_members"].to_string(), output_path).unwrap(),
-            current_relation_tags: TableDataWriter::new("public.current_relation_tags".to_string(), template_mapping["public.current_relation_tags"].to_string(), output_path).unwrap(),
-            current_relations: TableDataWriter::new("public.current_relations".to_string(), template_mapping["public.current_relations"].to_string(), output_path).unwrap(),
-            current_way_nodes: TableDataWriter::new("public.current_way_nodes".to_string(), template_mapping["public.current_way_nodes"].to_string(), output_path).unwrap(),
-            current_way_tags: TableDataWriter::new("public.current_way_tags".to_string(), template_mapping["public.current_way_tags"].to_string(), output_path).unwrap(),
-            current_ways: TableDataWriter::new("public.current_ways".to_string(), template_mapping["public.current_ways"].to_string(), output_path).unwrap(),
-            delayed_jobs: TableDataWriter::new("public.delayed_jobs".to_string(), template_mapping["public.delayed_jobs"].to_string(), output_path).unwrap(),
-            diary_comments: TableDataWriter::new("public.diary_comments".to_string(), template_mapping["public.diary_comments"].to_string(), output_path).unwrap(),
-            diary_entries: TableDataWriter::new("public.diary_entries".to_string(), template_mapping["public.diary_entries"].to_string(), output_path).unwrap(),
-            diary_entry_subscriptions: TableDataWriter::new("public.diary_entry_subscriptions".to_string(), template_mapping["public.diary_entry_subscriptions"].to_string(), output_path).unwrap(),
-            friends: TableDataWriter::new("public.friends".to_string(), template_mapping["public.friends"].to_string(), output_path).unwrap(),
-            gps_points: TableDataWriter::new("public.gps_points".to_string(), template_mapping["public.gps_points"].to_string(), output_path).unwrap(),
-            gpx_file_tags: TableDataWriter::new("public.gpx_file_tags".to_string(), template_mapping["public.gpx_file_tags"].to_string(), output_path).unwrap(),
-            gpx_files: TableDataWriter::new("public.gpx_files".to_string(), template_mapping["public.gpx_files"].to_string(), output_path).unwrap(),
-            issue_comments: TableDataWriter::new("public.issue_comments".to_string(), template_mapping["public.issue_comments"].to_string(), output_path).unwrap(),
-            issues: TableDataWriter::new("public.issues".to_string(), template_mapping["public.issues"].to_string(), output_path).unwrap(),
-            languages: TableDataWriter::new("public.languages".to_string(), template_mapping["public.languages"].to_string(), output_path).unwrap(),
-            messages: TableDataWriter::new("public.messages".to_string(), template_mapping["public.messages"].to_string(), output_path).unwrap(),
-            node_tags: TableDataWriter::new("public.node_tags".to_string(), template_mapping["public.node_tags"].to_string(), output_path).unwrap(),
-            nodes: TableDataWriter::new("public.nodes".to_string(), template_mapping["public.nodes"].to_string(), output_path).unwrap(),
-            note_comments: TableDataWriter::new("public.note_comments".to_string(), template_mapping["public.note_comments"].to_string(), output_path).unwrap(),
-            notes: TableDataWriter::new("public.notes".to_string(), template_mapping["public.notes"].to_string(), output_path).unwrap(),
-            oauth_access_grants: TableDataWriter::new("public.oauth_access_grants".to_string(), template_mapping["public.oauth_access_grants"].to_string(), output_path).unwrap(),
-            oauth_access_tokens: TableDataWriter::new("public.oauth_access_tokens".to_string(), template_mapping["public.oauth_access_tokens"].to_string(), output_path).unwrap(),
-            oauth_applications: TableDataWriter::new("public.oauth_applications".to_string(), template_mapping["public.oauth_applications"].to_string(), output_path).unwrap(),
-            oauth_nonces: TableDataWriter::new("public.oauth_nonces".to_string(), template_mapping["public.oauth_nonces"].to_string(), output_path).unwrap(),
-            oauth_tokens: TableDataWriter::new("public.oauth_tokens".to_string(), template_mapping["public.oauth_tokens"].to_string(), output_path).unwrap(),
-            redactions: TableDataWriter::new("public.redactions".to_string(), template_mapping["public.redactions"].to_string(), output_path).unwrap(),
-            relation_members: TableDataWriter::new("public.relation_members".to_string(), template_mapping["public.relation_members"].to_string(), output_path).unwrap(),
-            relation_tags: TableDataWriter::new("public.relation_tags".to_string(), template_mapping["public.relation_tags"].to_string(), output_path).unwrap(),
-            relations: TableDataWriter::new("public.relations".to_string(), template_mapping["public.relations"].to_string(), output_path).unwrap(),
-            reports: TableDataWriter::new("public.reports".to_string(), template_mapping["public.reports"].to_string(), output_path).unwrap(),
-            schema_migrations: TableDataWriter::new("public.schema_migrations".to_string(), template_mapping["public.schema_migrations"].to_string(), output_path).unwrap(),
-            user_blocks: TableDataWriter::new("public.user_blocks".to_string(), template_mapping["public.user_blocks"].to_string(), output_path).unwrap(),
-            user_preferences: TableDataWriter::new("public.user_preferences".to_string(), template_mapping["public.user_preferences"].to_string(), output_path).unwrap(),
-            user_roles: TableDataWriter::new("public.user_roles".to_string(), template_mapping["public.user_roles"].to_string(), output_path).unwrap(),
-            user_tokens: TableDataWriter::new("public.user_tokens".to_string(), template_mapping["public.user_tokens"].to_string(), output_path).unwrap(),
-            users: TableDataWriter::new("public.users".to_string(), template_mapping["public.users"].to_string(), output_path).unwrap(),
-            way_nodes: TableDataWriter::new("public.way_nodes".to_string(), template_mapping["public.way_nodes"].to_string(), output_path).unwrap(),
-            way_tags: TableDataWriter::new("public.way_tags".to_string(), template_mapping["public.way_tags"].to_string(), output_path).unwrap(),
-            ways: TableDataWriter::new("public.ways".to_string(), template_mapping["public.ways"].to_string(), output_path).unwrap(),
+            acls: create("public.acls")?,
+            active_storage_attachments: create("public.active_storage_attachments")?,
+            active_storage_blobs: create("public.active_storage_blobs")?,
+            active_storage_variant_records: create("public.active_storage_variant_records")?,
+            ar_internal_metadata: create("public.ar_internal_metadata")?,
+            changeset_comments: create("public.changeset_comments")?,
+            changeset_tags: create("public.changeset_tags")?,
+            changesets: create("public.changesets")?,
+            changesets_subscribers: create("public.changesets_subscribers")?,
+            client_applications: create("public.client_applications")?,
+            current_node_tags: create("public.current_node_tags")?,
+            current_nodes: create("public.current_nodes")?,
+            current_relation_members: create("public.current_relation_members")?,
+            current_relation_tags: create("public.current_relation_tags")?,
+            current_relations: create("public.current_relations")?,
+            current_way_nodes: create("public.current_way_nodes")?,
+            current_way_tags: create("public.current_way_tags")?,
+            current_ways: create("public.current_ways")?,
+            delayed_jobs: create("public.delayed_jobs")?,
+            diary_comments: create("public.diary_comments")?,
+            diary_entries: create("public.diary_entries")?,
+            diary_entry_subscriptions: create("public.diary_entry_subscriptions")?,
+            friends: create("public.friends")?,
+            gps_points: create("public.gps_points")?,
+            gpx_file_tags: create("public.gpx_file_tags")?,
+            gpx_files: create("public.gpx_files")?,
+            issue_comments: create("public.issue_comments")?,
+            issues: create("public.issues")?,
+            languages: create("public.languages")?,
+            messages: create("public.messages")?,
+            node_tags: create("public.node_tags")?,
+            nodes: create("public.nodes")?,
+            note_comments: create("public.note_comments")?,
+            notes: create("public.notes")?,
+            oauth_access_grants: create("public.oauth_access_grants")?,
+            oauth_access_tokens: create("public.oauth_access_tokens")?,
+            oauth_applications: create("public.oauth_applications")?,
+            oauth_nonces: create("public.oauth_nonces")?,
+            oauth_tokens: create("public.oauth_tokens")?,
+            redactions: create("public.redactions")?,
+            relation_members: create("public.relation_members")?,
+            relation_tags: create("public.relation_tags")?,
+            relations: create("public.relations")?,
+            reports: create("public.reports")?,
+            schema_migrations: create("public.schema_migrations")?,
+            user_blocks: create("public.user_blocks")?,
+            user_preferences: create("public.user_preferences")?,
+            user_roles: create("public.user_roles")?,
+            user_tokens: create("public.user_tokens")?,
+            users: create("public.users")?,
+            way_nodes: create("public.way_nodes")?,
+            way_tags: create("public.way_tags")?,
+            ways: create("public.ways")?,
 
             user_index,
-            changeset_user_index,
+            changeset_index,
+            changeset_metadata_index,
             user_index_buffer,
-            changeset_user_index_buffer,
+            changeset_buffer,
         })
     }
 
@@ -143,10 +156,12 @@ impl TableDataWriters {
         });
         self.user_index_buffer.clear();
 
-        self.changeset_user_index_buffer.iter().for_each(|(changeset_id, user_id)| {
-            self.changeset_user_index.insert(*changeset_id, *user_id).unwrap();
-        });
-        self.changeset_user_index_buffer.clear();
+        for (changeset_id, mut changeset_summary) in self.changeset_buffer.drain() {
+            if let Some(indexed_changeset_summary) = self.changeset_index.get(&changeset_id)? {
+                changeset_summary.merge(&indexed_changeset_summary);
+            }
+            self.changeset_index.insert(changeset_id, changeset_summary)?;
+        }
         Ok(())
     }
 
@@ -0,0 +1,76 @@
+use std::io::{ErrorKind, Write};
+use std::sync::mpsc::{Receiver, sync_channel, SyncSender};
+use std::thread;
+use std::thread::JoinHandle;
+
+use anyhow::{anyhow, Context};
+
+use crate::osm::apidb_dump::write::table_data_writer::TableDataWriter;
+
+/// Write a single table data file, including its compression, on a dedicated thread
+///
+/// The rows are received through a [TableFileSink]. The file is completed when all sinks are
+/// dropped.
+pub(crate) struct TableFileWriter {
+    table_name: String,
+    handle: JoinHandle<Result<(), anyhow::Error>>,
+}
+
+impl TableFileWriter {
+    pub(crate) fn start(table_data_writer: TableDataWriter, queue_size: usize) -> Result<(TableFileWriter, TableFileSink), anyhow::Error> {
+        let (sender, receiver) = sync_channel::<Vec<u8>>(queue_size);
+        let table_name = table_data_writer.table_name().to_string();
+        let handle = thread::Builder::new()
+            .name(format!("write-{}", table_name))
+            .spawn(move || {
+                Self::write(table_data_writer, receiver)
+            })?;
+        Ok(
+            (
+                TableFileWriter {
+                    table_name: table_name.clone(),
+                    handle,
+                },
+                TableFileSink {
+                    table_name,
+                    sender,
+                },
+            )
+        )
+    }
+
+    /// Wait for the file to be completed
+    ///
+    /// Must be called after all sinks of the writer are dropped
+    pub(crate) fn join(self) -> Result<(), anyhow::Error> {
+        self.handle.join()
+            .map_err(|_| anyhow!("Writer of {} panicked", self.table_name))?
+            .with_context(|| format!("Failed to write {}", self.table_name))
+    }
+
+    fn write(mut table_data_writer: TableDataWriter, receiver: Receiver<Vec<u8>>) -> Result<(), anyhow::Error> {
+        for data in receiver {
+            table_data_writer.writer().write_all(&data)
+                .with_context(|| format!("Problem writing table data {}", table_data_writer.sink_name()))?;
+        }
+        table_data_writer.close()
+    }
+}
+
+/// The writing end of a [TableFileWriter]
+pub(crate) struct TableFileSink {
+    table_name: String,
+    sender: SyncSender<Vec<u8>>,
+}
+
+impl Write for TableFileSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.sender.send(buf.to_vec())
+            .map_err(|_| std::io::Error::new(ErrorKind::BrokenPipe, format!("Writer of {} stopped", self.table_name)))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+
+use crate::osm::apidb_dump::write::schema::Schema;
+use crate::osm::apidb_dump::write::toc::load_template_columns;
+
+/// The tables populated by the writers. The other tables are written empty
+pub(crate) const POPULATED_TABLES: [&str; 19] = [
+    "public.changeset_tags",
+    "public.changesets",
+    "public.current_node_tags",
+    "public.current_nodes",
+    "public.current_relation_members",
+    "public.current_relation_tags",
+    "public.current_relations",
+    "public.current_way_nodes",
+    "public.current_way_tags",
+    "public.current_ways",
+    "public.node_tags",
+    "public.nodes",
+    "public.relation_members",
+    "public.relation_tags",
+    "public.relations",
+    "public.users",
+    "public.way_nodes",
+    "public.way_tags",
+    "public.ways",
+];
+
+/// The columns of the rows written to a table of a particular schema
+///
+/// The writers format the rows with the columns of the bundled template. The columns that are
+/// missing in the schema are projected out of the rows.
+#[derive(Clone, Debug)]
+pub(crate) struct TableLayout {
+    table_name: String,
+    columns: Vec<String>,
+    // the template columns of the written row, by position, if they differ from the template
+    projection: Option<Vec<usize>>,
+}
+
+impl TableLayout {
+    /// The layouts of all tables of schema. Fails if the rows of a populated table can't be
+    /// loaded into the schema
+    pub(crate) fn for_schema(schema: &Schema) -> Result<HashMap<String, TableLayout>, anyhow::Error> {
+        let template_columns = load_template_columns()?;
+        let mut layouts = HashMap::new();
+        for table_name in schema.table_names() {
+            let schema_columns = schema.columns(table_name).unwrap();
+            let layout = match (POPULATED_TABLES.contains(&table_name.as_str()), template_columns.get(table_name)) {
+                (true, Some(template_columns)) => {
+                    if let Some(column) = schema_columns.iter()
+                        .find(|column| column.is_required() && !template_columns.iter().any(|name| name == column.name())) {
+                        return Err(
+                            anyhow!(
+                                "Column {} of {} in apidb schema version {} requires a value that is not written",
+                                column.name(),
+                                table_name,
+                                schema.version().unwrap_or("unknown"),
+                            )
+                        );
+                    }
+                    let projection: Vec<usize> = template_columns.iter()
+                        .enumerate()
+                        .filter(|(_, name)| schema_columns.iter().any(|column| column.name() == *name))
+                        .map(|(i, _)| i)
+                        .collect();
+                    TableLayout {
+                        table_name: table_name.clone(),
+                        columns: projection.iter().map(|i| template_columns[*i].clone()).collect(),
+                        projection: match projection.len() == template_columns.len() {
+                            true => {
+                                None
+                            }
+                            false => {
+                                Some(projection)
+                            }
+                        },
+                    }
+                }
+                _ => {
+                    // written empty
+                    TableLayout {
+                        table_name: table_name.clone(),
+                        columns: schema_columns.iter().map(|column| column.name().to_string()).collect(),
+                        projection: None,
+                    }
+                }
+            };
+            layouts.insert(table_name.clone(), layout);
+        }
+        if let Some(table_name) = POPULATED_TABLES.iter().find(|table_name| !layouts.contains_key(**table_name)) {
+            return Err(anyhow!("Table {} is missing in apidb schema version {}", table_name, schema.version().unwrap_or("unknown")));
+        }
+        Ok(layouts)
+    }
+
+    pub(crate) fn columns(&self) -> &Vec<String> {
+        &self.columns
+    }
+
+    pub(crate) fn projection(&self) -> Option<&Vec<usize>> {
+        self.projection.as_ref()
+    }
+
+    /// COPY public.node_tags (node_id, version, k, v) FROM STDIN
+    pub(crate) fn copy_statement(&self) -> String {
+        format!("COPY {} ({}) FROM STDIN", self.table_name, self.columns.join(", "))
+    }
+}
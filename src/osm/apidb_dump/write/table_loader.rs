@@ -0,0 +1,142 @@
+use std::io::{ErrorKind, Write};
+use std::sync::mpsc::{channel, Receiver, Sender, sync_channel, SyncSender};
+use std::thread;
+use std::thread::JoinHandle;
+
+use anyhow::{anyhow, Context};
+use postgres::{Client, NoTls};
+
+use crate::osm::apidb_dump::write::commit_policy::CommitPolicy;
+
+// the number of buffers queued for each table before the writer blocks
+const QUEUE_SIZE: usize = 16;
+
+/// Load the rows of a single table with COPY FROM STDIN on a dedicated connection and thread
+///
+/// The rows are received through a [CopySink]. The connection is opened when the first rows
+/// arrive, so tables that are never written do not use a connection. The last transaction is
+/// committed only if the load is completed with [TableLoader::join], otherwise it is rolled back.
+pub(crate) struct TableLoader {
+    table_name: String,
+    handle: JoinHandle<Result<u64, anyhow::Error>>,
+    completion: Sender<()>,
+}
+
+impl TableLoader {
+    pub(crate) fn start(connection_params: &str, table_name: &str, copy_statement: &str, commit_policy: CommitPolicy) -> Result<(TableLoader, CopySink), anyhow::Error> {
+        let (sender, receiver) = sync_channel::<Vec<u8>>(QUEUE_SIZE);
+        let (completion, completion_receiver) = channel::<()>();
+        let connection_params = connection_params.to_string();
+        let copy_statement = copy_statement.to_string();
+        let handle = thread::Builder::new()
+            .name(format!("load-{}", table_name))
+            .spawn(move || {
+                Self::load(&connection_params, &copy_statement, &commit_policy, receiver, completion_receiver)
+            })?;
+        Ok(
+            (
+                TableLoader {
+                    table_name: table_name.to_string(),
+                    handle,
+                    completion,
+                },
+                CopySink {
+                    table_name: table_name.to_string(),
+                    sender,
+                },
+            )
+        )
+    }
+
+    /// Wait for the load to complete and return the number of loaded rows. With `commit` false the
+    /// last transaction is rolled back.
+    ///
+    /// Must be called after all sinks of the loader are dropped
+    pub(crate) fn join(self, commit: bool) -> Result<u64, anyhow::Error> {
+        if commit {
+            // fails only if the loader already stopped with an error, which is reported below
+            self.completion.send(()).ok();
+        }
+        drop(self.completion);
+        let rows = self.handle.join()
+            .map_err(|_| anyhow!("Loader of {} panicked", self.table_name))?
+            .with_context(|| format!("Failed to load {}", self.table_name))?;
+        log::info!("Loaded {} rows into {}", rows, self.table_name);
+        Ok(rows)
+    }
+
+    fn load(connection_params: &str, copy_statement: &str, commit_policy: &CommitPolicy, receiver: Receiver<Vec<u8>>, completion: Receiver<()>) -> Result<u64, anyhow::Error> {
+        let mut client: Option<Client> = None;
+        let mut pending: Option<Vec<u8>> = None;
+        let mut total_rows = 0_u64;
+        loop {
+            let first = match pending.take() {
+                Some(data) => data,
+                None => {
+                    match receiver.recv() {
+                        Ok(data) => data,
+                        Err(_) => break,
+                    }
+                }
+            };
+            if client.is_none() {
+                client = Some(Client::connect(connection_params, NoTls)?);
+            }
+            let mut transaction = client.as_mut().unwrap().transaction()?;
+            let mut writer = transaction.copy_in(copy_statement)?;
+            let mut rows = 0_usize;
+            let mut batch_full = false;
+            let mut next = Some(first);
+            while let Some(mut data) = next.take().or_else(|| receiver.recv().ok()) {
+                if let CommitPolicy::EveryRows(limit) = commit_policy {
+                    // rows are terminated by a new line, which is always escaped inside values
+                    let split = data.iter()
+                        .enumerate()
+                        .filter(|(_, b)| **b == b'\n')
+                        .nth(limit.saturating_sub(rows).saturating_sub(1))
+                        .map(|(i, _)| i + 1);
+                    if let Some(split) = split {
+                        let rest = data.split_off(split);
+                        writer.write_all(&data)?;
+                        if !rest.is_empty() {
+                            pending = Some(rest);
+                        }
+                        batch_full = true;
+                        break;
+                    }
+                    rows += data.iter().filter(|b| **b == b'\n').count();
+                }
+                writer.write_all(&data)?;
+            }
+            if !batch_full && completion.recv().is_err() {
+                // the stream ended without completing the load, roll back
+                return Err(anyhow!("The load was not completed"));
+            }
+            total_rows += writer.finish()?;
+            transaction.commit()?;
+            if !batch_full {
+                // the stream ended
+                break;
+            }
+        }
+        Ok(total_rows)
+    }
+}
+
+/// The writing end of a [TableLoader]
+pub(crate) struct CopySink {
+    table_name: String,
+    sender: SyncSender<Vec<u8>>,
+}
+
+impl Write for CopySink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.sender.send(buf.to_vec())
+            .map_err(|_| std::io::Error::new(ErrorKind::BrokenPipe, format!("Loader of {} stopped", self.table_name)))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
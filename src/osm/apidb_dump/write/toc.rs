@@ -1,14 +1,88 @@
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use anyhow::Context;
+use std::path::{Path, PathBuf};
+use anyhow::{anyhow, Context};
+use chrono::{Datelike, Timelike};
 use json::JsonValue;
+use regex::Regex;
+
+use crate::osm::apidb_dump::write::schema::{Schema, SchemaObject};
+use crate::osm::apidb_dump::write::sequence_values::{SequenceValues, setval_statement};
+use crate::osm::apidb_dump::write::table_layout::TableLayout;
+use crate::osm::apidb_dump::write::toc_entry::{read_str, TocEntry, TocSection, write_int, write_str};
 
 static TOC: &[u8] = include_bytes!("./toc/toc.dat");
 static MAPPING: &str = include_str!("./toc/mapping.json");
 
-pub(crate) fn write_toc(path: &PathBuf) -> Result<(), anyhow::Error> {
+/// The TOC of a dump directory, written when the dump is created and written again with the
+/// sequence values when the dump is closed
+#[derive(Clone, Debug)]
+pub(crate) enum DumpToc {
+    Template,
+    Schema {
+        schema: Schema,
+        layouts: HashMap<String, TableLayout>,
+    },
+}
+
+impl DumpToc {
+    pub(crate) fn write(&self, path: &PathBuf, compression_level: i8, sequence_values: &SequenceValues) -> Result<(), anyhow::Error> {
+        match self {
+            DumpToc::Template => {
+                write_toc(path, compression_level, sequence_values)
+            }
+            DumpToc::Schema { schema, layouts } => {
+                write_schema_toc(path, compression_level, schema, layouts, sequence_values)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Write the template TOC, with the compression of the table data files set in the header and the
+/// SEQUENCE SET entries of the populated tables set to sequence_values
+pub(crate) fn write_toc(path: &PathBuf, compression_level: i8, sequence_values: &SequenceValues) -> Result<(), anyhow::Error> {
     let toc_path = path.join(PathBuf::from("toc.dat"));
-    fs::write(&toc_path, TOC).with_context(|| format!("write {:?} to {:?}", &toc_path, path))?;
+    let mut toc = TOC.to_vec();
+    set_compression(&mut toc, compression_level)?;
+    for (sequence, value) in sequence_values.values() {
+        set_sequence(&mut toc, sequence, value)?;
+    }
+    fs::write(&toc_path, toc).with_context(|| format!("write {:?} to {:?}", &toc_path, path))?;
+    Ok(())
+}
+
+/// Set the compression level in the header of an archive version 1.14 TOC
+///
+/// The header is the "PGDMP" magic, the version major, minor and revision bytes, the int size, the
+/// offset size and the format bytes, followed by the compression level written as an int: a sign
+/// byte and int size bytes of the absolute value in little endian order
+fn set_compression(toc: &mut [u8], compression_level: i8) -> Result<(), anyhow::Error> {
+    if !toc.starts_with(b"PGDMP") || toc[5..8] != [1, 14, 0] || toc[8] != 4 {
+        return Err(anyhow!("Unsupported TOC header"));
+    }
+    toc[11] = match compression_level < 0 {
+        true => { 1 }
+        false => { 0 }
+    };
+    toc[12..16].copy_from_slice(&(compression_level.unsigned_abs() as u32).to_le_bytes());
+    Ok(())
+}
+
+/// Replace the setval statement of a sequence, which the template resets to 1. The entries of a
+/// directory format TOC have no offsets, so the length of an entry may change
+fn set_sequence(toc: &mut Vec<u8>, sequence: &str, value: i64) -> Result<(), anyhow::Error> {
+    if value <= 0 {
+        return Ok(());
+    }
+    let mut template_statement = Vec::new();
+    write_str(&mut template_statement, Some(&setval_statement(sequence, 0)));
+    let position = toc.windows(template_statement.len())
+        .position(|window| window == template_statement.as_slice())
+        .ok_or(anyhow!("Missing SEQUENCE SET entry in the template TOC: {}", sequence))?;
+    let mut statement = Vec::new();
+    write_str(&mut statement, Some(&setval_statement(sequence, value)));
+    toc.splice(position..position + template_statement.len(), statement);
     Ok(())
 }
 
@@ -16,3 +90,202 @@ pub(crate) fn load_template_mapping() -> Result<JsonValue, anyhow::Error> {
     Ok(json::parse(MAPPING)?)
 }
 
+
+/// The columns of the COPY FROM stdin statements of the template TOC by table name, in the order
+/// of the written table data
+pub(crate) fn load_template_columns() -> Result<HashMap<String, Vec<String>>, anyhow::Error> {
+    // COPY public.node_tags (node_id, version, k, v) FROM stdin;
+    let re = Regex::new("COPY ([a-z_.]+) \\(([^)]+)\\) FROM stdin;")?;
+    let toc = String::from_utf8_lossy(TOC);
+    Ok(
+        re.captures_iter(&toc)
+            .map(|captures| {
+                (
+                    captures[1].to_string(),
+                    captures[2].split(", ").map(|column| column.to_string()).collect(),
+                )
+            })
+            .collect()
+    )
+}
+
+/// Write a TOC that creates the objects of schema, loads the table data files, with the columns of
+/// layouts, and sets the sequences to sequence_values. Returns the table data file name of each
+/// table
+///
+/// The TOC has the format of the template: the archive version, the database name and the server
+/// and pg_dump versions are taken from the template header.
+pub(crate) fn write_schema_toc(path: &Path, compression_level: i8, schema: &Schema, layouts: &HashMap<String, TableLayout>, sequence_values: &SequenceValues) -> Result<HashMap<String, String>, anyhow::Error> {
+    if schema.objects().is_empty() {
+        return Err(anyhow!("The apidb schema has no object definitions, a TOC requires a structure.sql"));
+    }
+    let entries = create_toc_entries(schema.objects(), layouts, sequence_values);
+    let mapping = entries.iter()
+        .filter(|entry| entry.desc == "TABLE DATA")
+        .map(|entry| (format!("{}.{}", entry.namespace.as_deref().unwrap_or("public"), entry.tag), entry.file_name.clone()))
+        .collect();
+
+    let mut toc = template_header()?;
+    set_compression(&mut toc, compression_level)?;
+    write_int(&mut toc, entries.len() as i32);
+    for entry in &entries {
+        entry.write(&mut toc);
+    }
+    let toc_path = path.join(PathBuf::from("toc.dat"));
+    fs::write(&toc_path, toc).with_context(|| format!("write {:?} to {:?}", &toc_path, path))?;
+    Ok(mapping)
+}
+
+/// The header of the template TOC with the time of creation set to now
+fn template_header() -> Result<Vec<u8>, anyhow::Error> {
+    // magic, version, int size, offset size, format, compression, 7 time fields
+    let mut offset = 11 + 5 + 7 * 5;
+    let mut header = TOC[..16].to_vec();
+    let now = chrono::Local::now();
+    for value in [now.second(), now.minute(), now.hour(), now.day(), now.month0(), (now.year() - 1900) as u32, 0] {
+        write_int(&mut header, value as i32);
+    }
+    // database name, server version, pg_dump version
+    for _ in 0..3 {
+        let (value, next) = read_str(TOC, offset).ok_or(anyhow!("Invalid template TOC header"))?;
+        write_str(&mut header, value.as_deref());
+        offset = next;
+    }
+    Ok(header)
+}
+
+fn section(kind: &str) -> TocSection {
+    match kind {
+        "COMMENT" | "ACL" => {
+            TocSection::None
+        }
+        "TABLE DATA" | "SEQUENCE SET" => {
+            TocSection::Data
+        }
+        "CONSTRAINT" | "FK CONSTRAINT" | "INDEX" | "TRIGGER" | "RULE" | "POLICY" => {
+            TocSection::PostData
+        }
+        _ => {
+            TocSection::PreData
+        }
+    }
+}
+
+fn drop_statement(object: &SchemaObject) -> Option<String> {
+    let namespace = object.namespace().unwrap_or("public");
+    let mut names = object.name().split(' ');
+    match object.kind() {
+        "EXTENSION" => {
+            Some(format!("DROP EXTENSION {};\n", object.name()))
+        }
+        "TYPE" | "TABLE" | "SEQUENCE" | "INDEX" => {
+            Some(format!("DROP {} {}.{};\n", object.kind(), namespace, object.name()))
+        }
+        "CONSTRAINT" | "FK CONSTRAINT" => {
+            Some(format!("ALTER TABLE ONLY {}.{} DROP CONSTRAINT {};\n", namespace, names.next()?, names.next()?))
+        }
+        "DEFAULT" => {
+            Some(format!("ALTER TABLE {}.{} ALTER COLUMN {} DROP DEFAULT;\n", namespace, names.next()?, names.next()?))
+        }
+        _ => {
+            None
+        }
+    }
+}
+
+/// Order the entries as pg_dump does: the schema before the data and the indexes and constraints
+/// after the data. The dependencies allow a parallel pg_restore
+fn create_toc_entries(objects: &[SchemaObject], layouts: &HashMap<String, TableLayout>, sequence_values: &SequenceValues) -> Vec<TocEntry> {
+    let mut dump_id = 0;
+    let mut next_dump_id = || {
+        dump_id += 1;
+        dump_id
+    };
+    let mut entries = Vec::new();
+    for (tag, defn) in [
+        ("ENCODING", "SET client_encoding = 'UTF8';\n"),
+        ("STDSTRINGS", "SET standard_conforming_strings = 'on';\n"),
+        ("SEARCHPATH", "SELECT pg_catalog.set_config('search_path', '', false);\n"),
+    ] {
+        let mut entry = TocEntry::new(next_dump_id(), tag, tag, TocSection::PreData);
+        entry.defn = Some(defn.to_string());
+        entries.push(entry);
+    }
+
+    let mut schema_entries: Vec<(TocEntry, &SchemaObject)> = objects.iter()
+        .map(|object| {
+            let mut entry = TocEntry::new(next_dump_id(), object.name(), object.kind(), section(object.kind()));
+            entry.defn = Some(object.definition().to_string());
+            entry.drop_stmt = drop_statement(object);
+            entry.namespace = object.namespace().map(|namespace| namespace.to_string());
+            if ["TABLE", "INDEX", "CONSTRAINT"].contains(&object.kind()) {
+                entry.tablespace = Some(String::new());
+            }
+            if object.kind() == "TABLE" {
+                entry.tableam = Some("heap".to_string());
+            }
+            (entry, object)
+        })
+        .collect();
+    let table_dump_ids: HashMap<String, usize> = schema_entries.iter()
+        .filter(|(entry, _)| entry.desc == "TABLE")
+        .filter_map(|(entry, object)| object.table_name().map(|table_name| (table_name, entry.dump_id)))
+        .collect();
+    let unique_dump_ids: Vec<(String, usize)> = schema_entries.iter()
+        .filter(|(entry, _)| entry.desc == "CONSTRAINT" || entry.desc == "INDEX")
+        .filter_map(|(entry, object)| object.table_name().map(|table_name| (table_name, entry.dump_id)))
+        .collect();
+    for (entry, object) in schema_entries.iter_mut() {
+        if entry.desc != "TABLE" {
+            if let Some(table_dump_id) = object.table_name().and_then(|table_name| table_dump_ids.get(&table_name)) {
+                entry.dependencies.push(*table_dump_id);
+            }
+        }
+        if entry.desc == "FK CONSTRAINT" {
+            // the referenced key must exist first
+            if let Some(referenced_table_name) = object.referenced_table_name() {
+                entry.dependencies.extend(table_dump_ids.get(&referenced_table_name));
+                entry.dependencies.extend(
+                    unique_dump_ids.iter()
+                        .filter(|(table_name, _)| *table_name == referenced_table_name)
+                        .map(|(_, dump_id)| *dump_id)
+                );
+            }
+        }
+    }
+
+    let mut data_entries = Vec::new();
+    for (entry, object) in &schema_entries {
+        if entry.desc != "TABLE" {
+            continue;
+        }
+        if let Some(layout) = object.table_name().and_then(|table_name| layouts.get(&table_name)) {
+            let mut data_entry = TocEntry::new(next_dump_id(), object.name(), "TABLE DATA", TocSection::Data);
+            data_entry.copy_stmt = Some(format!("COPY {}.{} ({}) FROM stdin;\n", entry.namespace.as_deref().unwrap_or("public"), object.name(), layout.columns().join(", ")));
+            data_entry.namespace = entry.namespace.clone();
+            data_entry.dependencies.push(entry.dump_id);
+            data_entry.file_name = format!("{}.dat", data_entry.dump_id);
+            data_entries.push(data_entry);
+        }
+    }
+    for (entry, _) in &schema_entries {
+        if entry.desc != "SEQUENCE" {
+            continue;
+        }
+        let namespace = entry.namespace.as_deref().unwrap_or("public");
+        let mut sequence_entry = TocEntry::new(next_dump_id(), &entry.tag, "SEQUENCE SET", TocSection::Data);
+        let sequence = format!("{}.{}", namespace, entry.tag);
+        sequence_entry.defn = Some(setval_statement(&sequence, sequence_values.value(&sequence)));
+        sequence_entry.namespace = entry.namespace.clone();
+        sequence_entry.dependencies.push(entry.dump_id);
+        data_entries.push(sequence_entry);
+    }
+
+    let (post_data_entries, pre_data_entries): (Vec<TocEntry>, Vec<TocEntry>) = schema_entries.into_iter()
+        .map(|(entry, _)| entry)
+        .partition(|entry| entry.section == TocSection::PostData);
+    entries.extend(pre_data_entries);
+    entries.extend(data_entries);
+    entries.extend(post_data_entries);
+    entries
+}
@@ -0,0 +1,102 @@
+/// The section of a TOC entry, restored in this order
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TocSection {
+    None = 1,
+    PreData = 2,
+    Data = 3,
+    PostData = 4,
+}
+
+/// An entry of a pg_dump archive TOC, version 1.14, with the extra data of the directory format
+#[derive(Clone, Debug)]
+pub(crate) struct TocEntry {
+    pub(crate) dump_id: usize,
+    pub(crate) tag: String,
+    pub(crate) desc: String,
+    pub(crate) section: TocSection,
+    pub(crate) defn: Option<String>,
+    pub(crate) drop_stmt: Option<String>,
+    pub(crate) copy_stmt: Option<String>,
+    pub(crate) namespace: Option<String>,
+    pub(crate) tablespace: Option<String>,
+    pub(crate) tableam: Option<String>,
+    pub(crate) dependencies: Vec<usize>,
+    // the table data file, empty for entries without data
+    pub(crate) file_name: String,
+}
+
+impl TocEntry {
+    pub(crate) fn new(dump_id: usize, tag: &str, desc: &str, section: TocSection) -> TocEntry {
+        TocEntry {
+            dump_id,
+            tag: tag.to_string(),
+            desc: desc.to_string(),
+            section,
+            defn: None,
+            drop_stmt: None,
+            copy_stmt: None,
+            namespace: None,
+            tablespace: None,
+            tableam: None,
+            dependencies: Vec::new(),
+            file_name: String::new(),
+        }
+    }
+
+    pub(crate) fn write(&self, toc: &mut Vec<u8>) {
+        write_int(toc, self.dump_id as i32);
+        // had dumper
+        write_int(toc, (self.desc == "TABLE DATA") as i32);
+        // table oid, oid
+        write_str(toc, Some("0"));
+        write_str(toc, Some("0"));
+        write_str(toc, Some(&self.tag));
+        write_str(toc, Some(&self.desc));
+        write_int(toc, self.section as i32);
+        write_str(toc, self.defn.as_deref());
+        write_str(toc, self.drop_stmt.as_deref());
+        write_str(toc, self.copy_stmt.as_deref());
+        write_str(toc, self.namespace.as_deref());
+        write_str(toc, self.tablespace.as_deref());
+        write_str(toc, self.tableam.as_deref());
+        // no owner, the restoring user owns the objects
+        write_str(toc, Some(""));
+        // with oids
+        write_str(toc, Some("false"));
+        for dependency in &self.dependencies {
+            write_str(toc, Some(&dependency.to_string()));
+        }
+        write_str(toc, None);
+        write_str(toc, Some(&self.file_name));
+    }
+}
+
+/// Write an int as a sign byte followed by 4 bytes of the absolute value in little endian order
+pub(crate) fn write_int(toc: &mut Vec<u8>, value: i32) {
+    toc.push((value < 0) as u8);
+    toc.extend_from_slice(&value.unsigned_abs().to_le_bytes());
+}
+
+/// Write a string as its length followed by the bytes, None as length -1
+pub(crate) fn write_str(toc: &mut Vec<u8>, value: Option<&str>) {
+    match value {
+        None => {
+            write_int(toc, -1);
+        }
+        Some(value) => {
+            write_int(toc, value.len() as i32);
+            toc.extend_from_slice(value.as_bytes());
+        }
+    }
+}
+
+/// Read a string written by [write_str] at offset, return it and the offset that follows
+pub(crate) fn read_str(toc: &[u8], offset: usize) -> Option<(Option<String>, usize)> {
+    let header = toc.get(offset..offset + 5)?;
+    let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if header[0] != 0 {
+        return Some((None, offset + 5));
+    }
+    let value = toc.get(offset + 5..offset + 5 + len)?;
+    Some((Some(String::from_utf8_lossy(value).to_string()), offset + 5 + len))
+}
@@ -1,14 +1,23 @@
+use std::collections::HashSet;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
 
-use anyhow::{Context, Error};
+use anyhow::{anyhow, Context, Error};
 use escape_string::escape;
 
 use crate::osm::apidb_dump::sql::{calculate_tile, to_sql_bool, to_sql_time_millis, to_sql_time_micros};
+use crate::osm::apidb_dump::write::changeset_summary::ChangesetSummary;
 use crate::osm::apidb_dump::write::current_object::{CurrentObjectLine, CurrentObjectLines};
+use crate::osm::apidb_dump::write::post_load_options::PostLoadOptions;
+use crate::osm::apidb_dump::write::schema::Schema;
+use crate::osm::apidb_dump::write::sequence_values::SequenceValues;
+use crate::osm::apidb_dump::write::table_data_writer::TableDataWriter;
 use crate::osm::apidb_dump::write::table_data_writers::TableDataWriters;
-use crate::osm::apidb_dump::write::toc::{load_template_mapping, write_toc};
+use crate::osm::apidb_dump::write::table_layout::{POPULATED_TABLES, TableLayout};
+use crate::osm::apidb_dump::write::toc::{DumpToc, load_template_mapping, write_schema_toc};
+use crate::osm::model::changeset::Changeset;
+use crate::osm::model::coordinate::Coordinate;
 use crate::osm::model::element::Element;
 use crate::osm::model::node::Node;
 use crate::osm::model::relation::{Member, Relation};
@@ -17,11 +26,16 @@ use crate::osm::model::way::Way;
 /// Writer of apidb schema dump
 ///
 /// Writer of apidb schema dump that can be loaded using pg_restore into a Postgresql database
+///
+/// When the writer is closed the sequences of the id columns are set in the TOC past the largest
+/// written ids, and the optional post load steps of [PostLoadOptions] are written to
+/// post_load.sql, to be run with psql after pg_restore.
 pub struct Writer {
-    #[allow(dead_code)]
     output_path: PathBuf,
-    #[allow(dead_code)]
     compression_level: i8,
+    toc: Option<DumpToc>,
+    post_load_options: PostLoadOptions,
+    sequence_values: SequenceValues,
     writers: TableDataWriters,
     current_node_line: CurrentObjectLine,
     current_node_tag_lines: CurrentObjectLines,
@@ -39,24 +53,98 @@ impl Writer {
     /// * output_path - directory to write the output to. Must contain enough space which is very
     ///   difficult to calculate because the *.osm.pbf input is so condensed that 1GB of input can
     ///   easily transform into 100GB of output.
+    /// * compression_level - 0 to write plain NNNN.dat table data files, 1 to 9 to write them gzip
+    ///   compressed as NNNN.dat.gz with the given level, -1 for the default level. The compression
+    ///   is recorded in toc.dat, as pg_dump does, and the output can be restored with pg_restore
+    ///   and read with [Reader](crate::osm::apidb_dump::read::reader::Reader) either way.
     pub fn new(output_path: PathBuf, compression_level: i8) -> Result<Writer, Error> {
-        Self::create_result_dir(&output_path)?;
-        let writers = TableDataWriters::new(load_template_mapping()?, &output_path)?;
-        Ok(
-            Writer {
-                output_path,
-                compression_level,
-                writers,
-                current_node_line: CurrentObjectLine::new(),
-                current_node_tag_lines: CurrentObjectLines::new(),
-                current_way_line: CurrentObjectLine::new(),
-                current_way_node_lines: CurrentObjectLines::new(),
-                current_way_tag_lines: CurrentObjectLines::new(),
-                current_relation_line: CurrentObjectLine::new(),
-                current_relation_member_lines: CurrentObjectLines::new(),
-                current_relation_tag_lines: CurrentObjectLines::new(),
+        Self::create_result_dir(&output_path, compression_level)?;
+        let toc = DumpToc::Template;
+        toc.write(&output_path, compression_level, &SequenceValues::default())?;
+        let writers = TableDataWriters::new(load_template_mapping()?, &output_path, compression_level)?;
+        let mut writer = Self::from_table_data_writers(output_path, compression_level, writers);
+        writer.with_toc(toc);
+        Ok(writer)
+    }
+
+    /// Create a new [Writer] for a version of the apidb schema other than the bundled one
+    ///
+    /// The TOC is generated from the schema, which must be read from a structure.sql. The rows
+    /// leave out the columns that are missing in the schema and the columns that are not written
+    /// are set to their defaults by pg_restore. Fails if the schema requires a value that is not
+    /// written. See [Writer::new] for the other arguments.
+    pub fn from_schema(output_path: PathBuf, compression_level: i8, schema: &Schema) -> Result<Writer, Error> {
+        log::info!("Write apidb schema version {}", schema.version().unwrap_or("unknown"));
+        let layouts = TableLayout::for_schema(schema)?;
+        Self::create_result_dir(&output_path, compression_level)?;
+        let mapping = write_schema_toc(&output_path, compression_level, schema, &layouts, &SequenceValues::default())?;
+
+        let mut created = HashSet::new();
+        let writers = TableDataWriters::from_factory(|table_name| {
+            match (layouts.get(table_name), mapping.get(table_name)) {
+                (Some(layout), Some(file_name)) => {
+                    created.insert(table_name.to_string());
+                    let mut writer = TableDataWriter::new(table_name.to_string(), file_name.clone(), &output_path, compression_level)?;
+                    if let Some(projection) = layout.projection() {
+                        writer.project(projection.clone())?;
+                    }
+                    Ok(writer)
+                }
+                _ => {
+                    // the table was removed from the schema
+                    Ok(TableDataWriter::from_sink(table_name.to_string(), "none".to_string(), Box::new(std::io::sink()), 0))
+                }
+            }
+        })?;
+        // the tables added to the schema are empty
+        for (table_name, file_name) in &mapping {
+            if !created.contains(table_name) {
+                TableDataWriter::new(table_name.clone(), file_name.clone(), &output_path, compression_level)?.close()?;
+            }
+        }
+        let mut writer = Self::from_table_data_writers(output_path, compression_level, writers);
+        writer.with_toc(
+            DumpToc::Schema {
+                schema: schema.clone(),
+                layouts,
             }
-        )
+        );
+        Ok(writer)
+    }
+
+    /// Create a new [Writer] that writes the table rows to the provided table data writers
+    pub(crate) fn from_table_data_writers(output_path: PathBuf, compression_level: i8, writers: TableDataWriters) -> Writer {
+        Writer {
+            output_path,
+            compression_level,
+            toc: None,
+            post_load_options: PostLoadOptions::default(),
+            sequence_values: SequenceValues::default(),
+            writers,
+            current_node_line: CurrentObjectLine::new(),
+            current_node_tag_lines: CurrentObjectLines::new(),
+            current_way_line: CurrentObjectLine::new(),
+            current_way_node_lines: CurrentObjectLines::new(),
+            current_way_tag_lines: CurrentObjectLines::new(),
+            current_relation_line: CurrentObjectLine::new(),
+            current_relation_member_lines: CurrentObjectLines::new(),
+            current_relation_tag_lines: CurrentObjectLines::new(),
+        }
+    }
+
+    /// Set the [PostLoadOptions]. Must be called before [Writer::close]
+    pub fn with_post_load_options(&mut self, post_load_options: PostLoadOptions) {
+        self.post_load_options = post_load_options;
+    }
+
+    /// Write the TOC with the sequence values to the output path when the writer is closed
+    pub(crate) fn with_toc(&mut self, toc: DumpToc) {
+        self.toc = Some(toc);
+    }
+
+    /// The values of the sequences of the id columns, complete after [Writer::close]
+    pub(crate) fn sequence_values(&self) -> &SequenceValues {
+        &self.sequence_values
     }
 
     /// Write an element
@@ -78,7 +166,8 @@ impl Writer {
 
     fn write_node(&mut self, mut node: Node) -> Result<(), Error> {
         self.writers.user_index_buffer.insert(node.uid() as i64, node.take_user());
-        self.writers.changeset_user_index_buffer.insert(node.changeset(), node.uid() as i64);
+        self.add_change(node.changeset(), node.uid() as i64, node.timestamp(), node.visible().then(|| node.coordinate()));
+        self.sequence_values.add_node(node.id());
 
         // public.current_nodes (id, latitude, longitude, changeset_id, visible, "timestamp", tile, version)
         // template context: 4228.dat
@@ -156,7 +245,8 @@ impl Writer {
 
     fn write_way(&mut self, mut way: Way) -> Result<(), Error> {
         self.writers.user_index_buffer.insert(way.uid() as i64, way.take_user());
-        self.writers.changeset_user_index_buffer.insert(way.changeset(), way.uid() as i64);
+        self.add_change(way.changeset(), way.uid() as i64, way.timestamp(), None);
+        self.sequence_values.add_way(way.id());
 
 
         let mut current_way_node_lines = Vec::new();
@@ -260,7 +350,8 @@ impl Writer {
 
     fn write_relation(&mut self, mut relation: Relation) -> Result<(), Error> {
         self.writers.user_index_buffer.insert(relation.uid() as i64, relation.take_user());
-        self.writers.changeset_user_index_buffer.insert(relation.changeset(), relation.uid() as i64);
+        self.add_change(relation.changeset(), relation.uid() as i64, relation.timestamp(), None);
+        self.sequence_values.add_relation(relation.id());
         let mut current_relation_member_lines = Vec::new();
         for (sequence_id, member) in relation.members().iter().enumerate() {
             let (member_id, member_role, member_type) = match member {
@@ -377,12 +468,109 @@ impl Writer {
         Ok(())
     }
 
+    /// Use the supplied changeset metadata for the changesets and changeset_tags tables
+    ///
+    /// Without metadata the changesets are derived from the written elements: created_at and
+    /// closed_at are the earliest and the latest element timestamps, the bounding box is that of
+    /// the visible nodes and num_changes is the number of element versions. Only the changesets
+    /// referenced by the written elements are written, the rest of the supplied changesets are
+    /// ignored. The users of the supplied changesets are written to the users table, a changeset
+    /// without a uid is attributed to the user of its first element. May be called before or after
+    /// writing the elements, but before [Writer::close].
+    ///
+    /// Example:
+    /// ```
+    /// use std::path::PathBuf;
+    /// use osm_io::osm::apidb_dump::write::writer::Writer;
+    /// use osm_io::osm::changesets;
+    /// fn example() -> Result<(), anyhow::Error> {
+    ///     let changesets_reader = changesets::reader::Reader::new(&PathBuf::from("./changesets-230109.osm"))?;
+    ///     let mut writer = Writer::new(PathBuf::from("./target/results/niue-230109"), 0)?;
+    ///     writer.add_changesets(changesets_reader.changesets()?)?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn add_changesets(&mut self, changesets: impl IntoIterator<Item = Changeset>) -> Result<(), Error> {
+        for changeset in changesets {
+            // public.changesets (id, user_id, created_at, min_lat, max_lat, min_lon, max_lon, closed_at, num_changes)
+            // template context: 4222.dat
+            let (min_lat, max_lat, min_lon, max_lon) = match changeset.bounding_box() {
+                None => {
+                    ("\\N".to_string(), "\\N".to_string(), "\\N".to_string(), "\\N".to_string())
+                }
+                Some(bounding_box) => {
+                    (
+                        ((bounding_box.bottom() * 1E7).round() as i64).to_string(),
+                        ((bounding_box.top() * 1E7).round() as i64).to_string(),
+                        ((bounding_box.left() * 1E7).round() as i64).to_string(),
+                        ((bounding_box.right() * 1E7).round() as i64).to_string(),
+                    )
+                }
+            };
+            // the id and user_id columns are added when the changeset is written
+            let changeset_columns = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                                         to_sql_time_millis(changeset.created_at()),
+                                         min_lat,
+                                         max_lat,
+                                         min_lon,
+                                         max_lon,
+                                         to_sql_time_millis(changeset.closed_at().unwrap_or(changeset.created_at())),
+                                         changeset.num_changes(),
+            );
+
+            // public.changeset_tags (changeset_id, k, v)
+            // template context: 4221.dat
+            let mut changeset_tag_lines = String::new();
+            for tag in changeset.tags() {
+                changeset_tag_lines.push_str(
+                    &format!("{}\t{}\t{}\n",
+                             changeset.id(),
+                             escape(tag.k()),
+                             escape(tag.v()),
+                    )
+                );
+            }
+            if changeset.uid() >= 0 {
+                // the user must exist for changesets_user_id_fkey, even without written elements
+                self.writers.user_index_buffer.insert(changeset.uid() as i64, changeset.user().clone());
+            }
+            self.writers.changeset_metadata_index.insert(changeset.id(), (changeset.uid() as i64, changeset_columns, changeset_tag_lines))?;
+        }
+        Ok(())
+    }
+
+    fn add_change(&mut self, changeset_id: i64, user_id: i64, timestamp: i64, coordinate: Option<&Coordinate>) {
+        let changeset_summary = self.writers.changeset_buffer.entry(changeset_id)
+            .or_insert_with(|| ChangesetSummary::new(user_id, timestamp));
+        changeset_summary.add_change(timestamp);
+        if let Some(coordinate) = coordinate {
+            changeset_summary.add_coordinate(coordinate);
+        }
+    }
+
     fn write_changesets(&mut self) -> Result<(), Error> {
-        for element in self.writers.changeset_user_index.range(..)? {
-            let (changeset_id, user_id) = element?;
+        let lib_name = format!("osm-io {}", env!("CARGO_PKG_VERSION"));
+        for element in self.writers.changeset_index.range(..)? {
+            let (changeset_id, changeset_summary) = element?;
+            self.sequence_values.add_changeset(changeset_id);
+            if let Some((user_id, changeset_columns, changeset_tag_lines)) = self.writers.changeset_metadata_index.get(&changeset_id)? {
+                // without a uid the changeset is attributed to the user of its elements
+                let user_id = match user_id < 0 {
+                    true => {
+                        changeset_summary.user_id()
+                    }
+                    false => {
+                        user_id
+                    }
+                };
+                self.writers.changeset_tags.writer().write_all(changeset_tag_lines.as_bytes())?;
+                let changeset_line = format!("{}\t{}\t{}", changeset_id, user_id, changeset_columns);
+                self.writers.changesets.writer().write_all(changeset_line.as_bytes())?;
+                continue;
+            }
+
             // public.changeset_tags (changeset_id, k, v)
             // template context: 4221.dat
-            let lib_name = format!("osm-io {}", env!("CARGO_PKG_VERSION"));
             let line = format!("{}\t{}\t{}\n",
                                changeset_id,
                                "created_by",
@@ -399,17 +587,24 @@ impl Writer {
 
             // public.changesets (id, user_id, created_at, min_lat, max_lat, min_lon, max_lon, closed_at, num_changes)
             // template context: 4222.dat
-            let t = chrono::offset::Utc::now();
+            let (min_lat, max_lat, min_lon, max_lon) = match changeset_summary.bounding_box() {
+                None => {
+                    ("\\N".to_string(), "\\N".to_string(), "\\N".to_string(), "\\N".to_string())
+                }
+                Some((min_lat, max_lat, min_lon, max_lon)) => {
+                    (min_lat.to_string(), max_lat.to_string(), min_lon.to_string(), max_lon.to_string())
+                }
+            };
             let line = format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
                                changeset_id,
-                               user_id,
-                               to_sql_time_micros(t.timestamp_micros()),
-                               -900000000,
-                               900000000,
-                               -1800000000,
-                               1800000000,
-                               to_sql_time_micros(t.timestamp_micros()),
-                               0
+                               changeset_summary.user_id(),
+                               to_sql_time_millis(changeset_summary.min_timestamp()),
+                               min_lat,
+                               max_lat,
+                               min_lon,
+                               max_lon,
+                               to_sql_time_millis(changeset_summary.max_timestamp()),
+                               changeset_summary.num_changes(),
             );
             self.writers.changesets.writer().write_all(line.as_bytes())?;
         }
@@ -422,6 +617,7 @@ impl Writer {
         // template context: 4290.dat
         for element in self.writers.user_index.range(..)? {
             let (user_id, user_name) = element?;
+            self.sequence_values.add_user(user_id);
 
             let t = chrono::offset::Utc::now();
             let osm_admin_user = format!("osm-admin-user-{}@example.com", user_id);
@@ -532,6 +728,30 @@ impl Writer {
         Ok(())
     }
 
+    /// Write the remaining current object lines and close the table data writers, leaving the
+    /// users and changesets accumulated from the elements to be written by another writer
+    pub(crate) fn close_elements(&mut self) -> Result<(), Error> {
+        self.flush_current_object_lines()?;
+        self.writers.close()?;
+        Ok(())
+    }
+
+    /// Take over the users, changesets and sequence values accumulated by other
+    pub(crate) fn merge_accumulated(&mut self, other: &mut Writer) {
+        self.sequence_values.merge(&other.sequence_values);
+        self.writers.user_index_buffer.extend(other.writers.user_index_buffer.drain());
+        for (changeset_id, changeset_summary) in other.writers.changeset_buffer.drain() {
+            match self.writers.changeset_buffer.get_mut(&changeset_id) {
+                None => {
+                    self.writers.changeset_buffer.insert(changeset_id, changeset_summary);
+                }
+                Some(accumulated_changeset_summary) => {
+                    accumulated_changeset_summary.merge(&changeset_summary);
+                }
+            }
+        }
+    }
+
     /// Flush internal buffers and add file terminators
     pub fn close(&mut self) -> Result<(), Error> {
         self.flush_current_object_lines()?;
@@ -539,17 +759,39 @@ impl Writer {
         self.write_users()?;
         self.write_changesets()?;
         self.writers.close()?;
+        if let Some(toc) = &self.toc {
+            toc.write(&self.output_path, self.compression_level, &self.sequence_values)?;
+            self.write_post_load_sql()?;
+        }
+        Ok(())
+    }
+
+    fn write_post_load_sql(&self) -> Result<(), Error> {
+        let statements = self.post_load_options.statements(&POPULATED_TABLES);
+        if statements.is_empty() {
+            return Ok(());
+        }
+        let post_load_path = self.output_path.join("post_load.sql");
+        let post_load_sql = format!("-- Run after pg_restore, for example: psql -d openstreetmap -f post_load.sql\n{}", statements.concat());
+        fs::write(&post_load_path, post_load_sql).with_context(|| format!("write {:?}", post_load_path))?;
         Ok(())
     }
 
+    /// The compression level of the table data files
+    pub fn compression_level(&self) -> i8 {
+        self.compression_level
+    }
+
     /// Return table to file mapping for diagnostics
     pub fn table_mapping(&self) -> Vec<String> {
         Vec::new()
     }
 
-    fn create_result_dir(output_path: &PathBuf) -> Result<(), Error> {
+    pub(crate) fn create_result_dir(output_path: &PathBuf, compression_level: i8) -> Result<(), Error> {
+        if !(-1..=9).contains(&compression_level) {
+            return Err(anyhow!("Invalid compression level: {}, expected -1 to 9", compression_level));
+        }
         fs::create_dir_all(output_path).with_context(|| format!("Failed to create dir: {:?}", output_path))?;
-        write_toc(output_path)?;
 
         Ok(())
     }
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::io::{BufReader, Read};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::DateTime;
+
+use crate::osm::changesets::xml::{Markup, read_markup};
+use crate::osm::model::bounding_box::BoundingBox;
+use crate::osm::model::changeset::Changeset;
+use crate::osm::model::tag::Tag;
+
+/// Iterate over the changesets of a changeset XML document
+///
+/// Only the changeset attributes and tags are read, discussions are skipped. Panics on malformed
+/// input, reporting the path.
+pub struct ChangesetIterator {
+    path: PathBuf,
+    reader: BufReader<Box<dyn Read + Send>>,
+    buf: Vec<u8>,
+}
+
+impl ChangesetIterator {
+    pub(crate) fn new(path: PathBuf, reader: Box<dyn Read + Send>) -> ChangesetIterator {
+        ChangesetIterator {
+            path,
+            reader: BufReader::new(reader),
+            buf: Vec::new(),
+        }
+    }
+
+    fn next_changeset(&mut self) -> Result<Option<Changeset>, anyhow::Error> {
+        // the attributes of an open <changeset> element and the tags read so far
+        let mut changeset_attributes: Option<HashMap<String, String>> = None;
+        let mut tags = Vec::new();
+        while let Some(markup) = read_markup(&mut self.reader, &mut self.buf)? {
+            match markup {
+                Markup::Start { name, attributes, self_closing } => {
+                    if name == "changeset" {
+                        if self_closing {
+                            return Ok(Some(Self::parse_changeset(attributes.into_iter().collect(), tags)?));
+                        }
+                        changeset_attributes = Some(attributes.into_iter().collect());
+                    } else if name == "tag" && changeset_attributes.is_some() {
+                        let mut attributes: HashMap<String, String> = attributes.into_iter().collect();
+                        tags.push(
+                            Tag::new(
+                                attributes.remove("k").ok_or(anyhow!("Missing tag key"))?,
+                                attributes.remove("v").ok_or(anyhow!("Missing tag value"))?,
+                            )
+                        );
+                    }
+                }
+                Markup::End { name } => {
+                    if name == "changeset" {
+                        let attributes = changeset_attributes.take().ok_or(anyhow!("Unexpected </changeset>"))?;
+                        return Ok(Some(Self::parse_changeset(attributes, tags)?));
+                    }
+                }
+                Markup::Other => {}
+            }
+        }
+        match changeset_attributes {
+            None => {
+                Ok(None)
+            }
+            Some(_) => {
+                Err(anyhow!("Unexpected end of input in <changeset>"))
+            }
+        }
+    }
+
+    fn parse_changeset(mut attributes: HashMap<String, String>, tags: Vec<Tag>) -> Result<Changeset, anyhow::Error> {
+        let id = i64::from_str(&attributes.remove("id").ok_or(anyhow!("Missing changeset id"))?)?;
+        let created_at = Self::parse_time(&attributes.remove("created_at").ok_or(anyhow!("Missing created_at in changeset {}", id))?)?;
+        let closed_at = match attributes.remove("closed_at") {
+            None => {
+                None
+            }
+            Some(closed_at) => {
+                Some(Self::parse_time(&closed_at)?)
+            }
+        };
+        let open = attributes.remove("open").map(|open| open == "true").unwrap_or(closed_at.is_none());
+        // a missing uid defaults to -1, as for elements
+        let uid = match attributes.remove("uid") {
+            None => {
+                -1
+            }
+            Some(uid) => {
+                i32::from_str(&uid)?
+            }
+        };
+        let user = attributes.remove("user").unwrap_or_default();
+        let bounding_box = match (attributes.remove("min_lon"), attributes.remove("min_lat"), attributes.remove("max_lon"), attributes.remove("max_lat")) {
+            (Some(min_lon), Some(min_lat), Some(max_lon), Some(max_lat)) => {
+                Some(BoundingBox::new(f64::from_str(&min_lon)?, f64::from_str(&min_lat)?, f64::from_str(&max_lon)?, f64::from_str(&max_lat)?))
+            }
+            _ => {
+                None
+            }
+        };
+        let num_changes = match attributes.remove("num_changes") {
+            None => {
+                0
+            }
+            Some(num_changes) => {
+                i32::from_str(&num_changes)?
+            }
+        };
+        Ok(Changeset::new(id, created_at, closed_at, open, uid, user, bounding_box, num_changes, tags))
+    }
+
+    fn parse_time(s: &str) -> Result<i64, anyhow::Error> {
+        Ok(DateTime::parse_from_rfc3339(s)?.timestamp_millis())
+    }
+}
+
+impl Iterator for ChangesetIterator {
+    type Item = Changeset;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_changeset()
+            .unwrap_or_else(|e| panic!("Failed to read changesets from {:?}: {}", self.path, e))
+    }
+}
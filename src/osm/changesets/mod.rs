@@ -0,0 +1,4 @@
+pub mod reader;
+pub mod changeset_iterator;
+
+pub(crate) mod xml;
@@ -0,0 +1,65 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use flate2::read::MultiGzDecoder;
+
+use crate::osm::changesets::changeset_iterator::ChangesetIterator;
+
+/// Changeset XML reader
+///
+/// Read changeset metadata in the XML format of the OSM API `/api/0.6/changesets` responses and of
+/// the changesets-*.osm planet dumps. Files with the .gz extension are decompressed on the fly.
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::changesets;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let input_path = PathBuf::from("./tests/fixtures/changesets.osm");
+///     let reader = changesets::reader::Reader::new(&input_path)?;
+///     for changeset in reader.changesets()? {
+///         println!("{:?}", changeset);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct Reader {
+    path: PathBuf,
+}
+
+impl Reader {
+    /// Create a new [Reader]
+    pub fn new(path: &Path) -> Result<Reader, anyhow::Error> {
+        if !path.is_file() {
+            return Err(anyhow!("Not a file: {}", path.display()));
+        }
+        Ok(
+            Reader {
+                path: path.to_path_buf(),
+            }
+        )
+    }
+
+    /// Iterator used to iterate over changesets
+    pub fn changesets(&self) -> Result<ChangesetIterator, anyhow::Error> {
+        let file = File::open(&self.path)
+            .with_context(|| anyhow!("path: {}", self.path.display()))?;
+        let reader: Box<dyn Read + Send> = match self.path.extension().and_then(|extension| extension.to_str()) {
+            Some("gz") => {
+                Box::new(MultiGzDecoder::new(file))
+            }
+            _ => {
+                Box::new(file)
+            }
+        };
+        Ok(
+            ChangesetIterator::new(self.path.clone(), reader)
+        )
+    }
+
+    /// Input path
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
@@ -0,0 +1,182 @@
+use std::io::BufRead;
+
+use anyhow::anyhow;
+
+/// A single markup item of an XML document, that is anything between '<' and '>'
+#[derive(Debug, PartialEq)]
+pub(crate) enum Markup {
+    /// A start tag with its attributes, self closing if it ends with '/>'
+    Start {
+        name: String,
+        attributes: Vec<(String, String)>,
+        self_closing: bool,
+    },
+    End {
+        name: String,
+    },
+    /// Declarations, processing instructions and comments
+    Other,
+}
+
+/// Read the next markup item skipping the text before it. Returns None at the end of input
+pub(crate) fn read_markup(reader: &mut impl BufRead, buf: &mut Vec<u8>) -> Result<Option<Markup>, anyhow::Error> {
+    buf.clear();
+    if reader.read_until(b'<', buf)? == 0 || buf.last() != Some(&b'<') {
+        return Ok(None);
+    }
+    buf.clear();
+    loop {
+        if reader.read_until(b'>', buf)? == 0 || buf.last() != Some(&b'>') {
+            return Err(anyhow!("Unexpected end of input in markup: <{}", String::from_utf8_lossy(buf)));
+        }
+        // '>' may appear in attribute values and in comments
+        if buf.starts_with(b"!--") {
+            if buf.ends_with(b"-->") {
+                break;
+            }
+        } else if !in_quotes(buf) {
+            break;
+        }
+    }
+    buf.pop();
+    parse_markup(std::str::from_utf8(buf)?)
+}
+
+fn in_quotes(buf: &[u8]) -> bool {
+    let mut quote = None;
+    for b in buf {
+        match quote {
+            None => {
+                if *b == b'"' || *b == b'\'' {
+                    quote = Some(*b);
+                }
+            }
+            Some(q) => {
+                if *b == q {
+                    quote = None;
+                }
+            }
+        }
+    }
+    quote.is_some()
+}
+
+fn parse_markup(s: &str) -> Result<Option<Markup>, anyhow::Error> {
+    if s.starts_with('?') || s.starts_with('!') {
+        return Ok(Some(Markup::Other));
+    }
+    if let Some(name) = s.strip_prefix('/') {
+        return Ok(
+            Some(
+                Markup::End {
+                    name: name.trim().to_string(),
+                }
+            )
+        );
+    }
+
+    let (s, self_closing) = match s.strip_suffix('/') {
+        None => {
+            (s, false)
+        }
+        Some(s) => {
+            (s, true)
+        }
+    };
+    let name_end = s.find(|c: char| c.is_ascii_whitespace()).unwrap_or(s.len());
+    let name = s[..name_end].to_string();
+    let mut rest = s[name_end..].trim_start();
+    let mut attributes = Vec::new();
+    while !rest.is_empty() {
+        let eq = rest.find('=').ok_or(anyhow!("Malformed attribute in <{}>", s))?;
+        let attribute_name = rest[..eq].trim().to_string();
+        rest = rest[eq + 1..].trim_start();
+        let quote = rest.chars().next().ok_or(anyhow!("Missing attribute value in <{}>", s))?;
+        if quote != '"' && quote != '\'' {
+            return Err(anyhow!("Unquoted attribute value in <{}>", s));
+        }
+        let end = rest[1..].find(quote).ok_or(anyhow!("Unterminated attribute value in <{}>", s))?;
+        attributes.push((attribute_name, unescape(&rest[1..end + 1])?));
+        rest = rest[end + 2..].trim_start();
+    }
+
+    Ok(
+        Some(
+            Markup::Start {
+                name,
+                attributes,
+                self_closing,
+            }
+        )
+    )
+}
+
+/// Replace the predefined entities and the character references
+pub(crate) fn unescape(s: &str) -> Result<String, anyhow::Error> {
+    if !s.contains('&') {
+        return Ok(s.to_string());
+    }
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find(';').ok_or(anyhow!("Unterminated entity in: {}", s))? + start;
+        let entity = &rest[start + 1..end];
+        match entity {
+            "amp" => { result.push('&') }
+            "lt" => { result.push('<') }
+            "gt" => { result.push('>') }
+            "quot" => { result.push('"') }
+            "apos" => { result.push('\'') }
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16)?
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse::<u32>()?
+                } else {
+                    return Err(anyhow!("Unknown entity &{}; in: {}", entity, s));
+                };
+                result.push(char::from_u32(code).ok_or(anyhow!("Invalid character reference &{}; in: {}", entity, s))?);
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::osm::changesets::xml::{Markup, read_markup, unescape};
+
+    #[test]
+    fn test_unescape() -> Result<(), anyhow::Error> {
+        assert_eq!(unescape("a &amp; b &lt;c&gt; &quot;d&quot; &apos;e&apos;")?, "a & b <c> \"d\" 'e'");
+        assert_eq!(unescape("&#1488;&#x5D1;")?, "אב");
+        assert!(unescape("&unknown;").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_markup() -> Result<(), anyhow::Error> {
+        let mut input = Cursor::new("<?xml version=\"1.0\"?>\n<!-- a > b -->\n<tag k=\"a>b\" v='x'/>text</osm>");
+        let mut buf = Vec::new();
+        assert_eq!(read_markup(&mut input, &mut buf)?, Some(Markup::Other));
+        assert_eq!(read_markup(&mut input, &mut buf)?, Some(Markup::Other));
+        assert_eq!(
+            read_markup(&mut input, &mut buf)?,
+            Some(
+                Markup::Start {
+                    name: "tag".to_string(),
+                    attributes: vec![("k".to_string(), "a>b".to_string()), ("v".to_string(), "x".to_string())],
+                    self_closing: true,
+                }
+            )
+        );
+        assert_eq!(read_markup(&mut input, &mut buf)?, Some(Markup::End { name: "osm".to_string() }));
+        assert_eq!(read_markup(&mut input, &mut buf)?, None);
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
 pub mod pbf;
+pub mod opl;
+pub mod o5m;
+pub mod changesets;
 pub mod model;
 #[cfg(feature = "apidb")]
 pub mod apidb_dump;
@@ -1,3 +1,77 @@
+use crate::osm::model::bounding_box::BoundingBox;
+use crate::osm::model::tag::Tag;
+
+/// Changeset metadata as published by the OSM API and in the changeset dumps
+#[derive(Debug, Clone)]
 pub struct Changeset {
+    id: i64,
+    created_at: i64,
+    closed_at: Option<i64>,
+    open: bool,
+    uid: i32,
+    user: String,
+    bounding_box: Option<BoundingBox>,
+    num_changes: i32,
+    tags: Vec<Tag>,
+}
+
+impl Changeset {
+    /// Create a new [Changeset]
+    ///
+    /// * created_at, closed_at - milliseconds since epoch. closed_at is None for open changesets
+    /// * bounding_box - None for changesets that do not touch any node
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(id: i64, created_at: i64, closed_at: Option<i64>, open: bool, uid: i32, user: String, bounding_box: Option<BoundingBox>, num_changes: i32, tags: Vec<Tag>) -> Changeset {
+        Changeset {
+            id,
+            created_at,
+            closed_at,
+            open,
+            uid,
+            user,
+            bounding_box,
+            num_changes,
+            tags,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn created_at(&self) -> i64 {
+        self.created_at
+    }
+
+    pub fn closed_at(&self) -> Option<i64> {
+        self.closed_at
+    }
+
+    pub fn open(&self) -> bool {
+        self.open
+    }
+
+    pub fn uid(&self) -> i32 {
+        self.uid
+    }
+
+    pub fn user(&self) -> &String {
+        &self.user
+    }
+
+    pub fn bounding_box(&self) -> Option<&BoundingBox> {
+        self.bounding_box.as_ref()
+    }
+
+    pub fn num_changes(&self) -> i32 {
+        self.num_changes
+    }
+
+    pub fn tags(&self) -> &Vec<Tag> {
+        &self.tags
+    }
 
-}
\ No newline at end of file
+    pub fn take_tags(&mut self) -> Vec<Tag> {
+        std::mem::take(&mut self.tags)
+    }
+}
@@ -1,5 +1,6 @@
 use std::cmp::Ordering;
 
+use crate::osm::model::element_kind::ElementKind;
 use crate::osm::model::node::Node;
 use crate::osm::model::relation::Relation;
 use crate::osm::model::way::Way;
@@ -56,6 +57,24 @@ impl Element {
         }
     }
 
+    /// The kind of this element, None for [Element::Sentinel]
+    pub fn kind(&self) -> Option<ElementKind> {
+        match self {
+            Element::Node { .. } => {
+                Some(ElementKind::Node)
+            }
+            Element::Way { .. } => {
+                Some(ElementKind::Way)
+            }
+            Element::Relation { .. } => {
+                Some(ElementKind::Relation)
+            }
+            Element::Sentinel => {
+                None
+            }
+        }
+    }
+
     pub fn is_node(&self) -> bool {
         match self {
             Element::Node { .. } => {
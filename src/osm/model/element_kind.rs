@@ -0,0 +1,14 @@
+/// The kind of an [Element](crate::osm::model::element::Element)
+///
+/// Kinds are ordered as in *.osm.pbf files sorted by type then id: nodes, ways, relations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ElementKind {
+    Node,
+    Way,
+    Relation,
+}
+
+impl ElementKind {
+    /// All element kinds in sort order
+    pub const ALL: [ElementKind; 3] = [ElementKind::Node, ElementKind::Way, ElementKind::Relation];
+}
@@ -1,5 +1,6 @@
 pub mod bounding_box;
 pub mod element;
+pub mod element_kind;
 pub mod relation;
 pub mod coordinate;
 pub mod way;
@@ -0,0 +1,270 @@
+use std::io::{BufRead, ErrorKind};
+
+use anyhow::anyhow;
+
+use crate::osm::model::coordinate::Coordinate;
+use crate::osm::model::element::Element;
+use crate::osm::model::node::Node;
+use crate::osm::model::relation::{Member, MemberData, Relation};
+use crate::osm::model::tag::Tag;
+use crate::osm::model::way::Way;
+use crate::osm::o5m::{COORDINATE_SCALE, END_OF_FILE, NODE, RELATION, WAY};
+use crate::osm::o5m::string_table::{MAX_STRING_SIZE, StringTable};
+use crate::osm::o5m::varint;
+
+/// Read the next dataset as (type, payload)
+///
+/// The types 0xf0 to 0xff are single byte datasets without a length. The reset marker and the other
+/// single byte datasets are returned with an empty payload. Returns None at the end of file marker
+/// or at the end of input.
+pub(crate) fn read_dataset(reader: &mut impl BufRead) -> Result<Option<(u8, Vec<u8>)>, anyhow::Error> {
+    let mut dataset_type = [0_u8; 1];
+    match reader.read_exact(&mut dataset_type) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+            return Ok(None);
+        }
+        Err(e) => {
+            return Err(e.into());
+        }
+    }
+    match dataset_type[0] {
+        END_OF_FILE => {
+            Ok(None)
+        }
+        t @ 0xf0..=0xff => {
+            Ok(Some((t, Vec::new())))
+        }
+        t => {
+            let length = read_length(reader)?;
+            let mut payload = vec![0_u8; length];
+            reader.read_exact(&mut payload)?;
+            Ok(Some((t, payload)))
+        }
+    }
+}
+
+fn read_length(reader: &mut impl BufRead) -> Result<usize, anyhow::Error> {
+    let mut result = 0_u64;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0_u8; 1];
+        reader.read_exact(&mut byte)?;
+        if shift > 63 {
+            return Err(anyhow!("Dataset length too long"));
+        }
+        result |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(result as usize);
+        }
+        shift += 7;
+    }
+}
+
+/// Decode the element datasets of an o5m or o5c stream
+///
+/// Ids, timestamps, changesets, coordinates and references are delta coded against the previous
+/// element. The deltas and the string table are cleared by the reset marker.
+pub(crate) struct Decoder {
+    string_table: StringTable,
+    id: i64,
+    timestamp: i64,
+    changeset: i64,
+    lon: i64,
+    lat: i64,
+    // node, way and relation references
+    refs: [i64; 3],
+}
+
+struct Metadata {
+    version: i32,
+    timestamp: i64,
+    changeset: i64,
+    uid: i32,
+    user: String,
+}
+
+impl Decoder {
+    pub(crate) fn new() -> Decoder {
+        Decoder {
+            string_table: StringTable::for_decoder(),
+            id: 0,
+            timestamp: 0,
+            changeset: 0,
+            lon: 0,
+            lat: 0,
+            refs: [0; 3],
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.string_table.reset();
+        self.id = 0;
+        self.timestamp = 0;
+        self.changeset = 0;
+        self.lon = 0;
+        self.lat = 0;
+        self.refs = [0; 3];
+    }
+
+    /// Decode a node, way or relation dataset. An element without content is deleted, as in o5c
+    /// change files.
+    pub(crate) fn decode(&mut self, dataset_type: u8, data: &[u8]) -> Result<Element, anyhow::Error> {
+        let mut position = 0;
+        self.id += varint::read_signed(data, &mut position)?;
+        let id = self.id;
+        let metadata = self.read_metadata(data, &mut position)?;
+        let visible = position < data.len();
+        match dataset_type {
+            NODE => {
+                let mut coordinate = Coordinate::new(0.0, 0.0);
+                if visible {
+                    self.lon += varint::read_signed(data, &mut position)?;
+                    self.lat += varint::read_signed(data, &mut position)?;
+                    coordinate = Coordinate::new(self.lat as f64 / COORDINATE_SCALE, self.lon as f64 / COORDINATE_SCALE);
+                }
+                let tags = self.read_tags(data, &mut position)?;
+                Ok(
+                    Element::Node {
+                        node: Node::new(id, metadata.version, coordinate, metadata.timestamp, metadata.changeset, metadata.uid, metadata.user, visible, tags)
+                    }
+                )
+            }
+            WAY => {
+                let mut refs = Vec::new();
+                if visible {
+                    let end = Self::section_end(data, &mut position)?;
+                    while position < end {
+                        self.refs[0] += varint::read_signed(data, &mut position)?;
+                        refs.push(self.refs[0]);
+                    }
+                }
+                let tags = self.read_tags(data, &mut position)?;
+                Ok(
+                    Element::Way {
+                        way: Way::new(id, metadata.version, metadata.timestamp, metadata.changeset, metadata.uid, metadata.user, visible, refs, tags)
+                    }
+                )
+            }
+            RELATION => {
+                let mut members = Vec::new();
+                if visible {
+                    let end = Self::section_end(data, &mut position)?;
+                    while position < end {
+                        let delta = varint::read_signed(data, &mut position)?;
+                        let strings = self.read_strings(data, &mut position, 1)?;
+                        let type_and_role = strings[0].as_slice();
+                        let member_type = *type_and_role.first().ok_or(anyhow!("Missing member type"))?;
+                        let index = match member_type {
+                            b'0'..=b'2' => (member_type - b'0') as usize,
+                            _ => return Err(anyhow!("Invalid member type {}", member_type as char)),
+                        };
+                        self.refs[index] += delta;
+                        let member = MemberData::new(self.refs[index], Self::to_string(&type_and_role[1..])?);
+                        match index {
+                            0 => {
+                                members.push(Member::Node { member });
+                            }
+                            1 => {
+                                members.push(Member::Way { member });
+                            }
+                            _ => {
+                                members.push(Member::Relation { member });
+                            }
+                        }
+                    }
+                }
+                let tags = self.read_tags(data, &mut position)?;
+                Ok(
+                    Element::Relation {
+                        relation: Relation::new(id, metadata.version, metadata.timestamp, metadata.changeset, metadata.uid, metadata.user, visible, members, tags)
+                    }
+                )
+            }
+            _ => {
+                Err(anyhow!("Not an element dataset: 0x{:02x}", dataset_type))
+            }
+        }
+    }
+
+    fn section_end(data: &[u8], position: &mut usize) -> Result<usize, anyhow::Error> {
+        let length = varint::read_unsigned(data, position)? as usize;
+        let end = *position + length;
+        if end > data.len() {
+            return Err(anyhow!("Reference section exceeds the dataset"));
+        }
+        Ok(end)
+    }
+
+    fn read_metadata(&mut self, data: &[u8], position: &mut usize) -> Result<Metadata, anyhow::Error> {
+        let mut metadata = Metadata {
+            version: 0,
+            timestamp: -1,
+            changeset: -1,
+            uid: -1,
+            user: String::new(),
+        };
+        metadata.version = varint::read_unsigned(data, position)? as i32;
+        if metadata.version != 0 {
+            self.timestamp += varint::read_signed(data, position)?;
+            // a zero timestamp is written for a missing timestamp, which defaults to -1 as in
+            // *.osm.pbf, and is followed by no changeset and author
+            if self.timestamp != 0 {
+                metadata.timestamp = self.timestamp * 1000;
+                self.changeset += varint::read_signed(data, position)?;
+                metadata.changeset = self.changeset;
+                // the uid is stored as a varint in the first string of the author pair
+                let strings = self.read_strings(data, position, 2)?;
+                // an empty uid is written for a missing uid, which defaults to -1 as in *.osm.pbf
+                metadata.uid = match strings[0].is_empty() {
+                    true => -1,
+                    false => varint::read_unsigned(&strings[0], &mut 0)? as i32,
+                };
+                metadata.user = Self::to_string(&strings[1])?;
+            }
+        }
+        Ok(metadata)
+    }
+
+    fn read_tags(&mut self, data: &[u8], position: &mut usize) -> Result<Vec<Tag>, anyhow::Error> {
+        let mut tags = Vec::new();
+        while *position < data.len() {
+            let strings = self.read_strings(data, position, 2)?;
+            tags.push(Tag::new(Self::to_string(&strings[0])?, Self::to_string(&strings[1])?));
+        }
+        Ok(tags)
+    }
+
+    /// Read an inline or referenced string or string pair
+    fn read_strings(&mut self, data: &[u8], position: &mut usize, count: usize) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+        let reference = varint::read_unsigned(data, position)? as usize;
+        let entry = match reference {
+            0 => {
+                let start = *position;
+                for _ in 0..count {
+                    let terminator = data[*position..].iter().position(|b| *b == 0)
+                        .ok_or(anyhow!("Unterminated string"))?;
+                    *position += terminator + 1;
+                }
+                let entry = &data[start..*position];
+                if entry.len() - count <= MAX_STRING_SIZE {
+                    self.string_table.add(entry);
+                }
+                entry
+            }
+            _ => {
+                self.string_table.get(reference)?
+            }
+        };
+        Ok(
+            entry.split(|b| *b == 0)
+                .take(count)
+                .map(|s| s.to_vec())
+                .collect()
+        )
+    }
+
+    fn to_string(bytes: &[u8]) -> Result<String, anyhow::Error> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
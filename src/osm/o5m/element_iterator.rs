@@ -0,0 +1,52 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use crate::osm::model::element::Element;
+use crate::osm::o5m::{NODE, RELATION, RESET, WAY};
+use crate::osm::o5m::decoder;
+use crate::osm::o5m::decoder::Decoder;
+
+/// Iterate over elements in an o5m or o5c file
+///
+/// Panics on a dataset that cannot be decoded.
+pub struct ElementIterator {
+    path: PathBuf,
+    reader: BufReader<File>,
+    decoder: Decoder,
+}
+
+impl ElementIterator {
+    pub(crate) fn new(path: PathBuf, file: File) -> ElementIterator {
+        ElementIterator {
+            path,
+            reader: BufReader::new(file),
+            decoder: Decoder::new(),
+        }
+    }
+}
+
+impl Iterator for ElementIterator {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (dataset_type, payload) = decoder::read_dataset(&mut self.reader)
+                .unwrap_or_else(|e| panic!("Failed to read a dataset from {:?}: {}", self.path, e))?;
+            match dataset_type {
+                RESET => {
+                    self.decoder.reset();
+                }
+                NODE | WAY | RELATION => {
+                    return Some(
+                        self.decoder.decode(dataset_type, &payload)
+                            .unwrap_or_else(|e| panic!("Failed to decode a dataset from {:?}: {}", self.path, e))
+                    );
+                }
+                _ => {
+                    // header, bounding box, timestamp, sync and jump datasets
+                }
+            }
+        }
+    }
+}
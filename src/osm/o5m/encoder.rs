@@ -0,0 +1,164 @@
+use crate::osm::model::element::Element;
+use crate::osm::model::relation::Member;
+use crate::osm::model::tag::Tag;
+use crate::osm::o5m::{COORDINATE_SCALE, NODE, RELATION, WAY};
+use crate::osm::o5m::string_table::{MAX_STRING_SIZE, StringTable};
+use crate::osm::o5m::varint;
+
+/// Encode elements into o5m datasets. The counterpart of [Decoder](crate::osm::o5m::decoder::Decoder)
+pub(crate) struct Encoder {
+    string_table: StringTable,
+    id: i64,
+    timestamp: i64,
+    changeset: i64,
+    lon: i64,
+    lat: i64,
+    // node, way and relation references
+    refs: [i64; 3],
+    entry: Vec<u8>,
+    section: Vec<u8>,
+}
+
+impl Encoder {
+    pub(crate) fn new() -> Encoder {
+        Encoder {
+            string_table: StringTable::for_encoder(),
+            id: 0,
+            timestamp: 0,
+            changeset: 0,
+            lon: 0,
+            lat: 0,
+            refs: [0; 3],
+            entry: Vec::new(),
+            section: Vec::new(),
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.string_table.reset();
+        self.id = 0;
+        self.timestamp = 0;
+        self.changeset = 0;
+        self.lon = 0;
+        self.lat = 0;
+        self.refs = [0; 3];
+    }
+
+    /// Encode the payload of an element dataset and return the dataset type. Invisible elements
+    /// are written without content, as deleted elements in o5c change files.
+    pub(crate) fn encode(&mut self, element: &Element, out: &mut Vec<u8>) -> Option<u8> {
+        match element {
+            Element::Node { node } => {
+                self.write_id_and_metadata(node.id(), node.version(), node.timestamp(), node.changeset(), node.uid(), node.user(), out);
+                if node.visible() {
+                    let lon = (node.coordinate().lon() * COORDINATE_SCALE).round() as i64;
+                    let lat = (node.coordinate().lat() * COORDINATE_SCALE).round() as i64;
+                    varint::write_signed(lon - self.lon, out);
+                    varint::write_signed(lat - self.lat, out);
+                    self.lon = lon;
+                    self.lat = lat;
+                    self.write_tags(node.tags(), out);
+                }
+                Some(NODE)
+            }
+            Element::Way { way } => {
+                self.write_id_and_metadata(way.id(), way.version(), way.timestamp(), way.changeset(), way.uid(), way.user(), out);
+                if way.visible() {
+                    let mut section = std::mem::take(&mut self.section);
+                    section.clear();
+                    for node_ref in way.refs() {
+                        varint::write_signed(node_ref - self.refs[0], &mut section);
+                        self.refs[0] = *node_ref;
+                    }
+                    varint::write_unsigned(section.len() as u64, out);
+                    out.extend_from_slice(&section);
+                    self.section = section;
+                    self.write_tags(way.tags(), out);
+                }
+                Some(WAY)
+            }
+            Element::Relation { relation } => {
+                self.write_id_and_metadata(relation.id(), relation.version(), relation.timestamp(), relation.changeset(), relation.uid(), relation.user(), out);
+                if relation.visible() {
+                    let mut section = std::mem::take(&mut self.section);
+                    section.clear();
+                    for member in relation.members() {
+                        let (index, member_data) = match member {
+                            Member::Node { member } => (0, member),
+                            Member::Way { member } => (1, member),
+                            Member::Relation { member } => (2, member),
+                        };
+                        varint::write_signed(member_data.id() - self.refs[index], &mut section);
+                        self.refs[index] = member_data.id();
+                        self.entry.clear();
+                        self.entry.push(b'0' + index as u8);
+                        self.entry.extend_from_slice(member_data.role().as_bytes());
+                        self.entry.push(0);
+                        self.write_entry(1, &mut section);
+                    }
+                    varint::write_unsigned(section.len() as u64, out);
+                    out.extend_from_slice(&section);
+                    self.section = section;
+                    self.write_tags(relation.tags(), out);
+                }
+                Some(RELATION)
+            }
+            Element::Sentinel => {
+                None
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn write_id_and_metadata(&mut self, id: i64, version: i32, timestamp: i64, changeset: i64, uid: i32, user: &str, out: &mut Vec<u8>) {
+        varint::write_signed(id - self.id, out);
+        self.id = id;
+        // without a version there is no metadata
+        varint::write_unsigned(version.max(0) as u64, out);
+        if version > 0 {
+            let timestamp = timestamp.max(0) / 1000;
+            varint::write_signed(timestamp - self.timestamp, out);
+            self.timestamp = timestamp;
+            if timestamp != 0 {
+                varint::write_signed(changeset - self.changeset, out);
+                self.changeset = changeset;
+                self.entry.clear();
+                if uid > 0 {
+                    varint::write_unsigned(uid as u64, &mut self.entry);
+                }
+                self.entry.push(0);
+                self.entry.extend_from_slice(user.as_bytes());
+                self.entry.push(0);
+                self.write_entry(2, out);
+            }
+        }
+    }
+
+    fn write_tags(&mut self, tags: &[Tag], out: &mut Vec<u8>) {
+        for tag in tags {
+            self.entry.clear();
+            self.entry.extend_from_slice(tag.k().as_bytes());
+            self.entry.push(0);
+            self.entry.extend_from_slice(tag.v().as_bytes());
+            self.entry.push(0);
+            self.write_entry(2, out);
+        }
+    }
+
+    /// Write the current entry of `count` zero terminated strings as a reference, if it is in the
+    /// string table, or inline
+    fn write_entry(&mut self, count: usize, out: &mut Vec<u8>) {
+        match self.string_table.find(&self.entry) {
+            Some(reference) => {
+                varint::write_unsigned(reference as u64, out);
+            }
+            None => {
+                out.push(0);
+                out.extend_from_slice(&self.entry);
+                if self.entry.len() - count <= MAX_STRING_SIZE {
+                    self.string_table.add(&self.entry);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,23 @@
+pub mod reader;
+pub mod writer;
+pub mod element_iterator;
+
+pub(crate) mod varint;
+pub(crate) mod string_table;
+pub(crate) mod decoder;
+pub(crate) mod encoder;
+
+pub(crate) const NODE: u8 = 0x10;
+pub(crate) const WAY: u8 = 0x11;
+pub(crate) const RELATION: u8 = 0x12;
+pub(crate) const BOUNDING_BOX: u8 = 0xdb;
+pub(crate) const FILE_TIMESTAMP: u8 = 0xdc;
+pub(crate) const HEADER: u8 = 0xe0;
+pub(crate) const END_OF_FILE: u8 = 0xfe;
+pub(crate) const RESET: u8 = 0xff;
+
+pub(crate) const O5M_HEADER: &[u8] = b"o5m2";
+pub(crate) const O5C_HEADER: &[u8] = b"o5c2";
+
+// coordinates are stored in 100 nanodegree units
+pub(crate) const COORDINATE_SCALE: f64 = 1E7f64;
@@ -0,0 +1,120 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+
+use crate::osm::model::bounding_box::BoundingBox;
+use crate::osm::o5m::{BOUNDING_BOX, COORDINATE_SCALE, FILE_TIMESTAMP, HEADER, NODE, O5C_HEADER, O5M_HEADER, RELATION, RESET, WAY};
+use crate::osm::o5m::decoder;
+use crate::osm::o5m::element_iterator::ElementIterator;
+use crate::osm::o5m::varint;
+use crate::osm::pbf::file_info::FileInfo;
+
+/// o5m and o5c file reader
+///
+/// Read the [o5m](https://wiki.openstreetmap.org/wiki/O5m) format used by osmconvert and osmfilter
+/// and its o5c change file variant, in which deleted elements are returned as not visible.
+/// The bounding box and the file timestamp from the header are available in [Reader::info] as the
+/// bounding box and the replication timestamp.
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::o5m;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let input_path = PathBuf::from("./target/results/niue-230109.o5m");
+///     let reader = o5m::reader::Reader::new(&input_path)?;
+///     for element in reader.elements()? {
+///         println!("{:?}", element);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct Reader {
+    path: PathBuf,
+    info: FileInfo,
+    change: bool,
+}
+
+impl Reader {
+    /// Create a new [Reader]
+    pub fn new(path: &Path) -> Result<Reader, anyhow::Error> {
+        let file = File::open(path)
+            .with_context(|| anyhow!("path: {}", path.display()))?;
+        let mut reader = BufReader::new(file);
+        let mut info = FileInfo::default();
+        let mut change = false;
+
+        match decoder::read_dataset(&mut reader)? {
+            Some((RESET, _)) => {}
+            _ => {
+                return Err(anyhow!("Not an o5m file: {}", path.display()));
+            }
+        }
+        while let Some((dataset_type, payload)) = decoder::read_dataset(&mut reader)? {
+            match dataset_type {
+                HEADER => {
+                    match payload.as_slice() {
+                        O5M_HEADER => {
+                            change = false;
+                        }
+                        O5C_HEADER => {
+                            change = true;
+                        }
+                        _ => {
+                            return Err(anyhow!("Unsupported o5m header {:?} in {}", String::from_utf8_lossy(&payload), path.display()));
+                        }
+                    }
+                }
+                BOUNDING_BOX => {
+                    let mut position = 0;
+                    let mut coordinates = [0.0_f64; 4];
+                    for coordinate in coordinates.iter_mut() {
+                        *coordinate = varint::read_signed(&payload, &mut position)? as f64 / COORDINATE_SCALE;
+                    }
+                    info.with_bounding_box(&Some(BoundingBox::new(coordinates[0], coordinates[1], coordinates[2], coordinates[3])));
+                }
+                FILE_TIMESTAMP => {
+                    let timestamp = varint::read_signed(&payload, &mut 0)?;
+                    info.with_osmosis_replication_timestamp(&Some(timestamp));
+                }
+                NODE | WAY | RELATION | RESET => {
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(
+            Reader {
+                path: path.to_path_buf(),
+                info,
+                change,
+            }
+        )
+    }
+
+    /// Iterator used to iterate over elements
+    pub fn elements(&self) -> Result<ElementIterator, anyhow::Error> {
+        let file = File::open(&self.path)
+            .with_context(|| anyhow!("path: {}", self.path.display()))?;
+        Ok(
+            ElementIterator::new(self.path.clone(), file)
+        )
+    }
+
+    /// The header information, the bounding box and the file timestamp
+    pub fn info(&self) -> &FileInfo {
+        &self.info
+    }
+
+    /// True for o5c change files
+    pub fn is_change(&self) -> bool {
+        self.change
+    }
+
+    /// Input path
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+
+/// The number of most recent strings that can be referenced
+pub(crate) const STRING_TABLE_SIZE: usize = 15000;
+
+/// Strings longer than this are written inline and are not added to the table
+pub(crate) const MAX_STRING_SIZE: usize = 250;
+
+/// The o5m reference table of recently used strings and string pairs
+///
+/// A string is referenced by its position counted back from the most recently added string,
+/// starting at 1. The table is cleared by a reset.
+pub(crate) struct StringTable {
+    entries: Vec<Vec<u8>>,
+    next: usize,
+    // the encoder needs to find strings, the decoder does not
+    index: Option<HashMap<Vec<u8>, usize>>,
+    added: usize,
+}
+
+impl StringTable {
+    pub(crate) fn for_decoder() -> StringTable {
+        StringTable {
+            entries: Vec::with_capacity(STRING_TABLE_SIZE),
+            next: 0,
+            index: None,
+            added: 0,
+        }
+    }
+
+    pub(crate) fn for_encoder() -> StringTable {
+        StringTable {
+            index: Some(HashMap::new()),
+            ..Self::for_decoder()
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.entries.clear();
+        self.next = 0;
+        self.added = 0;
+        if let Some(index) = self.index.as_mut() {
+            index.clear();
+        }
+    }
+
+    /// Add the encoded string or string pair, including the terminating zeros
+    pub(crate) fn add(&mut self, entry: &[u8]) {
+        if self.entries.len() < STRING_TABLE_SIZE {
+            self.entries.push(entry.to_vec());
+        } else {
+            let evicted = std::mem::replace(&mut self.entries[self.next], entry.to_vec());
+            // keep the index bounded by the table size
+            let evicted_added = self.added + 1 - STRING_TABLE_SIZE;
+            if let Some(index) = self.index.as_mut() {
+                if index.get(&evicted) == Some(&evicted_added) {
+                    index.remove(&evicted);
+                }
+            }
+        }
+        self.next = (self.next + 1) % STRING_TABLE_SIZE;
+        self.added += 1;
+        if let Some(index) = self.index.as_mut() {
+            index.insert(entry.to_vec(), self.added);
+        }
+    }
+
+    pub(crate) fn get(&self, reference: usize) -> Result<&[u8], anyhow::Error> {
+        if reference == 0 || reference > self.entries.len() {
+            return Err(anyhow!("Invalid string reference {reference}"));
+        }
+        let position = (self.next + STRING_TABLE_SIZE - reference) % STRING_TABLE_SIZE;
+        Ok(&self.entries[position])
+    }
+
+    /// The reference to a previously added entry, if it is still in the table
+    pub(crate) fn find(&self, entry: &[u8]) -> Option<usize> {
+        let added = *self.index.as_ref()?.get(entry)?;
+        let reference = self.added - added + 1;
+        if reference <= STRING_TABLE_SIZE {
+            Some(reference)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_string_table() {
+        let mut string_table = StringTable::for_encoder();
+        string_table.add(b"a\0b\0");
+        string_table.add(b"c\0");
+        assert_eq!(string_table.find(b"c\0"), Some(1));
+        assert_eq!(string_table.find(b"a\0b\0"), Some(2));
+        assert_eq!(string_table.get(2).unwrap(), b"a\0b\0");
+        assert!(string_table.get(3).is_err());
+
+        for i in 0..STRING_TABLE_SIZE {
+            string_table.add(format!("{i}\0").as_bytes());
+        }
+        assert_eq!(string_table.find(b"a\0b\0"), None);
+        assert_eq!(string_table.find(b"0\0"), Some(STRING_TABLE_SIZE));
+        assert_eq!(string_table.get(STRING_TABLE_SIZE).unwrap(), b"0\0");
+
+        string_table.reset();
+        assert_eq!(string_table.find(b"0\0"), None);
+        assert!(string_table.get(1).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+use anyhow::anyhow;
+
+/// Read an unsigned varint, 7 bits per byte with the least significant group first
+pub(crate) fn read_unsigned(data: &[u8], position: &mut usize) -> Result<u64, anyhow::Error> {
+    let mut result = 0_u64;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*position).ok_or(anyhow!("Unexpected end of data in varint"))?;
+        *position += 1;
+        if shift > 63 {
+            return Err(anyhow!("Varint too long"));
+        }
+        result |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+        shift += 7;
+    }
+}
+
+/// Read a signed varint, the sign is stored in the least significant bit
+pub(crate) fn read_signed(data: &[u8], position: &mut usize) -> Result<i64, anyhow::Error> {
+    let value = read_unsigned(data, position)?;
+    Ok(((value >> 1) as i64) ^ -((value & 1) as i64))
+}
+
+pub(crate) fn write_unsigned(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+pub(crate) fn write_signed(value: i64, out: &mut Vec<u8>) {
+    write_unsigned(((value << 1) ^ (value >> 63)) as u64, out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        for value in [0_i64, 1, -1, 63, -64, 64, -65, 123456789, -123456789, i64::MAX, i64::MIN] {
+            let mut out = Vec::new();
+            write_signed(value, &mut out);
+            let mut position = 0;
+            assert_eq!(read_signed(&out, &mut position).unwrap(), value);
+            assert_eq!(position, out.len());
+        }
+        // examples from the o5m specification
+        let mut out = Vec::new();
+        write_unsigned(323, &mut out);
+        assert_eq!(out, vec![0xc3, 0x02]);
+        out.clear();
+        write_signed(-65, &mut out);
+        assert_eq!(out, vec![0x81, 0x01]);
+        assert!(read_unsigned(&[0x80], &mut 0).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+
+use crate::osm::model::element::Element;
+use crate::osm::o5m::{BOUNDING_BOX, COORDINATE_SCALE, END_OF_FILE, FILE_TIMESTAMP, HEADER, O5C_HEADER, O5M_HEADER, RESET};
+use crate::osm::o5m::encoder::Encoder;
+use crate::osm::o5m::varint;
+use crate::osm::pbf::file_info::FileInfo;
+
+/// o5m and o5c file writer
+///
+/// Write the [o5m](https://wiki.openstreetmap.org/wiki/O5m) format used by osmconvert and
+/// osmfilter. The writer follows the contract of [pbf::writer::Writer](crate::osm::pbf::writer::Writer):
+/// call [Writer::write_header], write ordered elements and [Writer::close] in the end. The bounding
+/// box and the replication timestamp of the [FileInfo] are written to the header. A reset is
+/// written before each change of the element type. With [Writer::with_change] an o5c change file is
+/// written, in which elements that are not visible are written as deleted.
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::o5m;
+/// use osm_io::osm::pbf;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let input_path = PathBuf::from("./tests/fixtures/malta-230109.osm.pbf");
+///     let output_path = PathBuf::from("./target/results/malta-230109.o5m");
+///     let reader = pbf::reader::Reader::new(&input_path)?;
+///     let mut writer = o5m::writer::Writer::new(output_path, reader.info().clone())?;
+///     writer.write_header()?;
+///     for element in reader.elements()? {
+///         writer.write_element(element)?;
+///     }
+///     writer.close()?;
+///     Ok(())
+/// }
+/// ```
+pub struct Writer {
+    path: PathBuf,
+    file_info: FileInfo,
+    change: bool,
+    writer: BufWriter<File>,
+    encoder: Encoder,
+    last_type: Option<u8>,
+    payload: Vec<u8>,
+}
+
+impl Writer {
+    /// Create a new [Writer]
+    pub fn new(path: PathBuf, file_info: FileInfo) -> Result<Writer, anyhow::Error> {
+        let file = File::create(&path)
+            .with_context(|| anyhow!("path: {}", path.display()))?;
+        Ok(
+            Writer {
+                path,
+                file_info,
+                change: false,
+                writer: BufWriter::new(file),
+                encoder: Encoder::new(),
+                last_type: None,
+                payload: Vec::with_capacity(1024),
+            }
+        )
+    }
+
+    /// Write an o5c change file instead of an o5m file. Must be set before writing the header
+    pub fn with_change(&mut self, change: bool) {
+        self.change = change;
+    }
+
+    /// Write the file header
+    pub fn write_header(&mut self) -> Result<(), anyhow::Error> {
+        self.writer.write_all(&[RESET])?;
+        let header = match self.change {
+            true => O5C_HEADER,
+            false => O5M_HEADER,
+        };
+        self.write_dataset(HEADER, header.to_vec())?;
+
+        if let Some(timestamp) = self.file_info.osmosis_replication_timestamp() {
+            let mut payload = Vec::new();
+            varint::write_signed(*timestamp, &mut payload);
+            self.write_dataset(FILE_TIMESTAMP, payload)?;
+        }
+
+        if let Some(bounding_box) = self.file_info.bounding_box() {
+            let mut payload = Vec::new();
+            for coordinate in [bounding_box.left(), bounding_box.bottom(), bounding_box.right(), bounding_box.top()] {
+                varint::write_signed((coordinate * COORDINATE_SCALE).round() as i64, &mut payload);
+            }
+            self.write_dataset(BOUNDING_BOX, payload)?;
+        }
+        Ok(())
+    }
+
+    fn write_dataset(&mut self, dataset_type: u8, payload: Vec<u8>) -> Result<(), anyhow::Error> {
+        let mut length = Vec::new();
+        varint::write_unsigned(payload.len() as u64, &mut length);
+        self.writer.write_all(&[dataset_type])
+            .and_then(|_| self.writer.write_all(&length))
+            .and_then(|_| self.writer.write_all(&payload))
+            .with_context(|| anyhow!("path: {}", self.path.display()))?;
+        self.payload = payload;
+        Ok(())
+    }
+
+    /// Write element
+    ///
+    /// Elements must be ordered, that is each element must be less then or equal to the following
+    /// element
+    pub fn write_element(&mut self, element: Element) -> Result<(), anyhow::Error> {
+        let mut payload = std::mem::take(&mut self.payload);
+        payload.clear();
+        if let Some(dataset_type) = self.encoder.encode(&element, &mut payload) {
+            if self.last_type != Some(dataset_type) {
+                // the element type changed, start over with a fresh string table and deltas, and
+                // encode the element again against the reset state
+                if self.last_type.is_some() {
+                    self.writer.write_all(&[RESET])?;
+                }
+                self.encoder.reset();
+                payload.clear();
+                self.encoder.encode(&element, &mut payload);
+                self.last_type = Some(dataset_type);
+            }
+            self.write_dataset(dataset_type, payload)?;
+        } else {
+            self.payload = payload;
+        }
+        Ok(())
+    }
+
+    /// Write elements
+    ///
+    /// Elements must be ordered, that is each element must be less then or equal to the following
+    /// element
+    pub fn write_elements(&mut self, elements: Vec<Element>) -> Result<(), anyhow::Error> {
+        for element in elements {
+            self.write_element(element)?;
+        }
+        Ok(())
+    }
+
+    /// Flush the internal buffers.
+    ///
+    /// Must be called in the end to write the end of file marker
+    pub fn close(&mut self) -> Result<(), anyhow::Error> {
+        self.writer.write_all(&[END_OF_FILE])
+            .and_then(|_| self.writer.flush())
+            .with_context(|| anyhow!("path: {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Output path
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
@@ -0,0 +1,45 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines};
+use std::path::PathBuf;
+
+use crate::osm::model::element::Element;
+use crate::osm::opl::format;
+
+/// Iterate over elements in an OPL file
+///
+/// Empty lines are skipped. Panics on a line that is not valid OPL, reporting the path and the line
+/// number.
+pub struct ElementIterator {
+    path: PathBuf,
+    lines: Lines<BufReader<File>>,
+    line_number: usize,
+}
+
+impl ElementIterator {
+    pub(crate) fn new(path: PathBuf, file: File) -> ElementIterator {
+        ElementIterator {
+            path,
+            lines: BufReader::new(file).lines(),
+            line_number: 0,
+        }
+    }
+}
+
+impl Iterator for ElementIterator {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?
+                .unwrap_or_else(|e| panic!("Failed to read {:?}: {}", self.path, e));
+            self.line_number += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            return Some(
+                format::parse_element(&line)
+                    .unwrap_or_else(|e| panic!("Failed to parse line {} in {:?}: {}", self.line_number, self.path, e))
+            );
+        }
+    }
+}
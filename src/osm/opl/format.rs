@@ -0,0 +1,368 @@
+use std::fmt::Write;
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, SecondsFormat};
+
+use crate::osm::model::coordinate::Coordinate;
+use crate::osm::model::element::Element;
+use crate::osm::model::node::Node;
+use crate::osm::model::relation::{Member, MemberData, Relation};
+use crate::osm::model::tag::Tag;
+use crate::osm::model::way::Way;
+
+/// Characters written as is, the same set as used by osmium. All other characters, including the
+/// space, ',', '=', '@' and '%' separators are written as %<hex code point>%
+fn is_plain(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0021..=0x0024 | 0x0026..=0x002b | 0x002d..=0x003c | 0x003e..=0x003f | 0x0041..=0x007e | 0x00a1..=0x00ac | 0x00ae..=0x05ff
+    )
+}
+
+pub(crate) fn escape(s: &str, out: &mut String) {
+    for c in s.chars() {
+        if is_plain(c) {
+            out.push(c);
+        } else {
+            write!(out, "%{:x}%", c as u32).unwrap();
+        }
+    }
+}
+
+pub(crate) fn unescape(s: &str) -> Result<String, anyhow::Error> {
+    if !s.contains('%') {
+        return Ok(s.to_string());
+    }
+    let mut result = String::with_capacity(s.len());
+    let mut parts = s.split('%');
+    // the text before the first escape sequence
+    result.push_str(parts.next().unwrap_or_default());
+    loop {
+        match (parts.next(), parts.next()) {
+            (None, _) => {
+                break;
+            }
+            (Some(hex), Some(text)) => {
+                let code = u32::from_str_radix(hex, 16)
+                    .with_context(|| anyhow!("Invalid escape sequence %{hex}% in {s}"))?;
+                let c = char::from_u32(code)
+                    .ok_or(anyhow!("Invalid code point %{hex}% in {s}"))?;
+                result.push(c);
+                result.push_str(text);
+            }
+            (Some(_), None) => {
+                return Err(anyhow!("Unterminated escape sequence in {s}"));
+            }
+        }
+    }
+    Ok(result)
+}
+
+fn format_coordinate(value: f64, out: &mut String) {
+    let formatted = format!("{:.7}", value);
+    let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+    match trimmed {
+        "-0" => {
+            out.push('0');
+        }
+        _ => {
+            out.push_str(trimmed);
+        }
+    }
+}
+
+fn format_timestamp(timestamp: i64, out: &mut String) {
+    // a missing timestamp is written as an empty value
+    if timestamp > 0 {
+        if let Some(datetime) = DateTime::from_timestamp_millis(timestamp) {
+            out.push_str(&datetime.to_rfc3339_opts(SecondsFormat::Secs, true));
+        }
+    }
+}
+
+fn parse_timestamp(s: &str) -> Result<i64, anyhow::Error> {
+    match s {
+        "" => {
+            Ok(-1)
+        }
+        _ => {
+            Ok(DateTime::parse_from_rfc3339(s)?.timestamp_millis())
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn format_common(kind: char, id: i64, version: i32, visible: bool, changeset: i64, timestamp: i64, uid: i32, user: &str, tags: &[Tag], out: &mut String) {
+    write!(out, "{kind}{id} v{version} d{} c{changeset} t", if visible { 'V' } else { 'D' }).unwrap();
+    format_timestamp(timestamp, out);
+    write!(out, " i{uid} u").unwrap();
+    escape(user, out);
+    out.push_str(" T");
+    for (i, tag) in tags.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        escape(tag.k(), out);
+        out.push('=');
+        escape(tag.v(), out);
+    }
+}
+
+fn member_kind(member: &Member) -> (char, &MemberData) {
+    match member {
+        Member::Node { member } => {
+            ('n', member)
+        }
+        Member::Way { member } => {
+            ('w', member)
+        }
+        Member::Relation { member } => {
+            ('r', member)
+        }
+    }
+}
+
+/// Format an element as a single OPL line, without the line terminator. Nothing is written for
+/// [Element::Sentinel]
+pub(crate) fn format_element(element: &Element, out: &mut String) {
+    match element {
+        Element::Node { node } => {
+            format_common('n', node.id(), node.version(), node.visible(), node.changeset(), node.timestamp(), node.uid(), node.user(), node.tags(), out);
+            out.push_str(" x");
+            format_coordinate(node.coordinate().lon(), out);
+            out.push_str(" y");
+            format_coordinate(node.coordinate().lat(), out);
+        }
+        Element::Way { way } => {
+            format_common('w', way.id(), way.version(), way.visible(), way.changeset(), way.timestamp(), way.uid(), way.user(), way.tags(), out);
+            out.push_str(" N");
+            for (i, node_ref) in way.refs().iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write!(out, "n{node_ref}").unwrap();
+            }
+        }
+        Element::Relation { relation } => {
+            format_common('r', relation.id(), relation.version(), relation.visible(), relation.changeset(), relation.timestamp(), relation.uid(), relation.user(), relation.tags(), out);
+            out.push_str(" M");
+            for (i, member) in relation.members().iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                let (kind, member_data) = member_kind(member);
+                write!(out, "{kind}{}@", member_data.id()).unwrap();
+                escape(member_data.role(), out);
+            }
+        }
+        Element::Sentinel => {}
+    }
+}
+
+fn parse_tags(s: &str) -> Result<Vec<Tag>, anyhow::Error> {
+    let mut tags = Vec::new();
+    for tag in s.split(',').filter(|tag| !tag.is_empty()) {
+        let (k, v) = tag.split_once('=')
+            .ok_or(anyhow!("Invalid tag {tag}"))?;
+        tags.push(Tag::new(unescape(k)?, unescape(v)?));
+    }
+    Ok(tags)
+}
+
+fn parse_refs(s: &str) -> Result<Vec<i64>, anyhow::Error> {
+    let mut refs = Vec::new();
+    for node_ref in s.split(',').filter(|node_ref| !node_ref.is_empty()) {
+        let id = node_ref.strip_prefix('n').unwrap_or(node_ref);
+        refs.push(id.parse::<i64>().with_context(|| anyhow!("Invalid node ref {node_ref}"))?);
+    }
+    Ok(refs)
+}
+
+fn parse_members(s: &str) -> Result<Vec<Member>, anyhow::Error> {
+    let mut members = Vec::new();
+    for member in s.split(',').filter(|member| !member.is_empty()) {
+        let (id, role) = member.split_once('@')
+            .ok_or(anyhow!("Invalid member {member}"))?;
+        let mut chars = id.chars();
+        let member_type = chars.next();
+        let member_data = MemberData::new(
+            chars.as_str().parse::<i64>().with_context(|| anyhow!("Invalid member {member}"))?,
+            unescape(role)?,
+        );
+        match member_type {
+            Some('n') => {
+                members.push(Member::Node { member: member_data });
+            }
+            Some('w') => {
+                members.push(Member::Way { member: member_data });
+            }
+            Some('r') => {
+                members.push(Member::Relation { member: member_data });
+            }
+            _ => {
+                return Err(anyhow!("Invalid member type {member}"));
+            }
+        }
+    }
+    Ok(members)
+}
+
+fn parse_coordinate(s: &str) -> Result<f64, anyhow::Error> {
+    match s {
+        "" => {
+            Ok(0.0)
+        }
+        _ => {
+            Ok(s.parse::<f64>()?)
+        }
+    }
+}
+
+/// Parse a single OPL line
+///
+/// The fields following the element id may appear in any order and may be omitted. Omitted fields
+/// get the same defaults as omitted *.osm.pbf metadata: version 0, changeset -1, uid -1, empty
+/// user, timestamp -1 and visible. An empty timestamp is also read as -1.
+pub(crate) fn parse_element(line: &str) -> Result<Element, anyhow::Error> {
+    let mut fields = line.split(' ').filter(|field| !field.is_empty());
+    let id_field = fields.next().ok_or(anyhow!("Empty line"))?;
+    let mut chars = id_field.chars();
+    let kind = chars.next().unwrap_or_default();
+    let id = chars.as_str().parse::<i64>()
+        .with_context(|| anyhow!("Invalid id {id_field}"))?;
+
+    let mut version = 0_i32;
+    let mut visible = true;
+    let mut changeset = -1_i64;
+    let mut timestamp = -1_i64;
+    let mut uid = -1_i32;
+    let mut user = String::new();
+    let mut tags = Vec::new();
+    let mut lon = 0.0_f64;
+    let mut lat = 0.0_f64;
+    let mut refs = Vec::new();
+    let mut members = Vec::new();
+    for field in fields {
+        let mut chars = field.chars();
+        let field_type = chars.next().unwrap_or_default();
+        let value = chars.as_str();
+        match field_type {
+            'v' => {
+                version = value.parse().with_context(|| anyhow!("Invalid version {field}"))?;
+            }
+            'd' => {
+                visible = match value {
+                    "V" => true,
+                    "D" => false,
+                    _ => return Err(anyhow!("Invalid visibility {field}")),
+                };
+            }
+            'c' => {
+                changeset = value.parse().with_context(|| anyhow!("Invalid changeset {field}"))?;
+            }
+            't' => {
+                timestamp = parse_timestamp(value).with_context(|| anyhow!("Invalid timestamp {field}"))?;
+            }
+            'i' => {
+                uid = value.parse().with_context(|| anyhow!("Invalid uid {field}"))?;
+            }
+            'u' => {
+                user = unescape(value)?;
+            }
+            'T' => {
+                tags = parse_tags(value)?;
+            }
+            'x' => {
+                lon = parse_coordinate(value).with_context(|| anyhow!("Invalid longitude {field}"))?;
+            }
+            'y' => {
+                lat = parse_coordinate(value).with_context(|| anyhow!("Invalid latitude {field}"))?;
+            }
+            'N' => {
+                refs = parse_refs(value)?;
+            }
+            'M' => {
+                members = parse_members(value)?;
+            }
+            _ => {
+                return Err(anyhow!("Unknown field {field}"));
+            }
+        }
+    }
+
+    match kind {
+        'n' => {
+            Ok(Element::Node { node: Node::new(id, version, Coordinate::new(lat, lon), timestamp, changeset, uid, user, visible, tags) })
+        }
+        'w' => {
+            Ok(Element::Way { way: Way::new(id, version, timestamp, changeset, uid, user, visible, refs, tags) })
+        }
+        'r' => {
+            Ok(Element::Relation { relation: Relation::new(id, version, timestamp, changeset, uid, user, visible, members, tags) })
+        }
+        _ => {
+            Err(anyhow!("Unsupported element type {id_field}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        let mut escaped = String::new();
+        escape("a b,c=d@e%f\u{a0}\u{1f600}", &mut escaped);
+        assert_eq!(escaped, "a%20%b%2c%c%3d%d%40%e%25%f%a0%%1f600%");
+        assert_eq!(unescape(&escaped).unwrap(), "a b,c=d@e%f\u{a0}\u{1f600}");
+        assert_eq!(unescape("plain").unwrap(), "plain");
+        assert!(unescape("%20").is_err());
+        assert!(unescape("%zz%").is_err());
+    }
+
+    #[test]
+    fn test_format_parse() {
+        let line = "n-7 v2 dD c33 t2023-01-09T12:30:00Z i44 uJohn%20%Doe Tname=A%2c%B,amenity=cafe x-170.1595029 y-19.05";
+        let element = parse_element(line).unwrap();
+        let mut formatted = String::new();
+        format_element(&element, &mut formatted);
+        assert_eq!(formatted, line);
+
+        let element = parse_element("r5 Mn1@,w2@outer,r3@sub%20%area").unwrap();
+        match &element {
+            Element::Relation { relation } => {
+                assert_eq!(relation.members().len(), 3);
+                assert_eq!(relation.version(), 0);
+                assert_eq!(relation.changeset(), -1);
+            }
+            _ => {
+                panic!("Expected a relation");
+            }
+        }
+        // a missing timestamp is written as an empty value and read back as -1
+        let element = parse_element("w8 v1 Nn1,n2").unwrap();
+        match &element {
+            Element::Way { way } => {
+                assert_eq!(way.timestamp(), -1);
+                assert_eq!(way.uid(), -1);
+                let mut formatted = String::new();
+                format_element(&element, &mut formatted);
+                assert!(formatted.contains(" t "));
+                match parse_element(&formatted).unwrap() {
+                    Element::Way { way } => {
+                        assert_eq!(way.timestamp(), -1);
+                    }
+                    _ => {
+                        panic!("Expected a way");
+                    }
+                }
+            }
+            _ => {
+                panic!("Expected a way");
+            }
+        }
+        assert!(parse_element("x1 v1").is_err());
+        assert!(parse_element("n1 q1").is_err());
+    }
+}
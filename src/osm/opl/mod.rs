@@ -0,0 +1,5 @@
+pub mod reader;
+pub mod writer;
+pub mod element_iterator;
+
+pub(crate) mod format;
@@ -0,0 +1,72 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+
+use crate::osm::model::element::Element;
+use crate::osm::opl::element_iterator::ElementIterator;
+use crate::osm::opl::format;
+
+/// OPL file reader
+///
+/// Read the [OPL](https://osmcode.org/opl-file-format/) text format, one element per line, as
+/// written by osmium. The fields that follow the element id may be omitted, which makes OPL
+/// convenient for writing small test fixtures by hand.
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::opl;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let input_path = PathBuf::from("./tests/fixtures/small.opl");
+///     let reader = opl::reader::Reader::new(&input_path)?;
+///     for element in reader.elements()? {
+///         println!("{:?}", element);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct Reader {
+    path: PathBuf,
+}
+
+impl Reader {
+    /// Create a new [Reader]
+    pub fn new(path: &Path) -> Result<Reader, anyhow::Error> {
+        if !path.is_file() {
+            return Err(anyhow!("Not a file: {}", path.display()));
+        }
+        Ok(
+            Reader {
+                path: path.to_path_buf(),
+            }
+        )
+    }
+
+    /// Iterator used to iterate over elements
+    pub fn elements(&self) -> Result<ElementIterator, anyhow::Error> {
+        let file = File::open(&self.path)
+            .with_context(|| anyhow!("path: {}", self.path.display()))?;
+        Ok(
+            ElementIterator::new(self.path.clone(), file)
+        )
+    }
+
+    /// Parse a single OPL line
+    /// Example:
+    /// ```
+    /// use osm_io::osm::opl::reader::Reader;
+    /// fn example() -> Result<(), anyhow::Error> {
+    ///     let element = Reader::parse_line("n1 v1 Tamenity=cafe x35.1 y32.7")?;
+    ///     assert!(element.is_node());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn parse_line(line: &str) -> Result<Element, anyhow::Error> {
+        format::parse_element(line)
+    }
+
+    /// Input path
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+
+use crate::osm::model::element::Element;
+use crate::osm::opl::format;
+
+/// OPL file writer
+///
+/// Write one element per line in the [OPL](https://osmcode.org/opl-file-format/) text format, as
+/// written by osmium. The writer follows the contract of [pbf::writer::Writer](crate::osm::pbf::writer::Writer):
+/// call [Writer::write_header], write the elements and [Writer::close] in the end. Sentinels are
+/// skipped.
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::opl;
+/// use osm_io::osm::pbf;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let input_path = PathBuf::from("./tests/fixtures/malta-230109.osm.pbf");
+///     let output_path = PathBuf::from("./target/results/malta-230109.opl");
+///     let reader = pbf::reader::Reader::new(&input_path)?;
+///     let mut writer = opl::writer::Writer::new(output_path)?;
+///     writer.write_header()?;
+///     for element in reader.elements()? {
+///         writer.write_element(element)?;
+///     }
+///     writer.close()?;
+///     Ok(())
+/// }
+/// ```
+pub struct Writer {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    line: String,
+}
+
+impl Writer {
+    /// Create a new [Writer]
+    pub fn new(path: PathBuf) -> Result<Writer, anyhow::Error> {
+        let file = File::create(&path)
+            .with_context(|| anyhow!("path: {}", path.display()))?;
+        Ok(
+            Writer {
+                path,
+                writer: BufWriter::new(file),
+                line: String::with_capacity(1024),
+            }
+        )
+    }
+
+    /// OPL has no header. Provided for compatibility with the other writers
+    pub fn write_header(&mut self) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    /// Write element
+    pub fn write_element(&mut self, element: Element) -> Result<(), anyhow::Error> {
+        if element.is_sentinel() {
+            return Ok(());
+        }
+        self.line.clear();
+        format::format_element(&element, &mut self.line);
+        self.line.push('\n');
+        self.writer.write_all(self.line.as_bytes())
+            .with_context(|| anyhow!("path: {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Write elements
+    pub fn write_elements(&mut self, elements: Vec<Element>) -> Result<(), anyhow::Error> {
+        for element in elements {
+            self.write_element(element)?;
+        }
+        Ok(())
+    }
+
+    /// Flush the internal buffers.
+    ///
+    /// Must be called in the end
+    pub fn close(&mut self) -> Result<(), anyhow::Error> {
+        self.writer.flush()
+            .with_context(|| anyhow!("path: {}", self.path.display()))?;
+        Ok(())
+    }
+
+    /// Output path
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
@@ -1,4 +1,7 @@
 use std::path::PathBuf;
+use std::sync::Arc;
+
+use memmap2::Mmap;
 
 #[derive(Debug)]
 pub struct BlobDesc {
@@ -7,16 +10,20 @@ pub struct BlobDesc {
     start: u64,
     length: u64,
     t: String,
+    skip_metadata: bool,
+    mmap: Option<Arc<Mmap>>,
 }
 
 impl BlobDesc {
-    pub(crate) fn new(path: PathBuf, index: usize, start: u64, length: u64, t: String) -> BlobDesc {
+    pub(crate) fn new(path: PathBuf, index: usize, start: u64, length: u64, t: String, skip_metadata: bool, mmap: Option<Arc<Mmap>>) -> BlobDesc {
         BlobDesc {
             path,
             index,
             start,
             length,
             t,
+            skip_metadata,
+            mmap,
         }
     }
 
@@ -39,4 +46,13 @@ impl BlobDesc {
     pub fn t(&self) -> String {
         self.t.clone()
     }
-}
\ No newline at end of file
+
+    pub(crate) fn skip_metadata(&self) -> bool {
+        self.skip_metadata
+    }
+
+    /// The blob bytes when the file is memory mapped
+    pub(crate) fn mapped_data(&self) -> Option<&[u8]> {
+        self.mmap.as_ref().map(|mmap| &mmap[self.start as usize..(self.start + self.length) as usize])
+    }
+}
@@ -2,8 +2,10 @@ use std::fs::File;
 use std::io::{Cursor, Read, Seek, SeekFrom};
 use std::ops::AddAssign;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::{anyhow, Context};
+use memmap2::Mmap;
 use prost::Message;
 
 use crate::osm;
@@ -11,44 +13,97 @@ use crate::osmpbf;
 
 pub struct BlobIterator {
     path: PathBuf,
-    file: File,
+    // None when the blobs are read from the mapped file
+    file: Option<File>,
     jump: u64,
     index: usize,
+    skip_metadata: bool,
+    mmap: Option<Arc<Mmap>>,
+    position: usize,
 }
 
 impl BlobIterator {
-    pub fn new(path: PathBuf) -> Result<BlobIterator, anyhow::Error> {
-        let file = File::open(path.clone())
-            .with_context(|| anyhow!("path: {}", path.display()))?;
+    pub fn new(path: PathBuf, skip_metadata: bool, mmap: Option<Arc<Mmap>>) -> Result<BlobIterator, anyhow::Error> {
+        let file = match mmap {
+            None => {
+                Some(
+                    File::open(path.clone())
+                        .with_context(|| anyhow!("path: {}", path.display()))?
+                )
+            }
+            Some(_) => {
+                None
+            }
+        };
         Ok(
             BlobIterator {
                 path: path.clone(),
                 file,
                 jump: 0,
                 index: 0,
+                skip_metadata,
+                mmap,
+                position: 0,
             }
         )
     }
+
+    /// Read the next blob header from the file. Returns the blob header and the blob offset
+    fn next_from_file(&mut self) -> Option<(osmpbf::BlobHeader, u64)> {
+        let file = self.file.as_mut()?;
+        file.seek(SeekFrom::Current(self.jump as i64)).ok()?;
+        let mut header_len_buffer = [0_u8; 4];
+        file.read_exact(&mut header_len_buffer).ok()?;
+        let blob_header_len = i32::from_be_bytes(header_len_buffer);
+        let mut blob_header_buffer = vec![0; blob_header_len as usize];
+        file.read_exact(&mut blob_header_buffer).ok()?;
+        let blob_header = osmpbf::BlobHeader::decode(&mut Cursor::new(blob_header_buffer)).ok()?;
+        let current_offset = file.stream_position().ok()?;
+        self.jump = blob_header.datasize as u64;
+        Some((blob_header, current_offset))
+    }
+
+    /// Read the next blob header from the mapped file. Returns the blob header and the blob offset
+    fn next_from_mmap(&mut self, mmap: &Mmap) -> Option<(osmpbf::BlobHeader, u64)> {
+        let header_start = self.position.checked_add(4)?;
+        let header_len_buffer: [u8; 4] = mmap.get(self.position..header_start)?.try_into().ok()?;
+        let blob_header_len = i32::from_be_bytes(header_len_buffer);
+        let blob_start = header_start.checked_add(usize::try_from(blob_header_len).ok()?)?;
+        let blob_header = osmpbf::BlobHeader::decode(mmap.get(header_start..blob_start)?).ok()?;
+        let blob_end = blob_start.checked_add(usize::try_from(blob_header.datasize).ok()?)?;
+        if blob_end > mmap.len() {
+            return None;
+        }
+        self.position = blob_end;
+        Some((blob_header, blob_start as u64))
+    }
 }
 
 impl Iterator for BlobIterator {
     type Item = osm::pbf::blob_desc::BlobDesc;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.file.seek(SeekFrom::Current(self.jump as i64)).ok()?;
-        let mut header_len_buffer = [0_u8; 4];
-        self.file.read_exact(&mut header_len_buffer).ok()?;
-        let blob_header_len = i32::from_be_bytes(header_len_buffer);
-        let mut blob_header_buffer = vec![0; blob_header_len as usize];
-        self.file.read_exact(&mut blob_header_buffer).ok()?;
-        let blob_header = osmpbf::BlobHeader::decode(&mut Cursor::new(blob_header_buffer)).ok()?;
-        let current_offset = self.file.stream_position().ok()?;
+        let (blob_header, current_offset) = match self.mmap.clone() {
+            None => {
+                self.next_from_file()?
+            }
+            Some(mmap) => {
+                self.next_from_mmap(&mmap)?
+            }
+        };
         let length = blob_header.datasize as u64;
-        self.jump = length;
         let index = self.index;
         self.index.add_assign(1);
         Some(
-            osm::pbf::blob_desc::BlobDesc::new(self.path.clone(), index, current_offset, length, blob_header.r#type)
+            osm::pbf::blob_desc::BlobDesc::new(
+                self.path.clone(),
+                index,
+                current_offset,
+                length,
+                blob_header.r#type,
+                self.skip_metadata,
+                self.mmap.clone(),
+            )
         )
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,246 @@
+use anyhow::anyhow;
+use prost::Message;
+
+use crate::osm::pbf::element_ref::{InfoRef, NodeRef, RelationRef, TagRefIterator, WayRef};
+use crate::osmpbf;
+use crate::osmpbf::PrimitiveBlock;
+
+/// A decoded *.osm.pbf data block with borrowed element views
+///
+/// Unlike [FileBlock](crate::osm::pbf::file_block::FileBlock) the elements are not converted into
+/// the owned model. The [NodeRef], [WayRef] and [RelationRef] views borrow the ids, coordinates and
+/// strings from the decoded block, and tags, way refs and relation members are decoded lazily on
+/// iteration. Use `to_owned()` on a view to get the owned element.
+///
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::pbf;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let input_path = PathBuf::from("./tests/fixtures/malta-230109.osm.pbf");
+///     let reader = pbf::reader::Reader::new(&input_path)?;
+///     let mut restaurants = Vec::new();
+///     for block_view in reader.block_views()? {
+///         for node in block_view.nodes() {
+///             if node.tags().find("amenity") == Some("restaurant") {
+///                 restaurants.push(node.to_owned());
+///             }
+///         }
+///     }
+///     println!("restaurants: {}", restaurants.len());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BlockView {
+    index: usize,
+    block: PrimitiveBlock,
+}
+
+impl BlockView {
+    pub(crate) fn new(index: usize, data: Vec<u8>) -> Result<BlockView, anyhow::Error> {
+        let block = PrimitiveBlock::decode(data.as_slice())?;
+        // validate once so that strings can be borrowed without further checks
+        for (i, s) in block.stringtable.s.iter().enumerate() {
+            std::str::from_utf8(s).map_err(|e| anyhow!("Invalid string {i} in block {index}: {e}"))?;
+        }
+        Ok(
+            BlockView {
+                index,
+                block,
+            }
+        )
+    }
+
+    /// The index of this block in the file
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Iterate over the nodes, both dense and plain, in this block
+    pub fn nodes(&self) -> NodeRefIterator<'_> {
+        NodeRefIterator::new(self)
+    }
+
+    /// Iterate over the ways in this block
+    pub fn ways(&self) -> impl Iterator<Item=WayRef<'_>> {
+        self.block.primitivegroup.iter()
+            .flat_map(|g| g.ways.iter())
+            .map(|way| WayRef::new(self, way))
+    }
+
+    /// Iterate over the relations in this block
+    pub fn relations(&self) -> impl Iterator<Item=RelationRef<'_>> {
+        self.block.primitivegroup.iter()
+            .flat_map(|g| g.relations.iter())
+            .map(|relation| RelationRef::new(self, relation))
+    }
+
+    /// The string i of the string table. Panics if i is out of range, as the owned decoder does
+    pub(crate) fn string(&self, i: usize) -> &str {
+        let s = self.block.stringtable.s.get(i)
+            .unwrap_or_else(|| panic!("String {i} out of range in block {}", self.index));
+        // SAFETY: all strings of the table are validated as UTF-8 in new() and the block is not
+        // modified afterward
+        unsafe { std::str::from_utf8_unchecked(s) }
+    }
+
+    pub(crate) fn date_granularity(&self) -> i32 {
+        self.block.date_granularity()
+    }
+
+    pub(crate) fn coordinate(&self, lat: i64, lon: i64) -> (f64, f64) {
+        let granularity = self.block.granularity() as i64;
+        (
+            (self.block.lat_offset() + granularity * lat) as f64 / 1000000000f64,
+            (self.block.lon_offset() + granularity * lon) as f64 / 1000000000f64,
+        )
+    }
+}
+
+/// Iterate over the [NodeRef]s of a [BlockView]
+pub struct NodeRefIterator<'a> {
+    block_view: &'a BlockView,
+    group: usize,
+    dense_index: usize,
+    keys_vals_index: usize,
+    node_index: usize,
+    last_id: i64,
+    last_lat: i64,
+    last_lon: i64,
+    last_timestamp: i64,
+    last_changeset: i64,
+    last_uid: i32,
+    last_user_sid: i32,
+}
+
+impl<'a> NodeRefIterator<'a> {
+    fn new(block_view: &'a BlockView) -> NodeRefIterator<'a> {
+        NodeRefIterator {
+            block_view,
+            group: 0,
+            dense_index: 0,
+            keys_vals_index: 0,
+            node_index: 0,
+            last_id: 0,
+            last_lat: 0,
+            last_lon: 0,
+            last_timestamp: 0,
+            last_changeset: 0,
+            last_uid: 0,
+            last_user_sid: 0,
+        }
+    }
+
+    fn next_group(&mut self) {
+        *self = NodeRefIterator {
+            group: self.group + 1,
+            ..NodeRefIterator::new(self.block_view)
+        };
+    }
+
+    fn next_dense(&mut self, dense: &'a osmpbf::DenseNodes) -> NodeRef<'a> {
+        let i = self.dense_index;
+        self.dense_index += 1;
+        self.last_id += dense.id[i];
+        self.last_lat += dense.lat[i];
+        self.last_lon += dense.lon[i];
+        let (lat, lon) = self.block_view.coordinate(self.last_lat, self.last_lon);
+
+        // any of the DenseInfo arrays may be omitted by the writer
+        let mut info = InfoRef {
+            version: 0,
+            timestamp: -1,
+            changeset: -1,
+            uid: -1,
+            user: "",
+            visible: true,
+        };
+        if let Some(dense_info) = &dense.denseinfo {
+            info.visible = dense_info.visible.get(i).copied().unwrap_or(true);
+            if let Some(v) = dense_info.version.get(i) {
+                info.version = *v;
+            }
+            if let Some(delta) = dense_info.timestamp.get(i) {
+                self.last_timestamp += delta;
+                info.timestamp = self.last_timestamp * self.block_view.date_granularity() as i64;
+            }
+            if let Some(delta) = dense_info.changeset.get(i) {
+                self.last_changeset += delta;
+                info.changeset = self.last_changeset;
+            }
+            if let Some(delta) = dense_info.uid.get(i) {
+                self.last_uid += delta;
+                info.uid = self.last_uid;
+            }
+            if let Some(delta) = dense_info.user_sid.get(i) {
+                self.last_user_sid += delta;
+                info.user = self.block_view.string(self.last_user_sid as usize);
+            }
+        }
+
+        // the tags of each node are terminated by 0
+        let start = self.keys_vals_index.min(dense.keys_vals.len());
+        let end = dense.keys_vals[start..].iter()
+            .position(|key_val| *key_val == 0)
+            .map(|position| start + position)
+            .unwrap_or(dense.keys_vals.len());
+        self.keys_vals_index = end + 1;
+        let tags = TagRefIterator::from_keys_vals(self.block_view, &dense.keys_vals[start..end]);
+
+        NodeRef::new(self.last_id, lat, lon, info, tags)
+    }
+}
+
+impl<'a> Iterator for NodeRefIterator<'a> {
+    type Item = NodeRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let group = self.block_view.block.primitivegroup.get(self.group)?;
+            if let Some(dense) = &group.dense {
+                if self.dense_index < dense.id.len() {
+                    return Some(self.next_dense(dense));
+                }
+            }
+            if let Some(node) = group.nodes.get(self.node_index) {
+                self.node_index += 1;
+                return Some(NodeRef::from_node(self.block_view, node));
+            }
+            self.next_group();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "String 5 out of range in block 1")]
+    fn test_string_out_of_range() {
+        let block = PrimitiveBlock {
+            stringtable: osmpbf::StringTable {
+                s: vec![Vec::new(), b"name".to_vec()],
+            },
+            primitivegroup: vec![
+                osmpbf::PrimitiveGroup {
+                    ways: vec![
+                        osmpbf::Way {
+                            id: 1,
+                            keys: vec![1],
+                            vals: vec![5],
+                            ..Default::default()
+                        }
+                    ],
+                    ..Default::default()
+                }
+            ],
+            ..Default::default()
+        };
+        let block_view = BlockView::new(1, block.encode_to_vec()).unwrap();
+        for way in block_view.ways() {
+            way.to_owned();
+        }
+    }
+}
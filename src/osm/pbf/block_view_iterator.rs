@@ -0,0 +1,31 @@
+use crate::osm::pbf::blob_iterator::BlobIterator;
+use crate::osm::pbf::block_view::BlockView;
+use crate::osm::pbf::file_block::FileBlock;
+
+/// Iterate over the data blocks of a *.osm.pbf file as [BlockView]s
+pub struct BlockViewIterator {
+    blob_iterator: BlobIterator,
+}
+
+impl BlockViewIterator {
+    pub(crate) fn new(blob_iterator: BlobIterator) -> BlockViewIterator {
+        BlockViewIterator {
+            blob_iterator,
+        }
+    }
+}
+
+impl Iterator for BlockViewIterator {
+    type Item = BlockView;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let blob_desc = self.blob_iterator.find(|blob_desc| blob_desc.t() == "OSMData")?;
+        Some(
+            FileBlock::read_blob_desc_data(&blob_desc)
+                .and_then(|data| BlockView::new(blob_desc.index(), data))
+                .unwrap_or_else(|_| panic!("Failed to create a block view from blob {} from {:?}",
+                                           blob_desc.index(),
+                                           blob_desc.path()))
+        )
+    }
+}
@@ -1,11 +1,9 @@
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
 
-use command_executor::shutdown_mode::ShutdownMode;
-use command_executor::thread_pool_builder::ThreadPoolBuilder;
+use anyhow::anyhow;
 
 use crate::osm::model::bounding_box::BoundingBox;
-use crate::osm::pbf::calc_bounding_box_command::CalcBoundingBoxCommand;
+use crate::osm::model::element::Element;
 use crate::osm::pbf::reader::Reader;
 
 pub struct BoundingBoxCalculator {
@@ -20,33 +18,43 @@ impl BoundingBoxCalculator {
     }
 
     pub fn calc(&self) -> Result<BoundingBox, anyhow::Error> {
-        let mut tp = ThreadPoolBuilder::new()
-            .with_name_str("bounding-box-calculator")
-            .with_tasks(num_cpus::get())
-            .with_queue_size(1024)
-            .with_shutdown_mode(ShutdownMode::CompletePending)
-            .build()?;
-
-        let result = Arc::new(
-            Mutex::new(
-                None
-            )
-        );
         let reader = Reader::new(&self.path)?;
-        for blob in reader.blobs()? {
-            tp.submit(
-                Box::new(
-                    CalcBoundingBoxCommand::new(
-                        blob,
-                        result.clone(),
-                    )
-                )
-            );
-        }
-
-        tp.shutdown();
-        tp.join()?;
-        let mut result_guard = result.lock().unwrap();
-        Ok(result_guard.take().unwrap())
+        reader.par_fold(
+            num_cpus::get(),
+            || None,
+            |bounding_box: Option<BoundingBox>, element| {
+                match element {
+                    Element::Node { node } => {
+                        match bounding_box {
+                            None => {
+                                Some(BoundingBox::from_point(node.coordinate()))
+                            }
+                            Some(mut bounding_box) => {
+                                bounding_box.merge_point(node.coordinate());
+                                Some(bounding_box)
+                            }
+                        }
+                    }
+                    _ => {
+                        bounding_box
+                    }
+                }
+            },
+            |a, b| {
+                match (a, b) {
+                    (Some(mut a), Some(b)) => {
+                        a.merge_bounding_box(&b);
+                        Some(a)
+                    }
+                    (a, None) => {
+                        a
+                    }
+                    (None, b) => {
+                        b
+                    }
+                }
+            },
+        )?
+            .ok_or(anyhow!("No nodes found in {}", self.path.display()))
     }
 }
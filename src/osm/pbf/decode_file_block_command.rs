@@ -0,0 +1,42 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Error;
+use command_executor::command::Command;
+
+use crate::osm::pbf::blob_desc::BlobDesc;
+use crate::osm::pbf::file_block::FileBlock;
+use crate::osm::pbf::parallel_element_iterator::DecodedBlocks;
+
+/// Decode a single blob for [ParallelElementIterator]
+///
+/// [ParallelElementIterator]: crate::osm::pbf::parallel_element_iterator::ParallelElementIterator
+pub(crate) struct DecodeFileBlockCommand {
+    blob_desc: Mutex<Option<BlobDesc>>,
+    decoded_blocks: Arc<DecodedBlocks>,
+}
+
+impl DecodeFileBlockCommand {
+    pub(crate) fn new(blob_desc: BlobDesc, decoded_blocks: Arc<DecodedBlocks>) -> DecodeFileBlockCommand {
+        DecodeFileBlockCommand {
+            blob_desc: Mutex::new(Some(blob_desc)),
+            decoded_blocks,
+        }
+    }
+}
+
+impl Command for DecodeFileBlockCommand {
+    fn execute(&self) -> Result<(), Error> {
+        if let Some(blob_desc) = self.blob_desc.lock().unwrap().take() {
+            let result = FileBlock::from_blob_desc(&blob_desc)
+                .map(|mut file_block| {
+                    if file_block.is_osm_data() {
+                        file_block.take_elements()
+                    } else {
+                        Vec::new()
+                    }
+                });
+            self.decoded_blocks.add(blob_desc.index(), result);
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 use crate::osm::model::node::Node;
+use crate::osm::pbf::metadata_fields::MetadataFields;
 use crate::osm::pbf::string_table_builder::StringTableBuilder;
+use crate::osm::pbf::writer_options::WriterOptions;
 use crate::osmpbf::{DenseInfo, DenseNodes, PrimitiveGroup};
 
 pub(crate) struct DenseGroupBuilder {
@@ -7,6 +9,7 @@ pub(crate) struct DenseGroupBuilder {
     date_granularity: i32,
     lat_offset: i64,
     lon_offset: i64,
+    metadata_fields: MetadataFields,
     dense: Option<DenseNodes>,
     last_id: i64,
     last_lon: i64,
@@ -17,113 +20,92 @@ pub(crate) struct DenseGroupBuilder {
     last_sid: i32,
 }
 
-#[allow(clippy::needless_late_init)]
 impl DenseGroupBuilder {
     pub(crate) fn new(
-        granularity: i32,
-        date_granularity: i32,
-        lat_offset: i64,
-        lon_offset: i64,
+        options: &WriterOptions,
         node: &Node,
         string_table_builder: &mut StringTableBuilder,
     ) -> DenseGroupBuilder {
-        let mut dense = Some(DenseNodes::default());
-        let last_id;
-        let last_lon;
-        let last_lat;
-        let last_timestamp;
-        let last_uid;
-        let last_changeset;
-        let last_sid;
-
-        last_id = node.id();
-        dense.as_mut().unwrap().id.push(last_id);
-
-        last_lon = (node.coordinate().lon() * 1E9f64 / granularity as f64 - lon_offset as f64).round() as i64;
-        dense.as_mut().unwrap().lon.push(last_lon);
-        last_lat = (node.coordinate().lat() * 1E9f64 / granularity as f64 - lat_offset as f64).round() as i64;
-        dense.as_mut().unwrap().lat.push(last_lat);
-
-        dense.as_mut().unwrap().denseinfo = Some(DenseInfo::default());
-        dense.as_mut().unwrap().denseinfo.as_mut().unwrap().visible.push(node.visible());
-
-        last_timestamp = node.timestamp() / date_granularity as i64;
-        dense.as_mut().unwrap().denseinfo.as_mut().unwrap().timestamp.push(last_timestamp);
-
-        dense.as_mut().unwrap().denseinfo.as_mut().unwrap().version.push(node.version());
-
-        last_uid = node.uid();
-        dense.as_mut().unwrap().denseinfo.as_mut().unwrap().uid.push(last_uid);
-
-        last_changeset = node.changeset();
-        dense.as_mut().unwrap().denseinfo.as_mut().unwrap().changeset.push(last_changeset);
-
-        last_sid = string_table_builder.add(node.user());
-        dense.as_mut().unwrap().denseinfo.as_mut().unwrap().user_sid.push(last_sid);
-
-        for tag in node.tags() {
-            let key_index = string_table_builder.add(tag.k());
-            let value_index = string_table_builder.add(tag.v());
-            dense.as_mut().unwrap().keys_vals.push(key_index);
-            dense.as_mut().unwrap().keys_vals.push(value_index);
+        let metadata_fields = *options.metadata_fields();
+        let mut dense = DenseNodes::default();
+        if !metadata_fields.is_empty() {
+            dense.denseinfo = Some(DenseInfo::default());
         }
-        dense.as_mut().unwrap().keys_vals.push(0);
 
-        DenseGroupBuilder {
-            granularity,
-            date_granularity,
-            lat_offset,
-            lon_offset,
-            dense,
-            last_id,
-            last_lon,
-            last_lat,
-            last_timestamp,
-            last_uid,
-            last_changeset,
-            last_sid,
-        }
+        // the first values are delta encoded against zero
+        let mut dense_group_builder = DenseGroupBuilder {
+            granularity: options.granularity(),
+            date_granularity: options.date_granularity(),
+            lat_offset: options.lat_offset(),
+            lon_offset: options.lon_offset(),
+            metadata_fields,
+            dense: Some(dense),
+            last_id: 0,
+            last_lon: 0,
+            last_lat: 0,
+            last_timestamp: 0,
+            last_uid: 0,
+            last_changeset: 0,
+            last_sid: 0,
+        };
+        dense_group_builder.add(node, string_table_builder);
+        dense_group_builder
     }
 
     pub(crate) fn add(&mut self, node: &Node, string_table_builder: &mut StringTableBuilder) {
+        let dense = self.dense.as_mut().unwrap();
         let current_id = node.id();
-        self.dense.as_mut().unwrap().id.push(current_id - self.last_id);
+        dense.id.push(current_id - self.last_id);
         self.last_id = current_id;
 
-        let current_lon = (node.coordinate().lon() * 1E9f64 / self.granularity as f64 - self.lon_offset as f64).round() as i64;
-        self.dense.as_mut().unwrap().lon.push(current_lon - self.last_lon);
+        let current_lon = WriterOptions::encode(node.coordinate().lon(), self.granularity, self.lon_offset);
+        dense.lon.push(current_lon - self.last_lon);
         self.last_lon = current_lon;
-        let current_lat = (node.coordinate().lat() * 1E9f64 / self.granularity as f64 - self.lat_offset as f64).round() as i64;
-        self.dense.as_mut().unwrap().lat.push(current_lat - self.last_lat);
+        let current_lat = WriterOptions::encode(node.coordinate().lat(), self.granularity, self.lat_offset);
+        dense.lat.push(current_lat - self.last_lat);
         self.last_lat = current_lat;
 
-        self.dense.as_mut().unwrap().denseinfo.as_mut().unwrap().visible.push(node.visible());
-
-        let current_timestamp = node.timestamp() / self.date_granularity as i64;
-        self.dense.as_mut().unwrap().denseinfo.as_mut().unwrap().timestamp.push(current_timestamp - self.last_timestamp);
-        self.last_timestamp = current_timestamp;
-
-        self.dense.as_mut().unwrap().denseinfo.as_mut().unwrap().version.push(node.version());
-
-        let current_uid = node.uid();
-        self.dense.as_mut().unwrap().denseinfo.as_mut().unwrap().uid.push(current_uid - self.last_uid);
-        self.last_uid = current_uid;
-
-        let current_changeset = node.changeset();
-        self.dense.as_mut().unwrap().denseinfo.as_mut().unwrap().changeset.push(current_changeset - self.last_changeset);
-        self.last_changeset = current_changeset;
-
-        let current_sid = string_table_builder.add(node.user());
-        self.dense.as_mut().unwrap().denseinfo.as_mut().unwrap().user_sid.push(current_sid - self.last_sid);
-        self.last_sid = current_sid;
+        if let Some(info) = dense.denseinfo.as_mut() {
+            if self.metadata_fields.visible() {
+                info.visible.push(node.visible());
+            }
+
+            if self.metadata_fields.timestamp() {
+                let current_timestamp = node.timestamp() / self.date_granularity as i64;
+                info.timestamp.push(current_timestamp - self.last_timestamp);
+                self.last_timestamp = current_timestamp;
+            }
+
+            if self.metadata_fields.version() {
+                info.version.push(node.version());
+            }
+
+            if self.metadata_fields.uid() {
+                let current_uid = node.uid();
+                info.uid.push(current_uid - self.last_uid);
+                self.last_uid = current_uid;
+            }
+
+            if self.metadata_fields.changeset() {
+                let current_changeset = node.changeset();
+                info.changeset.push(current_changeset - self.last_changeset);
+                self.last_changeset = current_changeset;
+            }
+
+            if self.metadata_fields.user() {
+                let current_sid = string_table_builder.add(node.user());
+                info.user_sid.push(current_sid - self.last_sid);
+                self.last_sid = current_sid;
+            }
+        }
 
         for tag in node.tags() {
             let key_index = string_table_builder.add(tag.k());
             let value_index = string_table_builder.add(tag.v());
-            self.dense.as_mut().unwrap().keys_vals.push(key_index);
-            self.dense.as_mut().unwrap().keys_vals.push(value_index);
+            dense.keys_vals.push(key_index);
+            dense.keys_vals.push(value_index);
         }
-        self.dense.as_mut().unwrap().keys_vals.push(0);
+        dense.keys_vals.push(0);
     }
 
     #[allow(clippy::field_reassign_with_default)]
@@ -132,4 +114,4 @@ impl DenseGroupBuilder {
         primitive_group.dense = self.dense.replace(DenseNodes::default());
         primitive_group
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,5 @@
 use crate::osm::model::element::Element;
+use crate::osm::pbf::osm_data::OsmData;
 
 enum State {
     Nodes,
@@ -8,19 +9,21 @@ enum State {
 
 pub(crate) struct ElementAccumulator {
     block_size: usize,
+    max_block_size: usize,
+    estimated_size: usize,
     elements: Vec<Element>,
     state: State,
-    index: usize,
 }
 
 impl ElementAccumulator {
-    pub(crate) fn new() -> ElementAccumulator {
+    pub(crate) fn new(max_block_size: usize) -> ElementAccumulator {
         let block_size = 8000usize;
         ElementAccumulator {
             block_size,
+            max_block_size,
+            estimated_size: 0,
             elements: Vec::with_capacity(block_size),
             state: State::Nodes,
-            index: 0,
         }
     }
 
@@ -30,16 +33,11 @@ impl ElementAccumulator {
             State::Nodes => {
                 match &element {
                     Element::Node { .. } => {
-                        self.elements.push(element);
-                        if self.elements.len() == self.block_size {
-                            result = Some(std::mem::replace(&mut self.elements, Vec::with_capacity(self.block_size)));
-                            self.index += 1;
-                        }
+                        result = self.push(element);
                     }
                     Element::Way { .. } => {
                         self.state = State::Ways;
-                        result = Some(std::mem::replace(&mut self.elements, Vec::with_capacity(self.block_size)));
-                        self.index += 1;
+                        result = Some(self.take_block());
                         self.add(element);
                     }
                     Element::Relation { .. } => {
@@ -54,16 +52,11 @@ impl ElementAccumulator {
                         panic!("expected Element::Way or Element::Relation but got Element::Node");
                     }
                     Element::Way { .. } => {
-                        self.elements.push(element);
-                        if self.elements.len() == self.block_size {
-                            result = Some(std::mem::replace(&mut self.elements, Vec::with_capacity(self.block_size)));
-                            self.index += 1;
-                        }
+                        result = self.push(element);
                     }
                     Element::Relation { .. } => {
                         self.state = State::Relations;
-                        result = Some(std::mem::replace(&mut self.elements, Vec::with_capacity(self.block_size)));
-                        self.index += 1;
+                        result = Some(self.take_block());
                         self.add(element);
                     }
                     Element::Sentinel => {}
@@ -78,11 +71,7 @@ impl ElementAccumulator {
                         panic!("expected Element::Relation but got Element::Way");
                     }
                     Element::Relation { .. } => {
-                        self.elements.push(element);
-                        if self.elements.len() == self.block_size {
-                            result = Some(std::mem::replace(&mut self.elements, Vec::with_capacity(self.block_size)));
-                            self.index += 1;
-                        }
+                        result = self.push(element);
                     }
                     Element::Sentinel => {}
                 }
@@ -91,13 +80,30 @@ impl ElementAccumulator {
         result
     }
 
-    pub(crate) fn elements(&mut self) -> Vec<Element> {
-        self.index += 1;
-        std::mem::take(&mut self.elements)
+    /// Add an element of the current variant, starting a new block when either the number of
+    /// elements or the estimated encoded size reach the limit
+    fn push(&mut self, element: Element) -> Option<Vec<Element>> {
+        let mut result = None;
+        let element_size = OsmData::estimate_encoded_size(&element);
+        if !self.elements.is_empty() && self.estimated_size + element_size > self.max_block_size {
+            result = Some(self.take_block());
+        }
+        self.estimated_size += element_size;
+        self.elements.push(element);
+        if result.is_none() && self.elements.len() == self.block_size {
+            result = Some(self.take_block());
+        }
+        result
+    }
+
+    fn take_block(&mut self) -> Vec<Element> {
+        self.estimated_size = 0;
+        std::mem::replace(&mut self.elements, Vec::with_capacity(self.block_size))
     }
 
-    pub(crate) fn index(&self) -> usize {
-        self.index
+    pub(crate) fn elements(&mut self) -> Vec<Element> {
+        self.estimated_size = 0;
+        std::mem::take(&mut self.elements)
     }
 
     #[allow(dead_code)]
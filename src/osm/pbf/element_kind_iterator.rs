@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use std::vec::IntoIter;
+
+use crate::osm::model::element::Element;
+use crate::osm::model::element_kind::ElementKind;
+use crate::osm::pbf::blob_desc::BlobDesc;
+use crate::osm::pbf::blob_iterator::BlobIterator;
+use crate::osm::pbf::file_block::FileBlock;
+
+/// Iterate over elements of selected kinds in *.osm.pbf file
+///
+/// Primitive groups with elements of other kinds are skipped without decoding the elements. For
+/// files sorted by type then id the blocks that precede or follow the requested kinds are located
+/// with a binary search and are not read at all.
+pub struct ElementKindIterator {
+    blob_descs: VecDeque<BlobDesc>,
+    kinds: Vec<ElementKind>,
+    element_iterator: Option<IntoIter<Element>>,
+}
+
+impl ElementKindIterator {
+    pub(crate) fn new(blob_iterator: BlobIterator, kinds: &[ElementKind], sorted: bool) -> Result<ElementKindIterator, anyhow::Error> {
+        let mut blob_descs = blob_iterator
+            .filter(|blob_desc| blob_desc.t() == "OSMData")
+            .collect::<VecDeque<BlobDesc>>();
+        if sorted {
+            match (kinds.iter().min(), kinds.iter().max()) {
+                (Some(first_kind), Some(last_kind)) => {
+                    // blocks are ordered by kind, so a block that may contain the requested kinds
+                    // starts at or before the last kind and ends at or after the first kind.
+                    // Blocks without elements are kept.
+                    let end = Self::partition_point(
+                        &blob_descs,
+                        |kind_range| kind_range.is_none_or(|(first, _)| first <= *last_kind),
+                    )?;
+                    blob_descs.truncate(end);
+                    let start = Self::partition_point(
+                        &blob_descs,
+                        |kind_range| kind_range.is_some_and(|(_, last)| last < *first_kind),
+                    )?;
+                    blob_descs.drain(..start);
+                }
+                _ => {
+                    blob_descs.clear();
+                }
+            }
+        }
+
+        Ok(
+            ElementKindIterator {
+                blob_descs,
+                kinds: kinds.to_vec(),
+                element_iterator: None,
+            }
+        )
+    }
+
+    /// Index of the first block for which the predicate on the (first, last) kind range is false
+    fn partition_point(blob_descs: &VecDeque<BlobDesc>, predicate: impl Fn(Option<(ElementKind, ElementKind)>) -> bool) -> Result<usize, anyhow::Error> {
+        let mut low = 0;
+        let mut high = blob_descs.len();
+        while low < high {
+            let middle = low + (high - low) / 2;
+            match predicate(FileBlock::kind_range(&blob_descs[middle])?) {
+                true => {
+                    low = middle + 1;
+                }
+                false => {
+                    high = middle;
+                }
+            }
+        }
+        Ok(low)
+    }
+
+    fn create_element_iterator(&mut self) -> Option<IntoIter<Element>> {
+        let blob_desc = self.blob_descs.pop_front()?;
+        let mut file_block = FileBlock::from_blob_desc_of_kinds(&blob_desc, &self.kinds)
+            .unwrap_or_else(|_| panic!("Failed to create a file block from blob {} from {:?}",
+                                       blob_desc.index(),
+                                       blob_desc.path()));
+        Some(file_block.take_elements().into_iter())
+    }
+}
+
+impl Iterator for ElementKindIterator {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(element) = self.element_iterator.as_mut().and_then(|element_iterator| element_iterator.next()) {
+                return Some(element);
+            }
+            self.element_iterator = Some(self.create_element_iterator()?);
+        }
+    }
+}
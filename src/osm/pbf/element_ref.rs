@@ -0,0 +1,419 @@
+use crate::osm::model::coordinate::Coordinate;
+use crate::osm::model::element_kind::ElementKind;
+use crate::osm::model::node::Node;
+use crate::osm::model::relation::{Member, MemberData, Relation};
+use crate::osm::model::tag::Tag;
+use crate::osm::model::way::Way;
+use crate::osm::pbf::block_view::BlockView;
+use crate::osmpbf;
+
+/// Element metadata borrowed from a [BlockView]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct InfoRef<'a> {
+    pub(crate) version: i32,
+    pub(crate) timestamp: i64,
+    pub(crate) changeset: i64,
+    pub(crate) uid: i32,
+    pub(crate) user: &'a str,
+    pub(crate) visible: bool,
+}
+
+impl<'a> InfoRef<'a> {
+    /// Decode the metadata of a node, way or relation with the same defaults as the owned model
+    fn from_info(block_view: &'a BlockView, info: &Option<osmpbf::Info>) -> InfoRef<'a> {
+        match info {
+            None => {
+                InfoRef {
+                    version: 0,
+                    timestamp: -1,
+                    changeset: -1,
+                    uid: -1,
+                    user: "",
+                    visible: true,
+                }
+            }
+            Some(info) => {
+                InfoRef {
+                    version: info.version(),
+                    timestamp: info.timestamp.map(|timestamp| timestamp * block_view.date_granularity() as i64).unwrap_or(-1),
+                    changeset: info.changeset.unwrap_or(-1),
+                    uid: info.uid.unwrap_or(-1),
+                    user: info.user_sid.map(|sid| block_view.string(sid as usize)).unwrap_or_default(),
+                    visible: info.visible.unwrap_or(true),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TagSource<'a> {
+    // interleaved key and value string ids of a dense node
+    KeysVals(&'a [i32]),
+    Separate(&'a [u32], &'a [u32]),
+}
+
+/// Lazy iterator over the (key, value) tag pairs of an element view
+#[derive(Debug, Clone)]
+pub struct TagRefIterator<'a> {
+    block_view: &'a BlockView,
+    source: TagSource<'a>,
+    position: usize,
+}
+
+impl<'a> TagRefIterator<'a> {
+    pub(crate) fn from_keys_vals(block_view: &'a BlockView, keys_vals: &'a [i32]) -> TagRefIterator<'a> {
+        TagRefIterator {
+            block_view,
+            source: TagSource::KeysVals(keys_vals),
+            position: 0,
+        }
+    }
+
+    fn from_keys_and_vals(block_view: &'a BlockView, keys: &'a [u32], vals: &'a [u32]) -> TagRefIterator<'a> {
+        TagRefIterator {
+            block_view,
+            source: TagSource::Separate(keys, vals),
+            position: 0,
+        }
+    }
+
+    /// Find the value of a tag without allocating
+    pub fn find(mut self, key: &str) -> Option<&'a str> {
+        Iterator::find(&mut self, |(k, _)| *k == key).map(|(_, v)| v)
+    }
+}
+
+impl<'a> Iterator for TagRefIterator<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (k, v) = match self.source {
+            TagSource::KeysVals(keys_vals) => {
+                let k = *keys_vals.get(self.position)? as usize;
+                let v = *keys_vals.get(self.position + 1)? as usize;
+                self.position += 2;
+                (k, v)
+            }
+            TagSource::Separate(keys, vals) => {
+                let k = *keys.get(self.position)? as usize;
+                let v = *vals.get(self.position)? as usize;
+                self.position += 1;
+                (k, v)
+            }
+        };
+        Some((self.block_view.string(k), self.block_view.string(v)))
+    }
+}
+
+fn owned_tags(tags: TagRefIterator) -> Vec<Tag> {
+    tags
+        .map(|(k, v)| Tag::new(k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// A node borrowed from a [BlockView]
+#[derive(Debug, Clone)]
+pub struct NodeRef<'a> {
+    id: i64,
+    lat: f64,
+    lon: f64,
+    info: InfoRef<'a>,
+    tags: TagRefIterator<'a>,
+}
+
+impl<'a> NodeRef<'a> {
+    pub(crate) fn new(id: i64, lat: f64, lon: f64, info: InfoRef<'a>, tags: TagRefIterator<'a>) -> NodeRef<'a> {
+        NodeRef {
+            id,
+            lat,
+            lon,
+            info,
+            tags,
+        }
+    }
+
+    pub(crate) fn from_node(block_view: &'a BlockView, node: &'a osmpbf::Node) -> NodeRef<'a> {
+        let (lat, lon) = block_view.coordinate(node.lat, node.lon);
+        NodeRef::new(
+            node.id,
+            lat,
+            lon,
+            InfoRef::from_info(block_view, &node.info),
+            TagRefIterator::from_keys_and_vals(block_view, &node.keys, &node.vals),
+        )
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    pub fn version(&self) -> i32 {
+        self.info.version
+    }
+
+    pub fn timestamp(&self) -> i64 {
+        self.info.timestamp
+    }
+
+    pub fn changeset(&self) -> i64 {
+        self.info.changeset
+    }
+
+    pub fn uid(&self) -> i32 {
+        self.info.uid
+    }
+
+    pub fn user(&self) -> &'a str {
+        self.info.user
+    }
+
+    pub fn visible(&self) -> bool {
+        self.info.visible
+    }
+
+    /// Lazy iterator over the (key, value) tag pairs
+    pub fn tags(&self) -> TagRefIterator<'a> {
+        self.tags.clone()
+    }
+
+    /// Copy into an owned [Node]
+    pub fn to_owned(&self) -> Node {
+        Node::new(
+            self.id,
+            self.info.version,
+            Coordinate::new(self.lat, self.lon),
+            self.info.timestamp,
+            self.info.changeset,
+            self.info.uid,
+            self.info.user.to_string(),
+            self.info.visible,
+            owned_tags(self.tags()),
+        )
+    }
+}
+
+/// A way borrowed from a [BlockView]
+#[derive(Debug, Clone, Copy)]
+pub struct WayRef<'a> {
+    block_view: &'a BlockView,
+    way: &'a osmpbf::Way,
+}
+
+impl<'a> WayRef<'a> {
+    pub(crate) fn new(block_view: &'a BlockView, way: &'a osmpbf::Way) -> WayRef<'a> {
+        WayRef {
+            block_view,
+            way,
+        }
+    }
+
+    fn info(&self) -> InfoRef<'a> {
+        InfoRef::from_info(self.block_view, &self.way.info)
+    }
+
+    pub fn id(&self) -> i64 {
+        self.way.id
+    }
+
+    pub fn version(&self) -> i32 {
+        self.info().version
+    }
+
+    pub fn timestamp(&self) -> i64 {
+        self.info().timestamp
+    }
+
+    pub fn changeset(&self) -> i64 {
+        self.info().changeset
+    }
+
+    pub fn uid(&self) -> i32 {
+        self.info().uid
+    }
+
+    pub fn user(&self) -> &'a str {
+        self.info().user
+    }
+
+    pub fn visible(&self) -> bool {
+        self.info().visible
+    }
+
+    /// Lazy iterator over the node ids of this way
+    pub fn refs(&self) -> impl Iterator<Item=i64> + 'a {
+        self.way.refs.iter().scan(0_i64, |last_ref, delta| {
+            *last_ref += delta;
+            Some(*last_ref)
+        })
+    }
+
+    /// Lazy iterator over the (key, value) tag pairs
+    pub fn tags(&self) -> TagRefIterator<'a> {
+        TagRefIterator::from_keys_and_vals(self.block_view, &self.way.keys, &self.way.vals)
+    }
+
+    /// Copy into an owned [Way]
+    pub fn to_owned(&self) -> Way {
+        let info = self.info();
+        Way::new(
+            self.id(),
+            info.version,
+            info.timestamp,
+            info.changeset,
+            info.uid,
+            info.user.to_string(),
+            info.visible,
+            self.refs().collect(),
+            owned_tags(self.tags()),
+        )
+    }
+}
+
+/// A relation member borrowed from a [BlockView]
+#[derive(Debug, Clone, Copy)]
+pub struct MemberRef<'a> {
+    id: i64,
+    kind: ElementKind,
+    role: &'a str,
+}
+
+impl<'a> MemberRef<'a> {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn kind(&self) -> ElementKind {
+        self.kind
+    }
+
+    pub fn role(&self) -> &'a str {
+        self.role
+    }
+
+    /// Copy into an owned [Member]
+    pub fn to_owned(&self) -> Member {
+        let member = MemberData::new(self.id, self.role.to_string());
+        match self.kind {
+            ElementKind::Node => {
+                Member::Node { member }
+            }
+            ElementKind::Way => {
+                Member::Way { member }
+            }
+            ElementKind::Relation => {
+                Member::Relation { member }
+            }
+        }
+    }
+}
+
+/// A relation borrowed from a [BlockView]
+#[derive(Debug, Clone, Copy)]
+pub struct RelationRef<'a> {
+    block_view: &'a BlockView,
+    relation: &'a osmpbf::Relation,
+}
+
+impl<'a> RelationRef<'a> {
+    pub(crate) fn new(block_view: &'a BlockView, relation: &'a osmpbf::Relation) -> RelationRef<'a> {
+        RelationRef {
+            block_view,
+            relation,
+        }
+    }
+
+    fn info(&self) -> InfoRef<'a> {
+        InfoRef::from_info(self.block_view, &self.relation.info)
+    }
+
+    pub fn id(&self) -> i64 {
+        self.relation.id
+    }
+
+    pub fn version(&self) -> i32 {
+        self.info().version
+    }
+
+    pub fn timestamp(&self) -> i64 {
+        self.info().timestamp
+    }
+
+    pub fn changeset(&self) -> i64 {
+        self.info().changeset
+    }
+
+    pub fn uid(&self) -> i32 {
+        self.info().uid
+    }
+
+    pub fn user(&self) -> &'a str {
+        self.info().user
+    }
+
+    pub fn visible(&self) -> bool {
+        self.info().visible
+    }
+
+    /// Lazy iterator over the members of this relation
+    pub fn members(&self) -> impl Iterator<Item=MemberRef<'a>> + 'a {
+        let block_view = self.block_view;
+        let relation = self.relation;
+        relation.memids.iter()
+            .zip(relation.types.iter())
+            .zip(relation.roles_sid.iter())
+            .scan(0_i64, move |last_memid, ((delta, member_type), role_sid)| {
+                *last_memid += delta;
+                let kind = match osmpbf::relation::MemberType::try_from(*member_type) {
+                    Ok(osmpbf::relation::MemberType::Node) => {
+                        ElementKind::Node
+                    }
+                    Ok(osmpbf::relation::MemberType::Way) => {
+                        ElementKind::Way
+                    }
+                    Ok(osmpbf::relation::MemberType::Relation) => {
+                        ElementKind::Relation
+                    }
+                    Err(_) => {
+                        panic!("Non existing relation member type: {}", member_type);
+                    }
+                };
+                Some(
+                    MemberRef {
+                        id: *last_memid,
+                        kind,
+                        role: block_view.string(*role_sid as usize),
+                    }
+                )
+            })
+    }
+
+    /// Lazy iterator over the (key, value) tag pairs
+    pub fn tags(&self) -> TagRefIterator<'a> {
+        TagRefIterator::from_keys_and_vals(self.block_view, &self.relation.keys, &self.relation.vals)
+    }
+
+    /// Copy into an owned [Relation]
+    pub fn to_owned(&self) -> Relation {
+        let info = self.info();
+        Relation::new(
+            self.id(),
+            info.version,
+            info.timestamp,
+            info.changeset,
+            info.uid,
+            info.user.to_string(),
+            info.visible,
+            self.members().map(|member| member.to_owned()).collect(),
+            owned_tags(self.tags()),
+        )
+    }
+}
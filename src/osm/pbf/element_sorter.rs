@@ -0,0 +1,221 @@
+use std::fs;
+use std::mem::size_of;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use uuid::Uuid;
+
+use crate::osm::model::element::Element;
+use crate::osm::model::relation::Member;
+use crate::osm::model::tag::Tag;
+use crate::osm::pbf::compression_type::CompressionType;
+use crate::osm::pbf::file_info::FileInfo;
+use crate::osm::pbf::merge_iterator::MergeIterator;
+use crate::osm::pbf::reader::Reader;
+use crate::osm::pbf::writer::Writer;
+
+/// External memory sort of an unordered element stream
+///
+/// Elements are accumulated in memory until the memory budget is exhausted, then the accumulated
+/// elements are sorted and spilled to a temporary *.osm.pbf file, a run. When all elements are
+/// added the runs are merged into a single ordered sequence that can be written with [Writer] or
+/// [crate::osm::pbf::parallel_writer::ParallelWriter] as a Sort.Type_then_ID file.
+///
+/// The memory budget is an estimate of the memory used by the accumulated elements and does
+/// not account for the allocator overhead.
+///
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::pbf::compression_type::CompressionType;
+/// use osm_io::osm::pbf::element_sorter::ElementSorter;
+/// use osm_io::osm::pbf::file_info::FileInfo;
+/// use osm_io::osm::pbf::writer::Writer;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let output_path = PathBuf::from("./target/results/sorted.osm.pbf");
+///     let mut sorter = ElementSorter::new(PathBuf::from("./target/tmp"), 1024 * 1024 * 1024)?;
+///     // for element in unordered_elements {
+///     //     sorter.add(element)?;
+///     // }
+///     let mut writer = Writer::from_file_info(
+///         output_path,
+///         FileInfo::default(),
+///         CompressionType::Zlib,
+///     )?;
+///     writer.write_header()?;
+///     for element in sorter.sort()? {
+///         writer.write_element(element)?;
+///     }
+///     writer.close()?;
+///     Ok(())
+/// }
+/// ```
+pub struct ElementSorter {
+    runs_path: PathBuf,
+    memory_budget: usize,
+    memory_used: usize,
+    elements: Vec<Element>,
+    runs: Vec<PathBuf>,
+}
+
+impl ElementSorter {
+    /// Create a new [ElementSorter]
+    ///
+    /// * tmp_path - a directory for the sorted runs. A unique subdirectory is created for each
+    ///   sorter and removed when the sorted elements are consumed.
+    /// * memory_budget - an estimate in bytes of the memory used for elements before spilling a
+    ///   run to disk
+    pub fn new(tmp_path: PathBuf, memory_budget: usize) -> Result<ElementSorter, anyhow::Error> {
+        let runs_path = tmp_path.join(format!("element-sorter-{}", Uuid::new_v4()));
+        fs::create_dir_all(&runs_path)
+            .with_context(|| anyhow!("path: {}", runs_path.display()))?;
+        Ok(
+            ElementSorter {
+                runs_path,
+                memory_budget,
+                memory_used: 0,
+                elements: Vec::new(),
+                runs: Vec::new(),
+            }
+        )
+    }
+
+    /// Add an element in any order. [Element::Sentinel] values are ignored.
+    pub fn add(&mut self, element: Element) -> Result<(), anyhow::Error> {
+        if element.is_sentinel() {
+            return Ok(());
+        }
+        self.memory_used += Self::estimate_size(&element);
+        self.elements.push(element);
+        if self.memory_used >= self.memory_budget {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    /// Add a vector of elements in any order
+    pub fn add_elements(&mut self, elements: Vec<Element>) -> Result<(), anyhow::Error> {
+        for element in elements {
+            self.add(element)?;
+        }
+        Ok(())
+    }
+
+    /// Number of runs spilled to disk so far
+    pub fn runs(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Sort the added elements
+    ///
+    /// Return an iterator over all added elements in [Element] order. Equal elements are returned
+    /// in the order they were added.
+    pub fn sort(mut self) -> Result<SortedElementIterator, anyhow::Error> {
+        let mut elements = std::mem::take(&mut self.elements);
+        elements.sort();
+
+        let mut iterators: Vec<Box<dyn Iterator<Item=Element>>> = Vec::new();
+        for run in &self.runs {
+            let reader = Reader::new(run)?;
+            iterators.push(Box::new(reader.elements()?));
+        }
+        iterators.push(Box::new(elements.into_iter()));
+
+        Ok(
+            SortedElementIterator {
+                merge_iterator: MergeIterator::new(iterators),
+                runs_path: std::mem::take(&mut self.runs_path),
+            }
+        )
+    }
+
+    fn spill(&mut self) -> Result<(), anyhow::Error> {
+        let mut elements = std::mem::take(&mut self.elements);
+        self.memory_used = 0;
+        elements.sort();
+
+        let run_path = self.runs_path.join(format!("run-{}.osm.pbf", self.runs.len()));
+        log::debug!("Spill {} elements to {}", elements.len(), run_path.display());
+        let mut writer = Writer::from_file_info(
+            run_path.clone(),
+            FileInfo::default(),
+            CompressionType::Uncompressed,
+        )?;
+        writer.write_header()?;
+        for element in elements {
+            writer.write_element(element)?;
+        }
+        writer.close()?;
+        self.runs.push(run_path);
+        Ok(())
+    }
+
+    fn estimate_size(element: &Element) -> usize {
+        let tags_size = |tags: &Vec<Tag>| -> usize {
+            tags.iter()
+                .map(|tag| size_of::<Tag>() + tag.k().len() + tag.v().len())
+                .sum()
+        };
+
+        size_of::<Element>() + match element {
+            Element::Node { node } => {
+                node.user().len() + tags_size(node.tags())
+            }
+            Element::Way { way } => {
+                way.user().len() + tags_size(way.tags()) + way.refs().len() * size_of::<i64>()
+            }
+            Element::Relation { relation } => {
+                let members_size: usize = relation.members().iter()
+                    .map(|member| {
+                        match member {
+                            Member::Node { member } => {
+                                member.role().len()
+                            }
+                            Member::Way { member } => {
+                                member.role().len()
+                            }
+                            Member::Relation { member } => {
+                                member.role().len()
+                            }
+                        }
+                    })
+                    .map(|role_len| size_of::<Member>() + role_len)
+                    .sum();
+                relation.user().len() + tags_size(relation.tags()) + members_size
+            }
+            Element::Sentinel => {
+                0
+            }
+        }
+    }
+}
+
+impl Drop for ElementSorter {
+    fn drop(&mut self) {
+        if !self.runs_path.as_os_str().is_empty() {
+            fs::remove_dir_all(&self.runs_path).ok();
+        }
+    }
+}
+
+/// Iterate over the elements sorted by [ElementSorter]
+///
+/// The temporary runs are removed when the iterator is dropped.
+pub struct SortedElementIterator {
+    merge_iterator: MergeIterator,
+    runs_path: PathBuf,
+}
+
+impl Iterator for SortedElementIterator {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.merge_iterator.next()
+    }
+}
+
+impl Drop for SortedElementIterator {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.runs_path).ok();
+    }
+}
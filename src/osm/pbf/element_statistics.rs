@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+
+use json::{JsonValue, object};
+
+use crate::osm::converters::timestamp_to_iso8601_seconds;
+use crate::osm::model::bounding_box::BoundingBox;
+use crate::osm::model::element::Element;
+use crate::osm::model::tag::Tag;
+
+/// Count and id range of a single element variant
+#[derive(Debug, Clone, Default)]
+pub struct IdStatistics {
+    count: i64,
+    min_id: Option<i64>,
+    max_id: Option<i64>,
+}
+
+impl IdStatistics {
+    fn add(&mut self, id: i64) {
+        self.count += 1;
+        self.min_id = Some(self.min_id.map_or(id, |min_id| min_id.min(id)));
+        self.max_id = Some(self.max_id.map_or(id, |max_id| max_id.max(id)));
+    }
+
+    fn merge(&mut self, other: &IdStatistics) {
+        self.count += other.count;
+        self.min_id = match (self.min_id, other.min_id) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max_id = match (self.max_id, other.max_id) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    /// Number of elements
+    pub fn count(&self) -> i64 {
+        self.count
+    }
+
+    /// The smallest id, None if there are no elements
+    pub fn min_id(&self) -> Option<i64> {
+        self.min_id
+    }
+
+    /// The largest id, None if there are no elements
+    pub fn max_id(&self) -> Option<i64> {
+        self.max_id
+    }
+}
+
+/// Statistics of an element sequence
+///
+/// Similar to the data section of `osmium fileinfo --extended` with the addition of changeset and
+/// user counts, the longest way and relation, and the most frequent tags.
+/// See [crate::osm::pbf::reader::Reader::statistics]
+#[derive(Debug, Clone)]
+pub struct ElementStatistics {
+    top_n: usize,
+    nodes: IdStatistics,
+    ways: IdStatistics,
+    relations: IdStatistics,
+    first_timestamp: Option<i64>,
+    last_timestamp: Option<i64>,
+    bounding_box: Option<BoundingBox>,
+    changesets: HashSet<i64>,
+    users: HashSet<i32>,
+    max_way_nodes: usize,
+    max_relation_members: usize,
+    keys: HashMap<String, u64>,
+    tags: HashMap<(String, String), u64>,
+}
+
+impl ElementStatistics {
+    /// Create empty [ElementStatistics]
+    ///
+    /// * top_n - the number of most frequent tag keys and key=value pairs to report
+    pub fn new(top_n: usize) -> ElementStatistics {
+        ElementStatistics {
+            top_n,
+            nodes: Default::default(),
+            ways: Default::default(),
+            relations: Default::default(),
+            first_timestamp: None,
+            last_timestamp: None,
+            bounding_box: None,
+            changesets: HashSet::new(),
+            users: HashSet::new(),
+            max_way_nodes: 0,
+            max_relation_members: 0,
+            keys: HashMap::new(),
+            tags: HashMap::new(),
+        }
+    }
+
+    /// Add an element to the statistics
+    pub fn add(&mut self, element: &Element) {
+        match element {
+            Element::Node { node } => {
+                self.nodes.add(node.id());
+                self.add_metadata(node.timestamp(), node.changeset(), node.uid());
+                self.add_tags(node.tags());
+                if node.visible() {
+                    match &mut self.bounding_box {
+                        None => {
+                            self.bounding_box = Some(BoundingBox::from_point(node.coordinate()));
+                        }
+                        Some(bounding_box) => {
+                            bounding_box.merge_point(node.coordinate());
+                        }
+                    }
+                }
+            }
+            Element::Way { way } => {
+                self.ways.add(way.id());
+                self.add_metadata(way.timestamp(), way.changeset(), way.uid());
+                self.add_tags(way.tags());
+                self.max_way_nodes = self.max_way_nodes.max(way.refs().len());
+            }
+            Element::Relation { relation } => {
+                self.relations.add(relation.id());
+                self.add_metadata(relation.timestamp(), relation.changeset(), relation.uid());
+                self.add_tags(relation.tags());
+                self.max_relation_members = self.max_relation_members.max(relation.members().len());
+            }
+            Element::Sentinel => {}
+        }
+    }
+
+    /// Merge statistics computed for another part of the sequence
+    pub fn merge(&mut self, other: ElementStatistics) {
+        self.nodes.merge(&other.nodes);
+        self.ways.merge(&other.ways);
+        self.relations.merge(&other.relations);
+        self.first_timestamp = match (self.first_timestamp, other.first_timestamp) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.last_timestamp = match (self.last_timestamp, other.last_timestamp) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+        if let Some(other_bounding_box) = other.bounding_box {
+            match &mut self.bounding_box {
+                None => {
+                    self.bounding_box = Some(other_bounding_box);
+                }
+                Some(bounding_box) => {
+                    bounding_box.merge_bounding_box(&other_bounding_box);
+                }
+            }
+        }
+        self.changesets.extend(other.changesets);
+        self.users.extend(other.users);
+        self.max_way_nodes = self.max_way_nodes.max(other.max_way_nodes);
+        self.max_relation_members = self.max_relation_members.max(other.max_relation_members);
+        for (key, count) in other.keys {
+            *self.keys.entry(key).or_insert(0) += count;
+        }
+        for (tag, count) in other.tags {
+            *self.tags.entry(tag).or_insert(0) += count;
+        }
+    }
+
+    fn add_metadata(&mut self, timestamp: i64, changeset: i64, uid: i32) {
+        self.first_timestamp = Some(self.first_timestamp.map_or(timestamp, |t| t.min(timestamp)));
+        self.last_timestamp = Some(self.last_timestamp.map_or(timestamp, |t| t.max(timestamp)));
+        self.changesets.insert(changeset);
+        self.users.insert(uid);
+    }
+
+    fn add_tags(&mut self, tags: &[Tag]) {
+        for tag in tags {
+            match self.keys.get_mut(tag.k()) {
+                None => {
+                    self.keys.insert(tag.k().clone(), 1);
+                }
+                Some(count) => {
+                    *count += 1;
+                }
+            }
+            *self.tags.entry((tag.k().clone(), tag.v().clone())).or_insert(0) += 1;
+        }
+    }
+
+    /// Node count and id range
+    pub fn nodes(&self) -> &IdStatistics {
+        &self.nodes
+    }
+
+    /// Way count and id range
+    pub fn ways(&self) -> &IdStatistics {
+        &self.ways
+    }
+
+    /// Relation count and id range
+    pub fn relations(&self) -> &IdStatistics {
+        &self.relations
+    }
+
+    /// The earliest element timestamp in milliseconds
+    pub fn first_timestamp(&self) -> Option<i64> {
+        self.first_timestamp
+    }
+
+    /// The latest element timestamp in milliseconds
+    pub fn last_timestamp(&self) -> Option<i64> {
+        self.last_timestamp
+    }
+
+    /// The bounding box of all visible nodes
+    pub fn bounding_box(&self) -> &Option<BoundingBox> {
+        &self.bounding_box
+    }
+
+    /// Number of distinct changesets
+    pub fn changesets(&self) -> usize {
+        self.changesets.len()
+    }
+
+    /// Number of distinct users
+    pub fn users(&self) -> usize {
+        self.users.len()
+    }
+
+    /// The number of nodes in the longest way
+    pub fn max_way_nodes(&self) -> usize {
+        self.max_way_nodes
+    }
+
+    /// The number of members in the largest relation
+    pub fn max_relation_members(&self) -> usize {
+        self.max_relation_members
+    }
+
+    /// The top_n most frequent tag keys with their counts, most frequent first
+    pub fn top_keys(&self) -> Vec<(String, u64)> {
+        let mut keys = self.keys.iter()
+            .map(|(key, count)| (key.clone(), *count))
+            .collect::<Vec<(String, u64)>>();
+        keys.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        keys.truncate(self.top_n);
+        keys
+    }
+
+    /// The top_n most frequent key=value pairs with their counts, most frequent first
+    pub fn top_tags(&self) -> Vec<(String, String, u64)> {
+        let mut tags = self.tags.iter()
+            .map(|((key, value), count)| (key.clone(), value.clone(), *count))
+            .collect::<Vec<(String, String, u64)>>();
+        tags.sort_by(|a, b| {
+            b.2.cmp(&a.2)
+                .then_with(|| a.0.cmp(&b.0))
+                .then_with(|| a.1.cmp(&b.1))
+        });
+        tags.truncate(self.top_n);
+        tags
+    }
+
+    /// JSON representation of the statistics
+    ///
+    /// The bbox, timestamp, count, minid and maxid entries follow the layout of the data section
+    /// in `osmium fileinfo --json` output.
+    pub fn to_json(&self) -> JsonValue {
+        let bbox = match &self.bounding_box {
+            None => {
+                JsonValue::Null
+            }
+            Some(bounding_box) => {
+                json::array![
+                    bounding_box.left(),
+                    bounding_box.bottom(),
+                    bounding_box.right(),
+                    bounding_box.top()
+                ]
+            }
+        };
+
+        let to_iso8601 = |timestamp: Option<i64>| -> JsonValue {
+            timestamp
+                .and_then(|timestamp| timestamp_to_iso8601_seconds(timestamp * 1000).ok())
+                .into()
+        };
+
+        let top_keys = self.top_keys().into_iter()
+            .map(|(key, count)| object! {key: key, count: count})
+            .collect::<Vec<JsonValue>>();
+
+        let top_tags = self.top_tags().into_iter()
+            .map(|(key, value, count)| object! {key: key, value: value, count: count})
+            .collect::<Vec<JsonValue>>();
+
+        object! {
+            bbox: bbox,
+            timestamp: {
+                first: to_iso8601(self.first_timestamp),
+                last: to_iso8601(self.last_timestamp),
+            },
+            count: {
+                nodes: self.nodes.count,
+                ways: self.ways.count,
+                relations: self.relations.count,
+            },
+            minid: {
+                nodes: self.nodes.min_id,
+                ways: self.ways.min_id,
+                relations: self.relations.min_id,
+            },
+            maxid: {
+                nodes: self.nodes.max_id,
+                ways: self.ways.max_id,
+                relations: self.relations.max_id,
+            },
+            changesets: self.changesets(),
+            users: self.users(),
+            max_way_nodes: self.max_way_nodes,
+            max_relation_members: self.max_relation_members,
+            top_keys: top_keys,
+            top_tags: top_tags,
+        }
+    }
+}
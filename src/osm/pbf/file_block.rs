@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
 
 use anyhow::{anyhow, Context};
 use flate2::bufread::ZlibDecoder;
@@ -10,14 +10,19 @@ use prost::Message;
 use crate::osmpbf;
 use crate::osm::model::bounding_box::BoundingBox;
 use crate::osm::model::element::Element;
+use crate::osm::model::element_kind::ElementKind;
 use crate::osm::pbf::blob_desc::BlobDesc;
 use crate::osm::pbf::compression_type::CompressionType;
 use crate::osm::pbf::file_block_metadata::FileBlockMetadata;
 use crate::osm::pbf::osm_data::OsmData;
 use crate::osm::pbf::osm_header::OsmHeader;
+use crate::osm::pbf::writer_options::WriterOptions;
 use crate::osmpbf::BlobHeader;
 use crate::osmpbf::blob::Data;
 
+/// Encoded blob header and blob body
+pub(crate) type EncodedBlob = (Vec<u8>, Vec<u8>);
+
 /// A header or data file block in *.osm.pbf file
 #[derive(Debug)]
 pub enum FileBlock {
@@ -32,7 +37,8 @@ pub enum FileBlock {
 }
 
 impl FileBlock {
-    pub(crate) fn new(index: usize, blob_type: String, data: Vec<u8>) -> Result<FileBlock, anyhow::Error> {
+    /// Create a file block decoding only the elements of the requested kinds
+    pub(crate) fn new_of_kinds(index: usize, blob_type: String, data: Vec<u8>, skip_metadata: bool, kinds: &[ElementKind]) -> Result<FileBlock, anyhow::Error> {
         let blob_type_str = blob_type.as_str();
         match blob_type_str {
             "OSMHeader" => {
@@ -47,7 +53,7 @@ impl FileBlock {
                 Ok(
                     FileBlock::Data {
                         metadata: FileBlockMetadata::new(blob_type, index),
-                        data: OsmData::new(data)?,
+                        data: OsmData::new_of_kinds(data, skip_metadata, kinds)?,
                     }
                 )
             }
@@ -69,13 +75,6 @@ impl FileBlock {
         }
     }
 
-    pub(crate) fn from_elements(index: usize, elements: Vec<Element>) -> FileBlock {
-        FileBlock::Data {
-            metadata: FileBlockMetadata::new("OSMData".to_string(), index),
-            data: OsmData::from_elements(elements, None),
-        }
-    }
-
     #[allow(dead_code)]
     pub(crate) fn compute_bounding_box(&self) -> Option<BoundingBox> {
         match self {
@@ -155,6 +154,27 @@ impl FileBlock {
     }
 
     pub(crate) fn from_blob_desc(blob_desc: &BlobDesc) -> Result<FileBlock, anyhow::Error> {
+        Self::from_blob_desc_of_kinds(blob_desc, &ElementKind::ALL)
+    }
+
+    /// Read a file block decoding only the elements of the requested kinds
+    pub(crate) fn from_blob_desc_of_kinds(blob_desc: &BlobDesc, kinds: &[ElementKind]) -> Result<FileBlock, anyhow::Error> {
+        let data = Self::read_blob_desc_data(blob_desc)?;
+        FileBlock::new_of_kinds(blob_desc.index(), blob_desc.t(), data, blob_desc.skip_metadata(), kinds)
+    }
+
+    /// The first and the last element kind in a data block, without decoding the elements
+    pub(crate) fn kind_range(blob_desc: &BlobDesc) -> Result<Option<(ElementKind, ElementKind)>, anyhow::Error> {
+        if blob_desc.t() != "OSMData" {
+            return Ok(None);
+        }
+        OsmData::kind_range(Self::read_blob_desc_data(blob_desc)?)
+    }
+
+    pub(crate) fn read_blob_desc_data(blob_desc: &BlobDesc) -> Result<Vec<u8>, anyhow::Error> {
+        if let Some(mapped_data) = blob_desc.mapped_data() {
+            return Self::decode_blob(blob_desc, mapped_data);
+        }
         let mut file = File::open(blob_desc.path()).with_context(
             || anyhow!("Failed to open {:?} for reading", blob_desc.path())
         )?;
@@ -165,19 +185,97 @@ impl FileBlock {
         file.read_exact(&mut blob_buffer).ok().with_context(
             || anyhow!("Failed to read {} bytes from {:?} ", blob_desc.length(), blob_desc.path())
         )?;
-        Self::deserialize(blob_desc, &mut blob_buffer)
+        Self::decode_blob(blob_desc, &blob_buffer)
     }
 
-    pub(crate) fn serialize(file_block: &FileBlock, compression: CompressionType) -> Result<(Vec<u8>, Vec<u8>), anyhow::Error> {
+    fn decode_blob(blob_desc: &BlobDesc, blob_buffer: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+        // use BlobDesc rather than BlobHeader to skip reading again the blob header
+        let protobuf_blob = osmpbf::Blob::decode(blob_buffer).with_context(
+            || anyhow!("Failed to decode a message from blob {} from {:?}", blob_desc.index(), blob_desc.path())
+        )?;
+        FileBlock::read_blob_data(protobuf_blob)
+    }
+
+    pub(crate) fn serialize(file_block: &FileBlock, compression: CompressionType, options: &WriterOptions) -> Result<(Vec<u8>, Vec<u8>), anyhow::Error> {
         let (blob_type, compression_level, block_data) = match file_block {
             FileBlock::Header { metadata: _, header } => {
                 ("OSMHeader".to_string(), Compression::none(), header.serialize()?)
             }
             FileBlock::Data { metadata: _, data } => {
-                ("OSMData".to_string(), Compression::default(), data.serialize()?)
+                ("OSMData".to_string(), Compression::default(), data.serialize(options)?)
             }
         };
+        Self::verify_block_size(block_data.len(), options)?;
+        Self::to_blob(blob_type, compression_level, block_data, compression)
+    }
+
+    /// Serialize elements into one or more data blobs.
+    ///
+    /// The elements are split in halves until each block fits into the maximal block size. Fails
+    /// if a single element does not fit.
+    pub(crate) fn serialize_elements(elements: Vec<Element>, compression: CompressionType, options: &WriterOptions) -> Result<Vec<EncodedBlob>, anyhow::Error> {
+        let mut blobs = Vec::new();
+        Self::serialize_elements_into(elements, compression, options, &mut blobs)?;
+        Ok(blobs)
+    }
+
+    fn serialize_elements_into(elements: Vec<Element>, compression: CompressionType, options: &WriterOptions, blobs: &mut Vec<EncodedBlob>) -> Result<(), anyhow::Error> {
+        let mut data = OsmData::from_elements(elements, None);
+        let block_data = data.serialize(options)?;
+        if block_data.len() <= options.max_block_size() {
+            blobs.push(Self::to_blob("OSMData".to_string(), Compression::default(), block_data, compression)?);
+            return Ok(());
+        }
+
+        let mut elements = data.take_elements();
+        if elements.len() < 2 {
+            return Err(
+                anyhow!(
+                    "The {} does not fit into a block of {} bytes, encoded size: {}",
+                    elements.first().map(Self::element_description).unwrap_or_default(),
+                    options.max_block_size(),
+                    block_data.len(),
+                )
+            );
+        }
+        let second_half = elements.split_off(elements.len() / 2);
+        Self::serialize_elements_into(elements, compression.clone(), options, blobs)?;
+        Self::serialize_elements_into(second_half, compression, options, blobs)
+    }
+
+    /// Pad the encoded blob header with an `indexdata` field of `padding` bytes
+    pub(crate) fn pad_blob_header(blob_header: &[u8], padding: Option<usize>) -> Result<Vec<u8>, anyhow::Error> {
+        let mut blob_header = BlobHeader::decode(blob_header)?;
+        blob_header.indexdata = padding.map(|padding| vec![0; padding]);
+        Ok(blob_header.encode_to_vec())
+    }
+
+    fn element_description(element: &Element) -> String {
+        match element {
+            Element::Node { node } => {
+                format!("node {}", node.id())
+            }
+            Element::Way { way } => {
+                format!("way {}", way.id())
+            }
+            Element::Relation { relation } => {
+                format!("relation {}", relation.id())
+            }
+            Element::Sentinel => {
+                "sentinel".to_string()
+            }
+        }
+    }
+
+    fn verify_block_size(block_size: usize, options: &WriterOptions) -> Result<(), anyhow::Error> {
+        if block_size > options.max_block_size() {
+            Err(anyhow!("Encoded block size {} exceeds the maximal block size {}", block_size, options.max_block_size()))
+        } else {
+            Ok(())
+        }
+    }
 
+    fn to_blob(blob_type: String, compression_level: Compression, block_data: Vec<u8>, compression: CompressionType) -> Result<(Vec<u8>, Vec<u8>), anyhow::Error> {
         let mut raw_size = None;
         let mut data = None;
         if !block_data.is_empty() {
@@ -211,15 +309,6 @@ impl FileBlock {
         Ok((header, body))
     }
 
-    fn deserialize(blob_desc: &BlobDesc, blob_buffer: &mut Vec<u8>) -> Result<FileBlock, anyhow::Error> {
-        // use BlobDesc rather than BlobHeader to skip reading again the blob header
-        let protobuf_blob = osmpbf::Blob::decode(&mut Cursor::new(blob_buffer)).with_context(
-            || anyhow!("Failed to decode a message from blob {} from {:?}", blob_desc.index(), blob_desc.path())
-        )?;
-        let data = FileBlock::read_blob_data(protobuf_blob)?;
-        FileBlock::new(blob_desc.index(), blob_desc.t(), data)
-    }
-
     #[allow(dead_code)]
     pub(crate) fn metadata(&self) -> &FileBlockMetadata {
         match self {
@@ -291,3 +380,56 @@ impl Default for FileBlock {
         FileBlock::Data { metadata: Default::default(), data: Default::default() }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use prost::Message;
+
+    use crate::osm::model::coordinate::Coordinate;
+    use crate::osm::model::element::Element;
+    use crate::osm::model::node::Node;
+    use crate::osm::model::tag::Tag;
+    use crate::osm::pbf::compression_type::CompressionType;
+    use crate::osm::pbf::file_block::FileBlock;
+    use crate::osm::pbf::writer_options::WriterOptions;
+    use crate::osmpbf;
+
+    fn node(id: i64, tag_size: usize) -> Element {
+        Element::Node {
+            node: Node::new(
+                id,
+                1,
+                Coordinate::new(1.0, 1.0),
+                0,
+                1,
+                1,
+                "user".to_string(),
+                true,
+                vec![Tag::new("key".to_string(), format!("{id:0>tag_size$}"))],
+            )
+        }
+    }
+
+    #[test]
+    fn test_serialize_elements_split() -> Result<(), anyhow::Error> {
+        let mut options = WriterOptions::default();
+        options.with_max_block_size(10000);
+        let elements = (1..100).map(|id| node(id, 500)).collect::<Vec<Element>>();
+        let blobs = FileBlock::serialize_elements(elements, CompressionType::Uncompressed, &options)?;
+        assert!(blobs.len() > 1);
+        for (_header, body) in blobs {
+            let blob = osmpbf::Blob::decode(body.as_slice())?;
+            assert!(blob.raw_size.unwrap() as usize <= options.max_block_size());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_serialize_elements_element_too_large() {
+        let mut options = WriterOptions::default();
+        options.with_max_block_size(1000);
+        let elements = vec![node(1, 10), node(2, 2000)];
+        let result = FileBlock::serialize_elements(elements, CompressionType::Uncompressed, &options);
+        assert!(result.expect_err("Expected an error").to_string().contains("node 2"));
+    }
+}
@@ -45,12 +45,16 @@ impl FileInfo {
         }
     }
 
-    #[allow(dead_code)]
     pub(crate) fn merge_bounding_box(&mut self, bounding_box: Option<BoundingBox>) {
-        if self.bounding_box.is_none() {
-            self.bounding_box = bounding_box;
-        } else if bounding_box.is_some() {
-            self.bounding_box.as_mut().unwrap().merge_bounding_box(bounding_box.as_ref().unwrap());
+        if let Some(bounding_box) = bounding_box {
+            match self.bounding_box.as_mut() {
+                None => {
+                    self.bounding_box = Some(bounding_box);
+                }
+                Some(current) => {
+                    current.merge_bounding_box(&bounding_box);
+                }
+            }
         }
     }
 
@@ -0,0 +1,80 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Error;
+use command_executor::command::Command;
+
+use crate::osm::model::element::Element;
+use crate::osm::pbf::blob_desc::BlobDesc;
+use crate::osm::pbf::file_block::FileBlock;
+
+type MapFn<T> = dyn Fn(Vec<Element>) -> Result<T, Error> + Send + Sync;
+type ReduceFn<T> = dyn Fn(T, T) -> T + Send + Sync;
+
+/// Map the elements of a single data block and reduce the block result into the shared result
+///
+/// Errors are recorded in the shared error instead of being returned to the thread pool, which
+/// would panic on join. Once an error is recorded the remaining blocks are skipped.
+pub(crate) struct MapReduceCommand<T> {
+    blob_desc: BlobDesc,
+    map: Arc<MapFn<T>>,
+    reduce: Arc<ReduceFn<T>>,
+    result: Arc<Mutex<Option<T>>>,
+    error: Arc<Mutex<Option<Error>>>,
+}
+
+impl<T> MapReduceCommand<T> {
+    pub(crate) fn new(
+        blob_desc: BlobDesc,
+        map: Arc<MapFn<T>>,
+        reduce: Arc<ReduceFn<T>>,
+        result: Arc<Mutex<Option<T>>>,
+        error: Arc<Mutex<Option<Error>>>,
+    ) -> MapReduceCommand<T> {
+        MapReduceCommand {
+            blob_desc,
+            map,
+            reduce,
+            result,
+            error,
+        }
+    }
+
+    fn map_reduce(&self) -> Result<(), Error> {
+        let mut file_block = FileBlock::from_blob_desc(&self.blob_desc)?;
+        if !file_block.is_osm_data() {
+            return Ok(());
+        }
+        let mut value = (self.map)(file_block.take_elements())?;
+        // reduce outside the lock, so that the other threads are not blocked by the reduction
+        loop {
+            let current = self.result.lock().unwrap().take();
+            match current {
+                None => {
+                    let mut result_guard = self.result.lock().unwrap();
+                    if result_guard.is_none() {
+                        result_guard.replace(value);
+                        return Ok(());
+                    }
+                }
+                Some(current) => {
+                    value = (self.reduce)(current, value);
+                }
+            }
+        }
+    }
+}
+
+impl<T: Send> Command for MapReduceCommand<T> {
+    fn execute(&self) -> Result<(), Error> {
+        if self.error.lock().unwrap().is_some() {
+            return Ok(());
+        }
+        if let Err(e) = self.map_reduce() {
+            let mut error_guard = self.error.lock().unwrap();
+            if error_guard.is_none() {
+                error_guard.replace(e);
+            }
+        }
+        Ok(())
+    }
+}
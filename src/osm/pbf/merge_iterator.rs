@@ -0,0 +1,55 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::osm::model::element::Element;
+
+/// K-way merge of ordered element iterators
+///
+/// Each of the input iterators must yield elements in order. The merged sequence is ordered as
+/// well. Equal elements are yielded in the order of the input iterators that produced them.
+/// [Element::Sentinel] values in the input are skipped.
+pub struct MergeIterator {
+    iterators: Vec<Box<dyn Iterator<Item=Element>>>,
+    heap: BinaryHeap<Reverse<(Element, usize)>>,
+}
+
+impl MergeIterator {
+    /// Create a new [MergeIterator]
+    ///
+    /// * iterators - ordered element iterators to merge
+    pub fn new(iterators: Vec<Box<dyn Iterator<Item=Element>>>) -> MergeIterator {
+        let mut merge_iterator = MergeIterator {
+            iterators,
+            heap: BinaryHeap::new(),
+        };
+        for i in 0..merge_iterator.iterators.len() {
+            merge_iterator.advance(i);
+        }
+        merge_iterator
+    }
+
+    fn advance(&mut self, i: usize) {
+        for element in self.iterators[i].by_ref() {
+            if !element.is_sentinel() {
+                self.heap.push(Reverse((element, i)));
+                break;
+            }
+        }
+    }
+}
+
+impl Iterator for MergeIterator {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.heap.pop() {
+            None => {
+                None
+            }
+            Some(Reverse((element, i))) => {
+                self.advance(i);
+                Some(element)
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+
+use crate::osm::model::element::Element;
+use crate::osm::pbf::compression_type::CompressionType;
+use crate::osm::pbf::file_info::FileInfo;
+use crate::osm::pbf::merge_iterator::MergeIterator;
+use crate::osm::pbf::reader::Reader;
+use crate::osm::pbf::writer::Writer;
+
+/// Merge ordered *.osm.pbf files into a single ordered file
+///
+/// Elements that appear in more than one input with the same type, id and version are written
+/// once. By default only the latest version of each element is written, including the versions
+/// that delete an element, so the HistoricalInformation required feature of the inputs is kept and
+/// readers honor the visible flag. Use [Merger::with_keep_all_versions] to merge history files and
+/// [Merger::with_drop_deleted] to drop the deleted elements, which also drops the
+/// HistoricalInformation required feature.
+///
+/// The header of the output is based on the header of the first input with the bounding boxes
+/// and the features of all inputs combined.
+///
+/// Example:
+/// ```
+/// use std::path::PathBuf;
+/// use osm_io::osm::pbf::compression_type::CompressionType;
+/// use osm_io::osm::pbf::merger::Merger;
+/// fn example() -> Result<(), anyhow::Error> {
+///     let input_paths = vec![
+///         PathBuf::from("./tests/fixtures/malta-230109.osm.pbf"),
+///         PathBuf::from("./tests/fixtures/gozo-230109.osm.pbf"),
+///     ];
+///     let output_path = PathBuf::from("./target/results/malta-and-gozo-230109.osm.pbf");
+///     let merger = Merger::new(&input_paths)?;
+///     merger.merge(output_path, CompressionType::Zlib)?;
+///     Ok(())
+/// }
+/// ```
+pub struct Merger {
+    readers: Vec<Reader>,
+    file_info: FileInfo,
+    keep_all_versions: bool,
+    drop_deleted: bool,
+}
+
+impl Merger {
+    /// Create a new [Merger]
+    ///
+    /// * input_paths - paths to *.osm.pbf files ordered by type, then id, then version
+    pub fn new(input_paths: &[PathBuf]) -> Result<Merger, anyhow::Error> {
+        let mut readers = Vec::new();
+        for input_path in input_paths {
+            readers.push(Reader::new(input_path)?);
+        }
+
+        let mut file_info = readers.first()
+            .ok_or(anyhow!("No input files to merge"))?
+            .info()
+            .clone();
+
+        let mut required_features = file_info.required_features().clone();
+        let mut optional_features = file_info.optional_features().clone();
+        for reader in &readers[1..] {
+            file_info.merge_bounding_box(reader.info().bounding_box().clone());
+            Self::merge_features(&mut required_features, reader.info().required_features());
+            Self::merge_features(&mut optional_features, reader.info().optional_features());
+        }
+        Self::merge_features(&mut optional_features, &["Sort.Type_then_ID".to_string()]);
+        file_info.with_required_features(&required_features);
+        file_info.with_optional_features(&optional_features);
+
+        Ok(
+            Merger {
+                readers,
+                file_info,
+                keep_all_versions: false,
+                drop_deleted: false,
+            }
+        )
+    }
+
+    /// Write all versions of each element, as required for history files
+    pub fn with_keep_all_versions(&mut self, keep_all_versions: bool) {
+        self.keep_all_versions = keep_all_versions;
+    }
+
+    /// Drop the elements whose latest version is deleted. Ignored when all versions are kept
+    pub fn with_drop_deleted(&mut self, drop_deleted: bool) {
+        self.drop_deleted = drop_deleted;
+    }
+
+    /// Header data for the output file
+    pub fn file_info(&self) -> &FileInfo {
+        &self.file_info
+    }
+
+    /// Replace the header data for the output file
+    pub fn with_file_info(&mut self, file_info: FileInfo) {
+        self.file_info = file_info;
+    }
+
+    /// Merge the inputs into output_path
+    pub fn merge(&self, output_path: PathBuf, compression_type: CompressionType) -> Result<(), anyhow::Error> {
+        let mut file_info = self.file_info.clone();
+        if !self.keep_all_versions && self.drop_deleted {
+            let required_features = file_info.required_features().iter()
+                .filter(|feature| feature.as_str() != "HistoricalInformation")
+                .cloned()
+                .collect::<Vec<String>>();
+            file_info.with_required_features(&required_features);
+        }
+
+        let mut iterators: Vec<Box<dyn Iterator<Item=Element>>> = Vec::new();
+        for reader in &self.readers {
+            iterators.push(Box::new(reader.elements()?));
+        }
+
+        let mut writer = Writer::from_file_info(output_path, file_info, compression_type)?;
+        writer.write_header()?;
+
+        let mut pending: Option<Element> = None;
+        for element in MergeIterator::new(iterators) {
+            match pending.take() {
+                None => {
+                    pending = Some(element);
+                }
+                Some(previous) => {
+                    if element < previous {
+                        return Err(
+                            anyhow!("Input is not ordered, {:?} follows {:?}", element, previous)
+                        );
+                    } else if element == previous {
+                        pending = Some(previous);
+                    } else if !self.keep_all_versions && Self::same_id(&previous, &element) {
+                        pending = Some(element);
+                    } else {
+                        self.write(&mut writer, previous)?;
+                        pending = Some(element);
+                    }
+                }
+            }
+        }
+        if let Some(element) = pending {
+            self.write(&mut writer, element)?;
+        }
+
+        writer.close()
+    }
+
+    fn write(&self, writer: &mut Writer, element: Element) -> Result<(), anyhow::Error> {
+        if self.keep_all_versions || !self.drop_deleted || Self::visible(&element) {
+            writer.write_element(element)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn merge_features(features: &mut Vec<String>, other: &[String]) {
+        for feature in other {
+            if !features.contains(feature) {
+                features.push(feature.clone());
+            }
+        }
+    }
+
+    fn same_id(e1: &Element, e2: &Element) -> bool {
+        match (e1, e2) {
+            (Element::Node { node: n1 }, Element::Node { node: n2 }) => {
+                n1.id() == n2.id()
+            }
+            (Element::Way { way: w1 }, Element::Way { way: w2 }) => {
+                w1.id() == w2.id()
+            }
+            (Element::Relation { relation: r1 }, Element::Relation { relation: r2 }) => {
+                r1.id() == r2.id()
+            }
+            _ => {
+                false
+            }
+        }
+    }
+
+    fn visible(element: &Element) -> bool {
+        match element {
+            Element::Node { node } => {
+                node.visible()
+            }
+            Element::Way { way } => {
+                way.visible()
+            }
+            Element::Relation { relation } => {
+                relation.visible()
+            }
+            Element::Sentinel => {
+                false
+            }
+        }
+    }
+}
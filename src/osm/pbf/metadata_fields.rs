@@ -0,0 +1,111 @@
+/// Selection of the element metadata fields written to *.osm.pbf files
+///
+/// Omitting metadata that consumers do not need reduces both the file size and the encoding time.
+/// When no field is selected the `DenseInfo` and `Info` messages are omitted entirely. The omitted
+/// fields are read back with default values, for example version 0 and an empty user.
+/// The visibility flag is only needed for files with historical information.
+///
+/// Example:
+/// ```
+/// use osm_io::osm::pbf::metadata_fields::MetadataFields;
+/// use osm_io::osm::pbf::writer_options::WriterOptions;
+/// // keep only the version
+/// let mut metadata_fields = MetadataFields::none();
+/// metadata_fields.with_version(true);
+/// let mut options = WriterOptions::default();
+/// options.with_metadata_fields(metadata_fields);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetadataFields {
+    version: bool,
+    timestamp: bool,
+    changeset: bool,
+    uid: bool,
+    user: bool,
+    visible: bool,
+}
+
+impl MetadataFields {
+    /// Select all metadata fields
+    pub fn all() -> MetadataFields {
+        MetadataFields {
+            version: true,
+            timestamp: true,
+            changeset: true,
+            uid: true,
+            user: true,
+            visible: true,
+        }
+    }
+
+    /// Select no metadata fields
+    pub fn none() -> MetadataFields {
+        MetadataFields {
+            version: false,
+            timestamp: false,
+            changeset: false,
+            uid: false,
+            user: false,
+            visible: false,
+        }
+    }
+
+    /// True if no field is selected
+    pub fn is_empty(&self) -> bool {
+        *self == Self::none()
+    }
+
+    pub fn version(&self) -> bool {
+        self.version
+    }
+
+    pub fn with_version(&mut self, version: bool) {
+        self.version = version;
+    }
+
+    pub fn timestamp(&self) -> bool {
+        self.timestamp
+    }
+
+    pub fn with_timestamp(&mut self, timestamp: bool) {
+        self.timestamp = timestamp;
+    }
+
+    pub fn changeset(&self) -> bool {
+        self.changeset
+    }
+
+    pub fn with_changeset(&mut self, changeset: bool) {
+        self.changeset = changeset;
+    }
+
+    pub fn uid(&self) -> bool {
+        self.uid
+    }
+
+    pub fn with_uid(&mut self, uid: bool) {
+        self.uid = uid;
+    }
+
+    pub fn user(&self) -> bool {
+        self.user
+    }
+
+    pub fn with_user(&mut self, user: bool) {
+        self.user = user;
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn with_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+}
+
+impl Default for MetadataFields {
+    fn default() -> Self {
+        Self::all()
+    }
+}
@@ -1,23 +1,36 @@
 pub mod reader;
 pub mod writer;
+pub mod writer_options;
+pub mod metadata_fields;
 pub mod parallel_writer;
+pub mod parallel_writer_options;
 pub mod element_iterator;
+pub mod element_kind_iterator;
+pub mod parallel_element_iterator;
 pub mod file_block_iterator;
 pub mod file_block;
+pub mod block_view;
+pub mod block_view_iterator;
+pub mod element_ref;
 pub mod file_info;
 pub mod compression_type;
 pub mod thread_local_accumulator;
 pub mod bounding_box_calculator;
+pub mod merge_iterator;
+pub mod element_sorter;
+pub mod merger;
+pub mod element_statistics;
 
 pub(crate) mod dense_group_builder;
 pub(crate) mod string_table_builder;
 pub(crate) mod ways_group_builder;
 pub(crate) mod relations_group_builder;
 pub(crate) mod parallel_element_iteration_command;
+pub(crate) mod decode_file_block_command;
 pub(crate) mod element_accumulator;
 pub(crate) mod file_block_metadata;
 pub(crate) mod osm_data;
 pub(crate) mod osm_header;
 pub(crate) mod blob_iterator;
 pub(crate) mod blob_desc;
-pub(crate) mod calc_bounding_box_command;
+pub(crate) mod map_reduce_command;
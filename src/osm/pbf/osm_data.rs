@@ -7,10 +7,12 @@ use prost::Message;
 use crate::{osm, osmpbf};
 use crate::osm::model::bounding_box::BoundingBox;
 use crate::osm::model::element::Element;
+use crate::osm::model::element_kind::ElementKind;
 use crate::osm::pbf::dense_group_builder::DenseGroupBuilder;
 use crate::osm::pbf::relations_group_builder::RelationsGroupBuilder;
 use crate::osm::pbf::string_table_builder::StringTableBuilder;
 use crate::osm::pbf::ways_group_builder::WaysGroupBuilder;
+use crate::osm::pbf::writer_options::WriterOptions;
 use crate::osmpbf::{PrimitiveBlock, PrimitiveGroup};
 
 #[derive(Debug, Default)]
@@ -20,8 +22,18 @@ pub struct OsmData {
 }
 
 impl OsmData {
-    pub fn new(data: Vec<u8>) -> Result<OsmData, anyhow::Error> {
+    pub fn new(data: Vec<u8>, skip_metadata: bool) -> Result<OsmData, anyhow::Error> {
+        Self::new_of_kinds(data, skip_metadata, &ElementKind::ALL)
+    }
+
+    /// Decode only the primitive groups with elements of the requested kinds
+    pub(crate) fn new_of_kinds(data: Vec<u8>, skip_metadata: bool, kinds: &[ElementKind]) -> Result<OsmData, anyhow::Error> {
         let primitive_block = PrimitiveBlock::decode(&mut Cursor::new(data))?;
+        let wanted = |g: &PrimitiveGroup| Self::group_kind(g).is_some_and(|kind| kinds.contains(&kind));
+        if !primitive_block.primitivegroup.iter().any(wanted) {
+            return Ok(OsmData::default());
+        }
+
         let string_table: Vec<String> = primitive_block.stringtable.s.iter()
             .map(
                 |e| {
@@ -34,11 +46,11 @@ impl OsmData {
         let lat_offset = primitive_block.lat_offset();
         let lon_offset = primitive_block.lon_offset();
         let mut elements = Vec::<Element>::with_capacity(8000);
-        for g in &primitive_block.primitivegroup {
-            Self::read_dense(&g.dense, &string_table, granularity, date_granularity, lat_offset, lon_offset, &mut elements);
-            Self::read_nodes(&g.nodes, &string_table, granularity, date_granularity, lat_offset, lon_offset, &mut elements);
-            Self::read_ways(&g.ways, &string_table, granularity, date_granularity, &mut elements);
-            Self::read_relations(&g.relations, &string_table, granularity, date_granularity, &mut elements);
+        for g in primitive_block.primitivegroup.iter().filter(|g| wanted(g)) {
+            Self::read_dense(&g.dense, &string_table, granularity, date_granularity, lat_offset, lon_offset, skip_metadata, &mut elements);
+            Self::read_nodes(&g.nodes, &string_table, granularity, date_granularity, lat_offset, lon_offset, skip_metadata, &mut elements);
+            Self::read_ways(&g.ways, &string_table, granularity, date_granularity, skip_metadata, &mut elements);
+            Self::read_relations(&g.relations, &string_table, granularity, date_granularity, skip_metadata, &mut elements);
             Self::read_changesets(&g.changesets, &string_table, granularity, date_granularity, lat_offset, lon_offset, &mut elements);
         }
         Ok(
@@ -46,6 +58,27 @@ impl OsmData {
         )
     }
 
+    /// The first and the last element kind in a block, without decoding the elements
+    pub(crate) fn kind_range(data: Vec<u8>) -> Result<Option<(ElementKind, ElementKind)>, anyhow::Error> {
+        let primitive_block = PrimitiveBlock::decode(&mut Cursor::new(data))?;
+        let mut kinds = primitive_block.primitivegroup.iter().filter_map(Self::group_kind);
+        Ok(
+            kinds.next().map(|first| (first, kinds.next_back().unwrap_or(first)))
+        )
+    }
+
+    fn group_kind(group: &PrimitiveGroup) -> Option<ElementKind> {
+        if group.dense.as_ref().is_some_and(|dense| !dense.id.is_empty()) || !group.nodes.is_empty() {
+            Some(ElementKind::Node)
+        } else if !group.ways.is_empty() {
+            Some(ElementKind::Way)
+        } else if !group.relations.is_empty() {
+            Some(ElementKind::Relation)
+        } else {
+            None
+        }
+    }
+
     pub fn from_elements(elements: Vec<Element>, bounding_box: Option<BoundingBox>) -> OsmData {
         OsmData { elements, bounding_box }
     }
@@ -85,25 +118,28 @@ impl OsmData {
         result
     }
 
-    fn read_dense(dense_group: &Option<osmpbf::DenseNodes>, string_table: &Vec<String>, granularity: i64, date_granularity: i32, lat_offset: i64, lon_offset: i64, elements: &mut Vec<Element>) {
+    #[allow(clippy::too_many_arguments)]
+    fn read_dense(dense_group: &Option<osmpbf::DenseNodes>, string_table: &Vec<String>, granularity: i64, date_granularity: i32, lat_offset: i64, lon_offset: i64, skip_metadata: bool, elements: &mut Vec<Element>) {
         if let Some(dense) = dense_group {
             let mut last_id = 0_i64;
             let mut last_lat = 0_i64;
             let mut last_lon = 0_i64;
 
             let mut last_timestamp = 0_i64;
-            let mut timestamp = 0_i64;
-            let mut last_changeset = -1_i64;
-            let mut uid = -1_i32;
+            let mut last_changeset = 0_i64;
+            let mut last_uid = 0_i32;
             let mut last_user_sid = 0_i32;
 
-            if let Some(_info) = &dense.denseinfo {
-                last_timestamp = 0_i64;
-                last_changeset = 0_i64;
-                uid = 0_i32;
-                last_user_sid = 0_i32;
-            }
-            let mut visible = true;
+            // any of the DenseInfo arrays may be omitted by the writer
+            let default_info = osmpbf::DenseInfo::default();
+            let info = match &dense.denseinfo {
+                None => {
+                    &default_info
+                }
+                Some(info) => {
+                    info
+                }
+            };
 
             let mut key_val_iterator = <Vec<i32> as Borrow<Vec<i32>>>::borrow(&dense.keys_vals).iter();
             for (i, id) in <Vec<i64> as Borrow<Vec<i64>>>::borrow(&dense.id).iter().enumerate() {
@@ -111,22 +147,33 @@ impl OsmData {
                 last_lat += dense.lat[i];
                 last_lon += dense.lon[i];
 
+                let visible = info.visible.get(i).copied().unwrap_or(true);
                 let mut version = 0_i32;
+                // the same defaults as in read_info
+                let mut timestamp = -1_i64;
+                let mut changeset = -1_i64;
+                let mut uid = -1_i32;
                 let mut user: String = String::default();
-                if let Some(info) = &dense.denseinfo {
-                    last_timestamp += info.timestamp[i];
-                    timestamp = last_timestamp * date_granularity as i64;
-                    last_changeset += info.changeset[i];
-                    uid += info.uid[i];
-                    last_user_sid += info.user_sid[i];
-                    let user_sid = last_user_sid as usize;
-                    if info.visible.len() > i {
-                        visible = info.visible[i];
-                    } else {
-                        visible = true;
+                if !skip_metadata {
+                    if let Some(v) = info.version.get(i) {
+                        version = *v;
+                    }
+                    if let Some(delta) = info.timestamp.get(i) {
+                        last_timestamp += delta;
+                        timestamp = last_timestamp * date_granularity as i64;
+                    }
+                    if let Some(delta) = info.changeset.get(i) {
+                        last_changeset += delta;
+                        changeset = last_changeset;
+                    }
+                    if let Some(delta) = info.uid.get(i) {
+                        last_uid += delta;
+                        uid = last_uid;
+                    }
+                    if let Some(delta) = info.user_sid.get(i) {
+                        last_user_sid += delta;
+                        user = string_table.index(last_user_sid as usize).clone();
                     }
-                    version = info.version[i];
-                    user = string_table.index(user_sid).clone();
                 }
 
                 let mut tags = Vec::<osm::model::tag::Tag>::new();
@@ -155,7 +202,7 @@ impl OsmData {
                     version,
                     coordinate,
                     timestamp,
-                    last_changeset,
+                    changeset,
                     uid,
                     user,
                     visible,
@@ -166,7 +213,8 @@ impl OsmData {
         }
     }
 
-    fn read_nodes(node_group: &Vec<osmpbf::Node>, string_table: &[String], granularity: i64, date_granularity: i32, lat_offset: i64, lon_offset: i64, elements: &mut Vec<Element>) {
+    #[allow(clippy::too_many_arguments)]
+    fn read_nodes(node_group: &Vec<osmpbf::Node>, string_table: &[String], granularity: i64, date_granularity: i32, lat_offset: i64, lon_offset: i64, skip_metadata: bool, elements: &mut Vec<Element>) {
         for node in node_group {
             let id = node.id;
             let coordinate = osm::model::coordinate::Coordinate::new(
@@ -175,7 +223,7 @@ impl OsmData {
             );
 
             let (timestamp, changeset, uid, user, visible, version) =
-                Self::read_info(string_table, date_granularity, &node.info);
+                Self::read_info(string_table, date_granularity, &node.info, skip_metadata);
 
             let mut tags = Vec::<osm::model::tag::Tag>::new();
             for i in 0..node.keys.len() {
@@ -199,33 +247,35 @@ impl OsmData {
         }
     }
 
-    fn read_info(string_table: &[String], date_granularity: i32, info_opt: &Option<osmpbf::Info>) -> (i64, i64, i32, String, bool, i32) {
+    fn read_info(string_table: &[String], date_granularity: i32, info_opt: &Option<osmpbf::Info>, skip_metadata: bool) -> (i64, i64, i32, String, bool, i32) {
         let mut timestamp = -1_i64;
         let mut changeset = -1_i64;
         let mut uid = -1_i32;
-        let user_sid;
         let mut user: String = String::default();
         let mut visible = true;
         let mut version = 0_i32;
 
         if let Some(info) = info_opt {
-            timestamp = info.timestamp.unwrap_or(0) * (date_granularity as i64);
-            changeset = info.changeset.unwrap_or(-1);
-            uid = info.uid.unwrap_or(-1);
-            user_sid = info.user_sid.unwrap_or(0) as usize;
             visible = info.visible.unwrap_or(true);
-            user = string_table[user_sid].clone();
-            version = info.version();
+            if !skip_metadata {
+                timestamp = info.timestamp.map(|timestamp| timestamp * date_granularity as i64).unwrap_or(-1);
+                changeset = info.changeset.unwrap_or(-1);
+                uid = info.uid.unwrap_or(-1);
+                if let Some(user_sid) = info.user_sid {
+                    user = string_table[user_sid as usize].clone();
+                }
+                version = info.version();
+            }
         }
         (timestamp, changeset, uid, user, visible, version)
     }
 
 
-    fn read_ways(way_group: &Vec<osmpbf::Way>, string_table: &[String], _granularity: i64, date_granularity: i32, elements: &mut Vec<Element>) {
+    fn read_ways(way_group: &Vec<osmpbf::Way>, string_table: &[String], _granularity: i64, date_granularity: i32, skip_metadata: bool, elements: &mut Vec<Element>) {
         for way in way_group {
             let id = way.id;
             let (timestamp, changeset, uid, user, visible, version) =
-                Self::read_info(string_table, date_granularity, &way.info);
+                Self::read_info(string_table, date_granularity, &way.info, skip_metadata);
 
             let mut refs = Vec::<i64>::new();
             let mut last_ref = 0_i64;
@@ -256,11 +306,11 @@ impl OsmData {
         }
     }
 
-    fn read_relations(relation_group: &Vec<osmpbf::Relation>, string_table: &[String], _granularity: i64, date_granularity: i32, elements: &mut Vec<Element>) {
+    fn read_relations(relation_group: &Vec<osmpbf::Relation>, string_table: &[String], _granularity: i64, date_granularity: i32, skip_metadata: bool, elements: &mut Vec<Element>) {
         for relation in relation_group {
             let id = relation.id;
             let (timestamp, changeset, uid, user, visible, version) =
-                Self::read_info(string_table, date_granularity, &relation.info);
+                Self::read_info(string_table, date_granularity, &relation.info, skip_metadata);
 
             let mut members = Vec::<osm::model::relation::Member>::new();
             let mut last_memid = 0_i64;
@@ -314,12 +364,8 @@ impl OsmData {
     }
 
     #[allow(clippy::unnecessary_unwrap)]
-    pub fn serialize(&self) -> Result<Vec<u8>, anyhow::Error> {
+    pub fn serialize(&self, options: &WriterOptions) -> Result<Vec<u8>, anyhow::Error> {
         let mut string_table_builder = StringTableBuilder::new();
-        let granularity = 100_i32;
-        let date_granularity = 1000_i32;
-        let lat_offset = 0_i64;
-        let lon_offset = 0_i64;
 
         let mut dense_group_builder = None;
         let mut ways_group_builder = None;
@@ -329,7 +375,7 @@ impl OsmData {
                 Element::Node { node } => {
                     if dense_group_builder.is_none() {
                         dense_group_builder = Some(
-                            DenseGroupBuilder::new(granularity, date_granularity, lat_offset, lon_offset, node, &mut string_table_builder)
+                            DenseGroupBuilder::new(options, node, &mut string_table_builder)
                         );
                     } else {
                         dense_group_builder.as_mut().unwrap().add(node, &mut string_table_builder)
@@ -338,7 +384,7 @@ impl OsmData {
                 Element::Way { way } => {
                     if ways_group_builder.is_none() {
                         ways_group_builder = Some(
-                            WaysGroupBuilder::new(date_granularity, way, &mut string_table_builder)
+                            WaysGroupBuilder::new(options, way, &mut string_table_builder)
                         );
                     } else {
                         ways_group_builder.as_mut().unwrap().add(way, &mut string_table_builder)
@@ -347,7 +393,7 @@ impl OsmData {
                 Element::Relation { relation } => {
                     if relations_group_builder.is_none() {
                         relations_group_builder = Some(
-                            RelationsGroupBuilder::new(date_granularity, relation, &mut string_table_builder)
+                            RelationsGroupBuilder::new(options, relation, &mut string_table_builder)
                         );
                     } else {
                         relations_group_builder.as_mut().unwrap().add(relation, &mut string_table_builder)
@@ -370,10 +416,10 @@ impl OsmData {
         let primitive_block = PrimitiveBlock {
             stringtable,
             primitivegroup: vec![primitivegroup],
-            granularity: Some(granularity),
-            lat_offset: Some(lat_offset),
-            lon_offset: Some(lon_offset),
-            date_granularity: Some(date_granularity),
+            granularity: Some(options.granularity()),
+            lat_offset: Some(options.lat_offset()),
+            lon_offset: Some(options.lon_offset()),
+            date_granularity: Some(options.date_granularity()),
         };
 
         let mut buf = Vec::<u8>::with_capacity(primitive_block.encoded_len());
@@ -388,4 +434,74 @@ impl OsmData {
     pub fn take_elements(&mut self) -> Vec<Element> {
         std::mem::take(&mut self.elements)
     }
+
+    /// Bounding box of the visible nodes
+    pub(crate) fn nodes_bounding_box(elements: &[Element]) -> Option<BoundingBox> {
+        let mut result: Option<BoundingBox> = None;
+        for element in elements {
+            if let Element::Node { node } = element {
+                if node.visible() {
+                    match result.as_mut() {
+                        None => {
+                            result = Some(BoundingBox::from_point(node.coordinate()));
+                        }
+                        Some(bounding_box) => {
+                            bounding_box.merge_point(node.coordinate());
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+
+    /// Pessimistic estimate of the contribution of the element to the size of an encoded block
+    ///
+    /// Assumes the maximal varint length for every number and that no string is shared with other
+    /// elements, so a block of elements with a total estimate below the limit is below the limit
+    /// once encoded.
+    pub(crate) fn estimate_encoded_size(element: &Element) -> usize {
+        // varint, tag and length overhead
+        const NUMBER: usize = 11;
+        const STRING: usize = 2 * NUMBER;
+        let tags_size = |tags: &Vec<osm::model::tag::Tag>| -> usize {
+            tags.iter()
+                .map(|tag| 2 * STRING + tag.k().len() + tag.v().len())
+                .sum()
+        };
+        let info_size = |user: &String| -> usize {
+            6 * NUMBER + STRING + user.len()
+        };
+
+        match element {
+            Element::Node { node } => {
+                3 * NUMBER + NUMBER + info_size(node.user()) + tags_size(node.tags())
+            }
+            Element::Way { way } => {
+                NUMBER + way.refs().len() * NUMBER + info_size(way.user()) + tags_size(way.tags())
+            }
+            Element::Relation { relation } => {
+                let members_size: usize = relation.members().iter()
+                    .map(|member| {
+                        match member {
+                            osm::model::relation::Member::Node { member } => {
+                                member.role().len()
+                            }
+                            osm::model::relation::Member::Way { member } => {
+                                member.role().len()
+                            }
+                            osm::model::relation::Member::Relation { member } => {
+                                member.role().len()
+                            }
+                        }
+                    })
+                    .map(|role_len| 2 * NUMBER + STRING + role_len)
+                    .sum();
+                NUMBER + members_size + info_size(relation.user()) + tags_size(relation.tags())
+            }
+            Element::Sentinel => {
+                0
+            }
+        }
+    }
 }
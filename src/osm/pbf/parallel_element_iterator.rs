@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::vec::IntoIter;
+
+use anyhow::Error;
+use command_executor::shutdown_mode::ShutdownMode;
+use command_executor::thread_pool::ThreadPool;
+use command_executor::thread_pool_builder::ThreadPoolBuilder;
+
+use crate::osm::model::element::Element;
+use crate::osm::pbf::blob_iterator::BlobIterator;
+use crate::osm::pbf::decode_file_block_command::DecodeFileBlockCommand;
+
+/// Blocks decoded by the decoding threads, keyed on the blob index, waiting to be consumed in
+/// file order
+pub(crate) struct DecodedBlocks {
+    blocks: Mutex<HashMap<usize, Result<Vec<Element>, Error>>>,
+    available: Condvar,
+}
+
+impl DecodedBlocks {
+    fn new() -> DecodedBlocks {
+        DecodedBlocks {
+            blocks: Mutex::new(HashMap::new()),
+            available: Condvar::new(),
+        }
+    }
+
+    pub(crate) fn add(&self, index: usize, block: Result<Vec<Element>, Error>) {
+        self.blocks.lock().unwrap().insert(index, block);
+        self.available.notify_all();
+    }
+
+    fn take(&self, index: usize) -> Result<Vec<Element>, Error> {
+        let mut blocks = self.blocks.lock().unwrap();
+        loop {
+            match blocks.remove(&index) {
+                None => {
+                    blocks = self.available.wait(blocks).unwrap();
+                }
+                Some(block) => {
+                    return block;
+                }
+            }
+        }
+    }
+}
+
+/// Iterate over elements in *.osm.pbf file in file order while decoding blobs concurrently
+///
+/// At most `capacity` blobs are decoded ahead of the element returned by the iterator, so the
+/// memory used by the iterator is bounded. The decoding threads are stopped when the iterator is
+/// dropped. Like [ElementIterator], the iterator panics if a blob fails to decode.
+///
+/// [ElementIterator]: crate::osm::pbf::element_iterator::ElementIterator
+pub struct ParallelElementIterator {
+    blob_iterator: BlobIterator,
+    blob_iterator_exhausted: bool,
+    decoded_blocks: Arc<DecodedBlocks>,
+    decoding_pool: ThreadPool,
+    capacity: usize,
+    submitted: usize,
+    next_to_yield: usize,
+    element_iterator: Option<IntoIter<Element>>,
+}
+
+impl ParallelElementIterator {
+    pub(crate) fn new(blob_iterator: BlobIterator, tasks: usize, capacity: usize) -> Result<ParallelElementIterator, Error> {
+        let decoding_pool = ThreadPoolBuilder::new()
+            .with_name_str("parallel-element-decoder")
+            .with_tasks(tasks)
+            .with_queue_size(capacity)
+            .with_shutdown_mode(ShutdownMode::Immediate)
+            .build()?;
+        Ok(
+            ParallelElementIterator {
+                blob_iterator,
+                blob_iterator_exhausted: false,
+                decoded_blocks: Arc::new(DecodedBlocks::new()),
+                decoding_pool,
+                capacity,
+                submitted: 0,
+                next_to_yield: 0,
+                element_iterator: None,
+            }
+        )
+    }
+
+    /// Keep up to `capacity` blobs in the decoding pipeline
+    fn submit(&mut self) {
+        while !self.blob_iterator_exhausted && self.submitted - self.next_to_yield < self.capacity {
+            match self.blob_iterator.next() {
+                None => {
+                    self.blob_iterator_exhausted = true;
+                }
+                Some(blob_desc) => {
+                    self.decoding_pool.submit(
+                        Box::new(DecodeFileBlockCommand::new(blob_desc, self.decoded_blocks.clone()))
+                    );
+                    self.submitted += 1;
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for ParallelElementIterator {
+    type Item = Element;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(element) = self.element_iterator.as_mut().and_then(|i| i.next()) {
+                return Some(element);
+            }
+            self.element_iterator = None;
+            self.submit();
+            if self.next_to_yield == self.submitted {
+                return None;
+            }
+            match self.decoded_blocks.take(self.next_to_yield) {
+                Ok(elements) => {
+                    self.element_iterator = Some(elements.into_iter());
+                }
+                Err(e) => {
+                    panic!("Failed to decode blob {}: {e:?}", self.next_to_yield);
+                }
+            }
+            self.next_to_yield += 1;
+        }
+    }
+}
+
+impl Drop for ParallelElementIterator {
+    fn drop(&mut self) {
+        self.decoding_pool.shutdown();
+        if let Err(e) = self.decoding_pool.join() {
+            log::warn!("Failed to stop the decoding threads: {e:?}");
+        }
+    }
+}
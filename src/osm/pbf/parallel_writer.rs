@@ -1,155 +1,245 @@
-use std::cell::RefCell;
-use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::ops::{AddAssign, Deref, DerefMut};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex, RwLock};
-use std::thread::LocalKey;
 
-use anyhow::{anyhow, Error};
+use anyhow::{anyhow, Context, Error};
 use command_executor::command::Command;
 use command_executor::shutdown_mode::ShutdownMode;
 use command_executor::thread_pool::ThreadPool;
 use command_executor::thread_pool_builder::ThreadPoolBuilder;
 
+use crate::osm::model::bounding_box::BoundingBox;
 use crate::osm::model::element::Element;
 use crate::osm::pbf::compression_type::CompressionType;
-use crate::osm::pbf::file_block::FileBlock;
+use crate::osm::pbf::file_block::{EncodedBlob, FileBlock};
 use crate::osm::pbf::file_info::FileInfo;
+use crate::osm::pbf::osm_data::OsmData;
+use crate::osm::pbf::parallel_writer_options::ParallelWriterOptions;
 use crate::osm::pbf::writer::Writer;
-
-thread_local! {
-    static ELEMENT_ORDERING_BUFFER: RefCell<VecDeque<Element>> = const { RefCell::new(VecDeque::new()) };
-    static ELEMENT_ORDERING_BUFFER_SIZE: RefCell<usize> = const { RefCell::new(0) };
-    static FILE_BLOCK_SIZE: RefCell<usize> = const { RefCell::new(0) };
-    static FILE_BLOCK_INDEX: RefCell<usize> = const { RefCell::new(1) };
-    static NEXT_THREAD_POOL: RefCell<Option<Arc<RwLock<ThreadPool>>>> = const { RefCell::new(None) };
-    static COMPRESSION_TYPE: RefCell<Option<CompressionType>> = const { RefCell::new(None) };
-    static CURRENT_MIN_ELEMENT: RefCell<Option<Element>> = const { RefCell::new(None) };
-
-    #[allow(clippy::type_complexity)]
-    pub static BLOB_ORDERING_BUFFER: RefCell<HashMap<usize, (Vec<u8>, Vec<u8>)>> = RefCell::new(HashMap::new());
-    // the first expected block is #1. #0 is the header
-    pub static NEXT_TO_WRITE: RefCell<usize> = const { RefCell::new(1) };
-    pub static PBF_WRITER: RefCell<Option<Writer>> = const { RefCell::new(None) };
-}
-
-fn flush_sorted_top() {
-    ELEMENT_ORDERING_BUFFER.with(|element_ordering_buffer| {
-        element_ordering_buffer.borrow_mut().make_contiguous().sort();
-        let elements = split_file_block(element_ordering_buffer);
-        set_current_min_element(elements.first());
-        NEXT_THREAD_POOL.with(|thread_pool| {
-            let thread_pool = thread_pool.borrow();
-            let thread_pool_guard = thread_pool.as_ref().unwrap().read().unwrap();
-            thread_pool_guard.submit(Box::new(EncodeFileBlockCommand::new(file_block_index(), Mutex::new(elements))));
-            inc_file_block_index();
-        })
-    });
+use crate::osm::pbf::writer_options::WriterOptions;
+
+/// State of the element ordering stage, accessed only from the element ordering thread
+struct ElementOrdering {
+    buffer: VecDeque<Element>,
+    buffer_size: usize,
+    file_block_size: usize,
+    max_block_size: usize,
+    file_block_index: usize,
+    current_min_element: Option<Element>,
 }
 
-fn flush_all_sorted() {
-    ELEMENT_ORDERING_BUFFER.with(|element_ordering_buffer| {
-        element_ordering_buffer.borrow_mut().make_contiguous().sort();
-        while !element_ordering_buffer.borrow().is_empty() {
-            let elements = split_file_block(element_ordering_buffer);
-            set_current_min_element(elements.first());
-            NEXT_THREAD_POOL.with(|thread_pool| {
-                let thread_pool = thread_pool.borrow();
-                let thread_pool_guard = thread_pool.as_ref().unwrap().read().unwrap();
-                thread_pool_guard.submit(Box::new(EncodeFileBlockCommand::new(file_block_index(), Mutex::new(elements))));
-                inc_file_block_index();
-            })
+impl ElementOrdering {
+    fn new(buffer_size: usize, file_block_size: usize, max_block_size: usize) -> ElementOrdering {
+        ElementOrdering {
+            buffer: VecDeque::new(),
+            buffer_size,
+            file_block_size,
+            max_block_size,
+            // the first data block is #1. #0 is the header
+            file_block_index: 1,
+            current_min_element: None,
         }
-    });
-}
+    }
 
-fn split_file_block(element_ordering_buffer: &RefCell<VecDeque<Element>>) -> Vec<Element> {
-    let mut elements = Vec::with_capacity(file_block_size());
-    for _i in 0..file_block_size() {
-        let element = element_ordering_buffer.borrow_mut().pop_front();
-        match element {
-            None => {
-                break;
-            }
-            Some(e) => {
-                if elements.is_empty() || Element::same_type(&e, &elements[0]) {
-                    elements.push(e);
-                } else {
-                    element_ordering_buffer.borrow_mut().push_front(e);
+    fn add(&mut self, element: Element) -> Result<(), Error> {
+        self.verify_order(&element)?;
+        self.buffer.push_back(element);
+        Ok(())
+    }
+
+    fn is_full(&self) -> bool {
+        self.buffer.len() > self.buffer_size
+    }
+
+    fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    fn sort(&mut self) {
+        self.buffer.make_contiguous().sort();
+    }
+
+    /// Take the next file block from the top of the sorted buffer
+    fn split_file_block(&mut self) -> (usize, Vec<Element>) {
+        let mut elements = Vec::with_capacity(self.file_block_size);
+        let mut estimated_size = 0;
+        for _i in 0..self.file_block_size {
+            let element = self.buffer.pop_front();
+            match element {
+                None => {
                     break;
                 }
+                Some(e) => {
+                    let element_size = OsmData::estimate_encoded_size(&e);
+                    if elements.is_empty()
+                        || (Element::same_type(&e, &elements[0]) && estimated_size + element_size <= self.max_block_size) {
+                        estimated_size += element_size;
+                        elements.push(e);
+                    } else {
+                        self.buffer.push_front(e);
+                        break;
+                    }
+                }
             }
         }
+        if let Some(e) = elements.first() {
+            self.current_min_element.replace(e.clone());
+        }
+        let index = self.file_block_index;
+        self.file_block_index += 1;
+        (index, elements)
     }
-    elements
-}
 
-fn element_ordering_buffer_size() -> usize {
-    ELEMENT_ORDERING_BUFFER_SIZE.with(|s| *s.borrow().deref())
+    fn verify_order(&self, element: &Element) -> Result<(), Error> {
+        if !element.is_sentinel() {
+            if let Some(current_min_element) = &self.current_min_element {
+                if element < current_min_element {
+                    return Err(
+                        anyhow!(
+                            "Element order, required by OSM PBF definition is lost. \
+                            Possible cause is that the length of the ordering buffer ({}) is too short \
+                            to for compensate for the loss of order caused by concurrent processing. \
+                            Recommended: reader_tasks * 8000 * n",
+                            self.buffer_size
+                        )
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
-fn file_block_size() -> usize {
-    FILE_BLOCK_SIZE.with(|s| *s.borrow().deref())
+/// State of the writing stage, accessed only from the writing thread
+///
+/// Each file block is encoded into one or more blobs, depending on its encoded size.
+struct BlobOrdering {
+    buffer: HashMap<usize, Vec<EncodedBlob>>,
+    // the first expected block is #1. #0 is the header
+    next_to_write: usize,
+    writer: Option<Writer>,
 }
 
-fn file_block_index() -> usize {
-    FILE_BLOCK_INDEX.with(|i| *i.borrow().deref())
-}
+impl BlobOrdering {
+    fn new() -> BlobOrdering {
+        BlobOrdering {
+            buffer: HashMap::new(),
+            next_to_write: 1,
+            writer: None,
+        }
+    }
 
-fn inc_file_block_index() {
-    FILE_BLOCK_INDEX.with(|i| i.borrow_mut().deref_mut().add_assign(1))
+    /// Write the blobs and any blobs that were waiting for them, in index order
+    fn add(&mut self, index: usize, blobs: Vec<EncodedBlob>, bounding_box: Option<BoundingBox>) -> Result<(), Error> {
+        self.buffer.insert(index, blobs);
+        let writer = self.writer.as_mut()
+            .ok_or(anyhow!("The header must be written before the elements"))?;
+        writer.merge_bounding_box(bounding_box);
+        while let Some(blobs) = self.buffer.remove(&self.next_to_write) {
+            for (header, body) in blobs {
+                writer.write_blob(header, body)
+                    .with_context(|| anyhow!("Failed to write blob #{}", self.next_to_write))?;
+            }
+            self.next_to_write += 1;
+        }
+        Ok(())
+    }
 }
 
-fn compression_type() -> CompressionType {
-    COMPRESSION_TYPE.with(|compression_type| compression_type.borrow().as_ref().unwrap().clone())
+/// State and thread pools shared by all stages of a single [ParallelWriter]
+///
+/// The first error that occurs in any of the stages is kept and reported to the caller. After an
+/// error the pipeline discards the data it receives.
+struct Pipeline {
+    element_ordering: Mutex<ElementOrdering>,
+    blob_ordering: Mutex<BlobOrdering>,
+    compression_type: CompressionType,
+    writer_options: WriterOptions,
+    encoding_pool: Arc<RwLock<ThreadPool>>,
+    writing_pool: Arc<RwLock<ThreadPool>>,
+    error: Mutex<Option<Error>>,
 }
 
-fn assert_order(element: &Element) {
-    if !element.is_sentinel() {
-        assert!(
-            compare_to_current_min_element(element).is_ge(),
-            "Element order, required by OSM PBF definition is lost. \
-                    Possible cause is that the length of the ordering buffer ({}) is too short \
-                    to for compensate for the loss of order caused by concurrent processing. \
-                    Recommended: reader_tasks * 8000 * n",
-            element_ordering_buffer_size()
-        );
+impl Pipeline {
+    fn add_elements(self: &Arc<Self>, elements: impl IntoIterator<Item=Element>) {
+        if self.failed() {
+            return;
+        }
+        let mut element_ordering = self.element_ordering.lock().unwrap();
+        for element in elements {
+            if let Err(e) = element_ordering.add(element) {
+                self.fail(e);
+                return;
+            }
+        }
+        if element_ordering.is_full() {
+            element_ordering.sort();
+            let (index, elements) = element_ordering.split_file_block();
+            self.encode(index, elements);
+        }
+    }
+
+    fn flush_all_sorted(self: &Arc<Self>) {
+        if self.failed() {
+            return;
+        }
+        let mut element_ordering = self.element_ordering.lock().unwrap();
+        element_ordering.sort();
+        while !element_ordering.is_empty() {
+            let (index, elements) = element_ordering.split_file_block();
+            self.encode(index, elements);
+        }
+    }
+
+    fn fail(&self, error: Error) {
+        let mut error_guard = self.error.lock().unwrap();
+        if error_guard.is_none() {
+            log::error!("Parallel writer failed: {error:?}");
+            error_guard.replace(error);
+        }
     }
-}
 
-fn compare_to_current_min_element(element: &Element) -> Ordering {
-    CURRENT_MIN_ELEMENT.with(|current_min_element|
-        match current_min_element.borrow().deref() {
+    fn failed(&self) -> bool {
+        self.error.lock().unwrap().is_some()
+    }
+
+    /// Report the first error that occurred in the pipeline, if any
+    fn check(&self) -> Result<(), Error> {
+        match self.error.lock().unwrap().as_ref() {
             None => {
-                Ordering::Greater
+                Ok(())
             }
             Some(e) => {
-                element.cmp(e)
+                Err(anyhow!("Parallel writer failed: {e:#}"))
             }
         }
-    )
-}
+    }
 
-fn set_current_min_element(element: Option<&Element>) {
-    CURRENT_MIN_ELEMENT.with(|current_min_element| {
-        match element {
-            None => {}
-            Some(e) => {
-                current_min_element.borrow_mut().replace(e.clone());
-            }
-        }
-    });
+    fn encode(self: &Arc<Self>, index: usize, elements: Vec<Element>) {
+        self.encoding_pool
+            .read()
+            .unwrap()
+            .submit(Box::new(EncodeFileBlockCommand::new(self.clone(), index, elements)));
+    }
+
+    fn write(self: &Arc<Self>, index: usize, blobs: Vec<EncodedBlob>, bounding_box: Option<BoundingBox>) {
+        self.writing_pool
+            .read()
+            .unwrap()
+            .submit(Box::new(WriteBlobCommand::new(self.clone(), index, blobs, bounding_box)));
+    }
 }
 
 struct AddElementCommand {
+    pipeline: Arc<Pipeline>,
     element: Mutex<Option<Element>>,
 }
 
 impl AddElementCommand {
-    fn new(element: Element) -> AddElementCommand {
+    fn new(pipeline: Arc<Pipeline>, element: Element) -> AddElementCommand {
         AddElementCommand {
+            pipeline,
             element: Mutex::new(Some(element)),
         }
     }
@@ -157,25 +247,21 @@ impl AddElementCommand {
 
 impl Command for AddElementCommand {
     fn execute(&self) -> Result<(), Error> {
-        ELEMENT_ORDERING_BUFFER.with(|element_ordering_buffer| {
-            let mut element_guard = self.element.lock().unwrap();
-            assert_order(element_guard.as_ref().unwrap());
-            element_ordering_buffer.borrow_mut().push_back(element_guard.take().unwrap());
-            if element_ordering_buffer.borrow().len() > element_ordering_buffer_size() {
-                flush_sorted_top()
-            }
-        });
+        let element = self.element.lock().unwrap().take();
+        self.pipeline.add_elements(element);
         Ok(())
     }
 }
 
 struct AddElementsCommand {
+    pipeline: Arc<Pipeline>,
     elements: Mutex<Option<Vec<Element>>>,
 }
 
 impl AddElementsCommand {
-    fn new(elements: Vec<Element>) -> AddElementsCommand {
+    fn new(pipeline: Arc<Pipeline>, elements: Vec<Element>) -> AddElementsCommand {
         AddElementsCommand {
+            pipeline,
             elements: Mutex::new(Some(elements)),
         }
     }
@@ -183,107 +269,78 @@ impl AddElementsCommand {
 
 impl Command for AddElementsCommand {
     fn execute(&self) -> Result<(), Error> {
-        ELEMENT_ORDERING_BUFFER.with(|element_ordering_buffer| {
-            let mut elements_guard = self.elements.lock().unwrap();
-            for element in elements_guard.take().unwrap() {
-                assert_order(&element);
-                element_ordering_buffer.borrow_mut().push_back(element);
-            }
-            if element_ordering_buffer.borrow().len() > element_ordering_buffer_size() {
-                flush_sorted_top();
-            }
-        });
+        let elements = self.elements.lock().unwrap().take().unwrap_or_default();
+        self.pipeline.add_elements(elements);
         Ok(())
     }
 }
 
 struct EncodeFileBlockCommand {
+    pipeline: Arc<Pipeline>,
     index: usize,
     elements: Mutex<Vec<Element>>,
 }
 
 impl EncodeFileBlockCommand {
-    fn new(index: usize, elements: Mutex<Vec<Element>>) -> EncodeFileBlockCommand {
+    fn new(pipeline: Arc<Pipeline>, index: usize, elements: Vec<Element>) -> EncodeFileBlockCommand {
         EncodeFileBlockCommand {
+            pipeline,
             index,
-            elements,
+            elements: Mutex::new(elements),
         }
     }
 }
 
 impl Command for EncodeFileBlockCommand {
     fn execute(&self) -> Result<(), Error> {
-        let mut elements_guard = self.elements.lock().unwrap();
-        let file_block = FileBlock::from_elements(self.index, std::mem::take(&mut elements_guard));
-        let (blob_header, blob_body) = FileBlock::serialize(&file_block, compression_type())?;
-        NEXT_THREAD_POOL.with(|thread_pool| {
-            let thread_pool = thread_pool.borrow();
-            let thread_pool_guard = thread_pool.as_ref().unwrap().read().unwrap();
-            thread_pool_guard.submit(
-                Box::new(
-                    WriteBlobCommand::new(
-                        self.index, Mutex::new(blob_header), Mutex::new(blob_body),
-                    )
-                )
-            );
-        });
+        if self.pipeline.failed() {
+            return Ok(());
+        }
+        let elements = std::mem::take(&mut *self.elements.lock().unwrap());
+        let mut bounding_box = None;
+        if self.pipeline.writer_options.auto_bounding_box() {
+            bounding_box = OsmData::nodes_bounding_box(&elements);
+        }
+        match FileBlock::serialize_elements(elements, self.pipeline.compression_type.clone(), &self.pipeline.writer_options) {
+            Ok(blobs) => {
+                self.pipeline.write(self.index, blobs, bounding_box);
+            }
+            Err(e) => {
+                self.pipeline.fail(e.context(format!("Failed to encode block #{}", self.index)));
+            }
+        }
         Ok(())
     }
 }
 
 struct WriteBlobCommand {
+    pipeline: Arc<Pipeline>,
     index: usize,
-    blob_header: Mutex<Vec<u8>>,
-    blob_body: Mutex<Vec<u8>>,
+    blobs: Mutex<Vec<EncodedBlob>>,
+    bounding_box: Option<BoundingBox>,
 }
 
 impl WriteBlobCommand {
-    fn new(index: usize, blob_header: Mutex<Vec<u8>>, blob_body: Mutex<Vec<u8>>) -> WriteBlobCommand {
+    fn new(pipeline: Arc<Pipeline>, index: usize, blobs: Vec<EncodedBlob>, bounding_box: Option<BoundingBox>) -> WriteBlobCommand {
         WriteBlobCommand {
+            pipeline,
             index,
-            blob_header,
-            blob_body,
+            blobs: Mutex::new(blobs),
+            bounding_box,
         }
     }
 }
 
 impl Command for WriteBlobCommand {
     fn execute(&self) -> Result<(), Error> {
-        BLOB_ORDERING_BUFFER.with(
-            |buffer| {
-                let mut blob_header_guard = self.blob_header.lock().unwrap();
-                let blob_header = std::mem::take(blob_header_guard.deref_mut());
-                let mut blob_body_guard = self.blob_body.lock().unwrap();
-                let blob_body = std::mem::take(blob_body_guard.deref_mut());
-                buffer
-                    .borrow_mut()
-                    .insert(self.index, (blob_header, blob_body));
-            }
-        );
-
-        BLOB_ORDERING_BUFFER.with(
-            |buffer| {
-                NEXT_TO_WRITE.with(|next| {
-                    let next_to_write = *next.borrow();
-                    for i in next_to_write..usize::MAX {
-                        match buffer.borrow_mut().remove(&i) {
-                            None => {
-                                *next.borrow_mut() = i;
-                                break;
-                            }
-                            Some((header, body)) => {
-                                PBF_WRITER.with(
-                                    |writer| {
-                                        writer.borrow_mut().as_mut().unwrap().write_blob(header, body).expect("Failed to write a blob");
-                                    }
-                                );
-                            }
-                        }
-                    }
-                });
-            }
-        );
-
+        if self.pipeline.failed() {
+            return Ok(());
+        }
+        let blobs = std::mem::take(&mut *self.blobs.lock().unwrap());
+        let result = self.pipeline.blob_ordering.lock().unwrap().add(self.index, blobs, self.bounding_box.clone());
+        if let Err(e) = result {
+            self.pipeline.fail(e);
+        }
         Ok(())
     }
 }
@@ -299,14 +356,18 @@ impl Command for WriteBlobCommand {
 /// `element_ordering_buffer_size` parameter to constructor. It is limited to use cases where the
 /// processing of each element takes roughly the same time, as in simple filtering tasks or that
 /// elements were ordered before calling the writer.
+/// Each [ParallelWriter] owns its thread pools and pipeline state, so multiple writers can be used
+/// at the same time, for example to split a single input into several output files.
+/// Errors that occur while encoding or writing in the pipeline threads are returned by the next
+/// call to `write_element`, `write_elements` or `close`. The pipeline is configured with
+/// [ParallelWriterOptions].
 /// For example please see ./examples/parallel-bf-io.rs
 pub struct ParallelWriter {
     path: PathBuf,
     file_info: FileInfo,
     compression_type: CompressionType,
+    pipeline: Arc<Pipeline>,
     element_ordering_pool: Arc<RwLock<ThreadPool>>,
-    encoding_pool: Arc<RwLock<ThreadPool>>,
-    writing_pool: Arc<RwLock<ThreadPool>>,
 }
 
 impl ParallelWriter {
@@ -318,24 +379,69 @@ impl ParallelWriter {
         file_info: FileInfo,
         compression_type: CompressionType,
     ) -> Result<ParallelWriter, Error> {
-        let element_ordering_pool = Self::create_thread_pool("element-ordering", 1, 256)?;
-        let encoding_pool = Self::create_thread_pool("encoding", 4, 256)?;
-        let writing_pool = Self::create_thread_pool("writing", 1, 256)?;
+        let mut options = ParallelWriterOptions::default();
+        options.with_element_ordering_buffer_size(element_ordering_buffer_size);
+        options.with_file_block_size(file_block_size);
+        Self::from_options(path, file_info, compression_type, options)
+    }
 
-        Self::set_thread_local(element_ordering_pool.clone(), &ELEMENT_ORDERING_BUFFER_SIZE, element_ordering_buffer_size);
-        Self::set_thread_local(element_ordering_pool.clone(), &FILE_BLOCK_SIZE, file_block_size);
-        Self::set_thread_local(encoding_pool.clone(), &COMPRESSION_TYPE, Some(compression_type.clone()));
-        Self::set_thread_local(element_ordering_pool.clone(), &NEXT_THREAD_POOL, Some(encoding_pool.clone()));
-        Self::set_thread_local(encoding_pool.clone(), &NEXT_THREAD_POOL, Some(writing_pool.clone()));
+    /// Create [ParallelWriter] from [FileInfo] and [ParallelWriterOptions]
+    pub fn from_options(
+        path: PathBuf,
+        file_info: FileInfo,
+        compression_type: CompressionType,
+        options: ParallelWriterOptions,
+    ) -> Result<ParallelWriter, Error> {
+        if options.file_block_size() == 0 || options.encoding_tasks() == 0 {
+            return Err(anyhow!("File block size and the number of encoding tasks must be positive"));
+        }
+        if options.element_ordering_queue_size() == 0
+            || options.encoding_queue_size() == 0
+            || options.writing_queue_size() == 0 {
+            return Err(anyhow!("Queue sizes must be positive"));
+        }
+        options.writer_options().validate()?;
+        let element_ordering_pool = Self::create_thread_pool(
+            "element-ordering",
+            1,
+            options.element_ordering_queue_size(),
+        )?;
+        let encoding_pool = Self::create_thread_pool(
+            "encoding",
+            options.encoding_tasks(),
+            options.encoding_queue_size(),
+        )?;
+        let writing_pool = Self::create_thread_pool(
+            "writing",
+            1,
+            options.writing_queue_size(),
+        )?;
+
+        let pipeline = Arc::new(
+            Pipeline {
+                element_ordering: Mutex::new(
+                    ElementOrdering::new(
+                        options.element_ordering_buffer_size(),
+                        options.file_block_size(),
+                        options.writer_options().max_block_size(),
+                    )
+                ),
+                blob_ordering: Mutex::new(BlobOrdering::new()),
+                compression_type: compression_type.clone(),
+                writer_options: options.writer_options().clone(),
+                encoding_pool,
+                writing_pool,
+                error: Mutex::new(None),
+            }
+        );
 
         Ok(
             ParallelWriter {
                 path,
                 file_info,
                 compression_type,
+                pipeline,
                 element_ordering_pool,
-                encoding_pool,
-                writing_pool,
             }
         )
     }
@@ -344,64 +450,74 @@ impl ParallelWriter {
     ///
     /// Must be called before writing the first element.
     pub fn write_header(&mut self) -> Result<(), Error> {
-        let writing_pool_guard = self.writing_pool.read()
+        let mut blob_ordering = self.pipeline.blob_ordering.lock()
             .map_err(|e| anyhow!("{}", e))?;
-        let path = self.path.clone();
-        let file_info = self.file_info.clone();
-        let compression_type = self.compression_type.clone();
-        writing_pool_guard.in_all_threads(
-            Arc::new(move || {
-                PBF_WRITER.with(|writer| {
-                    if writer.borrow().is_none() {
-                        let mut w = Writer::from_file_info(
-                            path.clone(),
-                            file_info.clone(),
-                            compression_type.clone(),
-                        ).unwrap();
-                        w.write_header().unwrap();
-                        writer.replace(Some(w));
-                    }
-                })
-            })
-        );
+        if blob_ordering.writer.is_none() {
+            let mut writer = Writer::from_options(
+                self.path.clone(),
+                self.file_info.clone(),
+                self.compression_type.clone(),
+                self.pipeline.writer_options.clone(),
+            )?;
+            writer.write_header()?;
+            blob_ordering.writer.replace(writer);
+        }
         Ok(())
     }
 
     /// Write an [Element]
+    ///
+    /// Blocks when the pipeline queues are full. Returns an error if any of the previously written
+    /// elements failed.
     pub fn write_element(&mut self, element: Element) -> Result<(), Error> {
+        self.pipeline.check()?;
         self.element_ordering_pool
             .read()
             .unwrap()
-            .submit(Box::new(AddElementCommand::new(element)));
+            .submit(Box::new(AddElementCommand::new(self.pipeline.clone(), element)));
         Ok(())
     }
 
     /// Write list of [Element]s
+    ///
+    /// Blocks when the pipeline queues are full. Returns an error if any of the previously written
+    /// elements failed.
     pub fn write_elements(&mut self, elements: Vec<Element>) -> Result<(), Error> {
+        self.pipeline.check()?;
         self.element_ordering_pool
             .read()
             .unwrap()
-            .submit(Box::new(AddElementsCommand::new(elements)));
+            .submit(Box::new(AddElementsCommand::new(self.pipeline.clone(), elements)));
         Ok(())
     }
 
     /// Flush internal buffers.
+    ///
+    /// Returns the first error that occurred in the pipeline, if any.
     pub fn close(&mut self) -> Result<(), Error> {
         self.flush_element_ordering();
         Self::shutdown(self.element_ordering_pool.clone())?;
-        Self::shutdown(self.encoding_pool.clone())?;
-        self.flush_writing();
-        Self::shutdown(self.writing_pool.clone())?;
-        Ok(())
+        Self::shutdown(self.pipeline.encoding_pool.clone())?;
+        Self::shutdown(self.pipeline.writing_pool.clone())?;
+        self.pipeline.check()?;
+        let mut blob_ordering = self.pipeline.blob_ordering.lock()
+            .map_err(|e| anyhow!("{}", e))?;
+        match blob_ordering.writer.as_mut() {
+            None => {
+                Ok(())
+            }
+            Some(writer) => {
+                writer.close()
+            }
+        }
     }
 
     fn flush_element_ordering(&self) {
+        let pipeline = self.pipeline.clone();
         let element_ordering_pool_guard = self.element_ordering_pool.read().unwrap();
-        element_ordering_pool_guard.in_all_threads(Arc::new(flush_all_sorted))
+        element_ordering_pool_guard.in_all_threads(Arc::new(move || pipeline.flush_all_sorted()))
     }
 
-    fn flush_writing(&self) {}
-
     fn create_thread_pool(name: &str, tasks: usize, queue_size: usize) -> Result<Arc<RwLock<ThreadPool>>, Error> {
         Ok(
             Arc::new(
@@ -417,14 +533,6 @@ impl ParallelWriter {
         )
     }
 
-    fn set_thread_local<T>(thread_pool: Arc<RwLock<ThreadPool>>, local_key: &'static LocalKey<RefCell<T>>, val: T)
-        where T: Sync + Send + Clone {
-        thread_pool
-            .read()
-            .unwrap()
-            .set_thread_local(local_key, val);
-    }
-
     fn shutdown(thread_pool: Arc<RwLock<ThreadPool>>) -> Result<(), Error> {
         let mut thread_pool = thread_pool
             .write()
@@ -432,4 +540,4 @@ impl ParallelWriter {
         thread_pool.shutdown();
         thread_pool.join()
     }
-}
\ No newline at end of file
+}
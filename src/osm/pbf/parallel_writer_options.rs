@@ -0,0 +1,115 @@
+use crate::osm::pbf::writer_options::WriterOptions;
+
+/// Configuration of the [ParallelWriter] pipeline
+///
+/// The queues between the pipeline stages are bounded. When a stage cannot keep up, for example
+/// when the disk is slow, its queue fills up and the previous stage blocks until there is room.
+/// Eventually the calls to `write_element` and `write_elements` block, so the memory used by the
+/// pipeline is bounded by the queue sizes and the element ordering buffer.
+///
+/// Example:
+/// ```
+/// use osm_io::osm::pbf::parallel_writer_options::ParallelWriterOptions;
+/// let mut options = ParallelWriterOptions::default();
+/// options.with_encoding_tasks(8);
+/// options.with_writing_queue_size(64);
+/// ```
+///
+/// [ParallelWriter]: crate::osm::pbf::parallel_writer::ParallelWriter
+#[derive(Debug, Clone)]
+pub struct ParallelWriterOptions {
+    element_ordering_buffer_size: usize,
+    file_block_size: usize,
+    encoding_tasks: usize,
+    element_ordering_queue_size: usize,
+    encoding_queue_size: usize,
+    writing_queue_size: usize,
+    writer_options: WriterOptions,
+}
+
+impl ParallelWriterOptions {
+    /// Get the number of elements buffered to restore the order of the input
+    pub fn element_ordering_buffer_size(&self) -> usize {
+        self.element_ordering_buffer_size
+    }
+
+    /// Set the number of elements buffered to restore the order of the input.
+    /// Recommended: reader_tasks * 8000 * n
+    pub fn with_element_ordering_buffer_size(&mut self, element_ordering_buffer_size: usize) {
+        self.element_ordering_buffer_size = element_ordering_buffer_size;
+    }
+
+    /// Get the maximal number of elements in a file block
+    pub fn file_block_size(&self) -> usize {
+        self.file_block_size
+    }
+
+    /// Set the maximal number of elements in a file block
+    pub fn with_file_block_size(&mut self, file_block_size: usize) {
+        self.file_block_size = file_block_size;
+    }
+
+    /// Get the number of encoding threads
+    pub fn encoding_tasks(&self) -> usize {
+        self.encoding_tasks
+    }
+
+    /// Set the number of encoding threads
+    pub fn with_encoding_tasks(&mut self, encoding_tasks: usize) {
+        self.encoding_tasks = encoding_tasks;
+    }
+
+    /// Get the capacity of the queue in front of the element ordering thread
+    pub fn element_ordering_queue_size(&self) -> usize {
+        self.element_ordering_queue_size
+    }
+
+    /// Set the capacity of the queue in front of the element ordering thread
+    pub fn with_element_ordering_queue_size(&mut self, element_ordering_queue_size: usize) {
+        self.element_ordering_queue_size = element_ordering_queue_size;
+    }
+
+    /// Get the capacity of the queue in front of the encoding threads
+    pub fn encoding_queue_size(&self) -> usize {
+        self.encoding_queue_size
+    }
+
+    /// Set the capacity of the queue in front of the encoding threads
+    pub fn with_encoding_queue_size(&mut self, encoding_queue_size: usize) {
+        self.encoding_queue_size = encoding_queue_size;
+    }
+
+    /// Get the capacity of the queue in front of the writing thread
+    pub fn writing_queue_size(&self) -> usize {
+        self.writing_queue_size
+    }
+
+    /// Set the capacity of the queue in front of the writing thread
+    pub fn with_writing_queue_size(&mut self, writing_queue_size: usize) {
+        self.writing_queue_size = writing_queue_size;
+    }
+
+    /// Get the encoding options of the output file
+    pub fn writer_options(&self) -> &WriterOptions {
+        &self.writer_options
+    }
+
+    /// Set the encoding options of the output file
+    pub fn with_writer_options(&mut self, writer_options: WriterOptions) {
+        self.writer_options = writer_options;
+    }
+}
+
+impl Default for ParallelWriterOptions {
+    fn default() -> Self {
+        ParallelWriterOptions {
+            element_ordering_buffer_size: 4 * 8000 * 32,
+            file_block_size: 8000,
+            encoding_tasks: 4,
+            element_ordering_queue_size: 256,
+            encoding_queue_size: 256,
+            writing_queue_size: 256,
+            writer_options: WriterOptions::default(),
+        }
+    }
+}
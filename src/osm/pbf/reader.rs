@@ -1,17 +1,27 @@
 use std::collections::HashSet;
+use std::fs::File;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 
-use anyhow::anyhow;
+use anyhow::{anyhow, Context};
 use command_executor::shutdown_mode::ShutdownMode;
 use command_executor::thread_pool_builder::ThreadPoolBuilder;
+use memmap2::Mmap;
 
 use crate::osm::model::element::Element;
+use crate::osm::model::element_kind::ElementKind;
+use crate::osm::model::node::Node;
+use crate::osm::model::relation::Relation;
+use crate::osm::model::way::Way;
 use crate::osm::pbf::blob_iterator::BlobIterator;
+use crate::osm::pbf::block_view_iterator::BlockViewIterator;
 use crate::osm::pbf::element_iterator::ElementIterator;
+use crate::osm::pbf::element_kind_iterator::ElementKindIterator;
+use crate::osm::pbf::element_statistics::ElementStatistics;
 use crate::osm::pbf::file_block_iterator::FileBlockIterator;
 use crate::osm::pbf::file_info::FileInfo;
+use crate::osm::pbf::map_reduce_command::MapReduceCommand;
+use crate::osm::pbf::parallel_element_iterator::ParallelElementIterator;
 use crate::osm::pbf::parallel_element_iteration_command::ParallelElementIterationCommand;
 
 #[derive(Debug, Clone)]
@@ -19,6 +29,8 @@ pub struct Reader {
     supported_features: Vec<String>,
     path: PathBuf,
     info: FileInfo,
+    skip_metadata: bool,
+    mmap: Option<Arc<Mmap>>,
 }
 
 /// *.osm.pbf file reader
@@ -37,6 +49,35 @@ impl Reader {
     /// let reader = Reader::new(&input_path);
     /// ```
     pub fn new(path: &Path) -> Result<Reader, anyhow::Error> {
+        Self::open(path, None)
+    }
+
+    /// Create a new Reader that reads the file through a memory map
+    ///
+    /// The blobs are decoded straight from the mapped file, which avoids opening, seeking and
+    /// copying the file for every blob. The map is shared by all iterators created by this reader,
+    /// including the parallel ones.
+    ///
+    /// * path - a path to a valid *.osm.pbf file. The file must not be modified while the reader
+    ///   or any of its iterators exist.
+    ///
+    ///   Example:
+    /// ```
+    /// use std::path::PathBuf;
+    /// use osm_io::osm::pbf::reader::Reader;
+    /// let input_path = PathBuf::from("./planet.osm.pbf");
+    /// let reader = Reader::new_mmap(&input_path);
+    /// ```
+    pub fn new_mmap(path: &Path) -> Result<Reader, anyhow::Error> {
+        let file = File::open(path)
+            .with_context(|| anyhow!("path: {}", path.display()))?;
+        // the file is expected to stay unchanged while mapped, as documented above
+        let mmap = unsafe { Mmap::map(&file) }
+            .with_context(|| anyhow!("Failed to map {}", path.display()))?;
+        Self::open(path, Some(Arc::new(mmap)))
+    }
+
+    fn open(path: &Path, mmap: Option<Arc<Mmap>>) -> Result<Reader, anyhow::Error> {
         let supported_features = vec![
             "OsmSchema-V0.6".to_string(),
             "DenseNodes".to_string(),
@@ -48,6 +89,8 @@ impl Reader {
             supported_features,
             path: path.to_path_buf(),
             info: Default::default(),
+            skip_metadata: false,
+            mmap,
         };
         let mut block_iterator = reader.clone().blocks()?;
         let file_block = block_iterator.next().ok_or(
@@ -66,8 +109,22 @@ impl Reader {
         )
     }
 
+    /// Skip decoding of the element metadata
+    ///
+    /// When set, the version, timestamp, changeset, uid and user of the elements returned by the
+    /// reader have default values, for example version 0 and an empty user. The visibility flag is
+    /// always decoded.
+    pub fn with_skip_metadata(&mut self, skip_metadata: bool) {
+        self.skip_metadata = skip_metadata;
+    }
+
+    /// True if decoding of the element metadata is skipped
+    pub fn skip_metadata(&self) -> bool {
+        self.skip_metadata
+    }
+
     pub(crate) fn blobs(&self) -> Result<BlobIterator, anyhow::Error> {
-        BlobIterator::new(self.path.clone())
+        BlobIterator::new(self.path.clone(), self.skip_metadata, self.mmap.clone())
     }
 
     /// Low level [FileBlockIterator] used to access the sequence of underlying PBF blocks
@@ -134,6 +191,115 @@ impl Reader {
         }
     }
 
+    /// Iterator used to iterate over data blocks as borrowed element views.
+    ///
+    /// A lower level alternative to [Reader::elements] for high throughput filters that avoids
+    /// building owned strings and elements. See [BlockView](crate::osm::pbf::block_view::BlockView)
+    pub fn block_views(&self) -> Result<BlockViewIterator, anyhow::Error> {
+        Ok(
+            BlockViewIterator::new(self.blobs()?)
+        )
+    }
+
+    /// Iterator used to iterate over elements of the requested kinds.
+    ///
+    /// Primitive groups of other kinds are not decoded. On files sorted by type then id the blocks
+    /// outside the requested kinds are skipped without reading them.
+    /// Example:
+    /// ```
+    /// use std::path::PathBuf;
+    /// use osm_io::osm::model::element_kind::ElementKind;
+    /// use osm_io::osm::pbf;
+    /// fn example() -> Result<(), anyhow::Error> {
+    ///     let input_path = PathBuf::from("./tests/fixtures/malta-230109.osm.pbf");
+    ///     let reader = pbf::reader::Reader::new(&input_path)?;
+    ///     let ways_and_relations = reader.elements_of(&[ElementKind::Way, ElementKind::Relation])?.count();
+    ///     println!("ways and relations: {}", ways_and_relations);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn elements_of(&self, kinds: &[ElementKind]) -> Result<ElementKindIterator, anyhow::Error> {
+        ElementKindIterator::new(self.blobs()?, kinds, self.info.optional("Sort.Type_then_ID"))
+    }
+
+    /// Iterator used to iterate over nodes. See [Reader::elements_of]
+    pub fn nodes(&self) -> Result<impl Iterator<Item=Node>, anyhow::Error> {
+        Ok(
+            self.elements_of(&[ElementKind::Node])?
+                .filter_map(|element| {
+                    match element {
+                        Element::Node { node } => Some(node),
+                        _ => None,
+                    }
+                })
+        )
+    }
+
+    /// Iterator used to iterate over ways. See [Reader::elements_of]
+    /// Example:
+    /// ```
+    /// use std::path::PathBuf;
+    /// use osm_io::osm::pbf;
+    /// fn example() -> Result<(), anyhow::Error> {
+    ///     let input_path = PathBuf::from("./tests/fixtures/malta-230109.osm.pbf");
+    ///     let reader = pbf::reader::Reader::new(&input_path)?;
+    ///     let refs: usize = reader.ways()?.map(|way| way.refs().len()).sum();
+    ///     println!("way node references: {}", refs);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn ways(&self) -> Result<impl Iterator<Item=Way>, anyhow::Error> {
+        Ok(
+            self.elements_of(&[ElementKind::Way])?
+                .filter_map(|element| {
+                    match element {
+                        Element::Way { way } => Some(way),
+                        _ => None,
+                    }
+                })
+        )
+    }
+
+    /// Iterator used to iterate over relations. See [Reader::elements_of]
+    pub fn relations(&self) -> Result<impl Iterator<Item=Relation>, anyhow::Error> {
+        Ok(
+            self.elements_of(&[ElementKind::Relation])?
+                .filter_map(|element| {
+                    match element {
+                        Element::Relation { relation } => Some(relation),
+                        _ => None,
+                    }
+                })
+        )
+    }
+
+    /// Iterator used to iterate over elements in file order while decoding with multiple threads.
+    ///
+    /// * tasks - the number of decoding threads
+    ///
+    /// Yields the same sequence of elements as [Reader::elements], but decodes up to
+    /// `4 * tasks` blobs ahead of the consumer.
+    /// Example:
+    /// ```
+    /// use std::path::PathBuf;
+    /// use osm_io::osm::pbf;
+    /// fn example() -> Result<(), anyhow::Error> {
+    ///     let input_path = PathBuf::from("./tests/fixtures/malta-230109.osm.pbf");
+    ///     let reader = pbf::reader::Reader::new(&input_path)?;
+    ///     let nodes = reader.par_elements(4)?
+    ///         .filter(|element| element.is_node())
+    ///         .count();
+    ///     println!("nodes: {}", nodes);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn par_elements(&self, tasks: usize) -> Result<ParallelElementIterator, anyhow::Error> {
+        if tasks == 0 {
+            return Err(anyhow!("The number of decoding tasks must be positive"));
+        }
+        ParallelElementIterator::new(self.blobs()?, tasks, 4 * tasks)
+    }
+
     /// Parallel iteration over elements in a *.osm.pbf file
     ///
     /// Note that because of the parallel access the order of elements enforced by *.osm.pbf format
@@ -203,6 +369,116 @@ impl Reader {
         Ok(())
     }
 
+    /// Parallel map/reduce over the blocks of a *.osm.pbf file
+    ///
+    /// * tasks - the number of threads
+    /// * map - called with the elements of each data block, in parallel
+    /// * reduce - combines the results of two blocks. The blocks are reduced in no particular
+    ///   order, so `reduce` must be associative and commutative
+    ///
+    /// Returns None for a file without data blocks. Fails with the first error returned by `map`.
+    /// Example:
+    /// ```
+    /// use std::path::PathBuf;
+    /// use osm_io::osm::pbf;
+    /// fn example() -> Result<(), anyhow::Error> {
+    ///     let input_path = PathBuf::from("./tests/fixtures/malta-230109.osm.pbf");
+    ///     let reader = pbf::reader::Reader::new(&input_path)?;
+    ///     let max_ways = reader.map_reduce(
+    ///         4,
+    ///         |elements| Ok(elements.iter().filter(|e| e.is_way()).count()),
+    ///         |a, b| a.max(b),
+    ///     )?;
+    ///     println!("max ways in a block: {:?}", max_ways);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn map_reduce<T: Send + 'static>(
+        &self,
+        tasks: usize,
+        map: impl Fn(Vec<Element>) -> Result<T, anyhow::Error> + Send + Sync + 'static,
+        reduce: impl Fn(T, T) -> T + Send + Sync + 'static,
+    ) -> Result<Option<T>, anyhow::Error> {
+        let mut map_reduce_pool = ThreadPoolBuilder::new()
+            .with_tasks(tasks)
+            .with_queue_size(1024)
+            .with_shutdown_mode(ShutdownMode::CompletePending)
+            .with_name_str("map-reduce")
+            .build()?;
+
+        let map: Arc<dyn Fn(Vec<Element>) -> Result<T, anyhow::Error> + Send + Sync> = Arc::new(map);
+        let reduce: Arc<dyn Fn(T, T) -> T + Send + Sync> = Arc::new(reduce);
+        let result = Arc::new(Mutex::new(None));
+        let error = Arc::new(Mutex::new(None));
+        for blob_desc in self.blobs()? {
+            map_reduce_pool.submit(
+                Box::new(
+                    MapReduceCommand::new(blob_desc, map.clone(), reduce.clone(), result.clone(), error.clone())
+                )
+            );
+        }
+
+        map_reduce_pool.shutdown();
+        map_reduce_pool.join()?;
+        let mut error_guard = error.lock().unwrap();
+        match error_guard.take() {
+            None => {
+                let mut result_guard = result.lock().unwrap();
+                Ok(result_guard.take())
+            }
+            Some(e) => {
+                Err(e)
+            }
+        }
+    }
+
+    /// Parallel fold over the elements of a *.osm.pbf file
+    ///
+    /// Each data block is folded into a local accumulator starting from `identity()`, and the
+    /// accumulators of the blocks are combined with `merge`, which must be associative and
+    /// commutative.
+    /// Example:
+    /// ```
+    /// use std::path::PathBuf;
+    /// use osm_io::osm::model::element::Element;
+    /// use osm_io::osm::pbf;
+    /// fn example() -> Result<(), anyhow::Error> {
+    ///     let input_path = PathBuf::from("./tests/fixtures/malta-230109.osm.pbf");
+    ///     let reader = pbf::reader::Reader::new(&input_path)?;
+    ///     let trees = reader.par_fold(
+    ///         4,
+    ///         || 0usize,
+    ///         |count, element| {
+    ///             match element {
+    ///                 Element::Node { node } => {
+    ///                     count + node.tags().iter().filter(|t| t.k() == "natural" && t.v() == "tree").count()
+    ///                 }
+    ///                 _ => count
+    ///             }
+    ///         },
+    ///         |a, b| a + b,
+    ///     )?;
+    ///     println!("trees: {}", trees);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn par_fold<A: Send + 'static>(
+        &self,
+        tasks: usize,
+        identity: impl Fn() -> A + Send + Sync + 'static,
+        fold: impl Fn(A, Element) -> A + Send + Sync + 'static,
+        merge: impl Fn(A, A) -> A + Send + Sync + 'static,
+    ) -> Result<A, anyhow::Error> {
+        let identity = Arc::new(identity);
+        let identity_clone = identity.clone();
+        let result = self.map_reduce(
+            tasks,
+            move |elements| Ok(elements.into_iter().fold(identity_clone(), &fold)),
+            merge,
+        )?;
+        Ok(result.unwrap_or_else(|| identity()))
+    }
+
     fn find_missing_features(supported_features: &[String], required_features: &[String]) -> Vec<String> {
         let supported: HashSet<&String> = supported_features.iter().collect::<HashSet<&String>>();
         let required: HashSet<&String> = required_features.iter().collect::<HashSet<&String>>();
@@ -235,38 +511,62 @@ impl Reader {
         &self.info
     }
 
+    /// Count the nodes, ways and relations in this file
     pub fn count_objects(&self) -> Result<(i64, i64, i64), anyhow::Error> {
-        let nodes = Arc::new(AtomicUsize::new(0));
-        let ways = Arc::new(AtomicUsize::new(0));
-        let relations = Arc::new(AtomicUsize::new(0));
-
-        let nodes_clone = nodes.clone();
-        let ways_clone = ways.clone();
-        let relations_clone = relations.clone();
-
-        self.parallel_for_each(num_cpus::get(), move |element| {
-            match element {
-                Element::Node { node: _ } => {
-                    nodes.fetch_add(1, Ordering::SeqCst);
+        self.par_fold(
+            num_cpus::get(),
+            || (0_i64, 0_i64, 0_i64),
+            |(nodes, ways, relations), element| {
+                match element {
+                    Element::Node { .. } => {
+                        (nodes + 1, ways, relations)
+                    }
+                    Element::Way { .. } => {
+                        (nodes, ways + 1, relations)
+                    }
+                    Element::Relation { .. } => {
+                        (nodes, ways, relations + 1)
+                    }
+                    Element::Sentinel => {
+                        (nodes, ways, relations)
+                    }
                 }
-                Element::Way { .. } => {
-                    ways.fetch_add(1, Ordering::SeqCst);
-                }
-                Element::Relation { .. } => {
-                    relations.fetch_add(1, Ordering::SeqCst);
-                }
-                Element::Sentinel => {}
-            }
-            Ok(())
-        },
-        )?;
+            },
+            |a, b| (a.0 + b.0, a.1 + b.1, a.2 + b.2),
+        )
+    }
 
-        Ok(
-            (
-                nodes_clone.load(Ordering::SeqCst) as i64,
-                ways_clone.load(Ordering::SeqCst) as i64,
-                relations_clone.load(Ordering::SeqCst) as i64
-            )
+    /// Calculate [ElementStatistics] for this file
+    ///
+    /// The statistics are calculated for each block in parallel and merged.
+    ///
+    /// * tasks - the number of threads
+    /// * top_n - the number of most frequent tag keys and key=value pairs to report
+    ///
+    /// Example:
+    /// ```
+    /// use std::path::PathBuf;
+    /// use osm_io::osm::pbf;
+    /// fn example() -> Result<(), anyhow::Error> {
+    ///     let input_path = PathBuf::from("./tests/fixtures/malta-230109.osm.pbf");
+    ///     let reader = pbf::reader::Reader::new(&input_path)?;
+    ///     let statistics = reader.statistics(4, 10)?;
+    ///     println!("{}", statistics.to_json().pretty(4));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn statistics(&self, tasks: usize, top_n: usize) -> Result<ElementStatistics, anyhow::Error> {
+        self.par_fold(
+            tasks,
+            move || ElementStatistics::new(top_n),
+            |mut statistics, element| {
+                statistics.add(&element);
+                statistics
+            },
+            |mut a, b| {
+                a.merge(b);
+                a
+            },
         )
     }
 }
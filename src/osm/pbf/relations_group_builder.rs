@@ -1,30 +1,34 @@
 use crate::osm::model::relation::{Member, Relation};
+use crate::osm::pbf::metadata_fields::MetadataFields;
 use crate::osm::pbf::string_table_builder::StringTableBuilder;
+use crate::osm::pbf::writer_options::WriterOptions;
 use crate::osmpbf;
 use crate::osmpbf::PrimitiveGroup;
 
 pub(crate) struct RelationsGroupBuilder {
     relations: Option<Vec<osmpbf::Relation>>,
     date_granularity: i32,
+    metadata_fields: MetadataFields,
 }
 
 impl RelationsGroupBuilder {
-    pub(crate) fn new(date_granularity: i32, relation: &Relation, string_table_builder: &mut StringTableBuilder) -> RelationsGroupBuilder {
+    pub(crate) fn new(options: &WriterOptions, relation: &Relation, string_table_builder: &mut StringTableBuilder) -> RelationsGroupBuilder {
         let mut relations = Some(Vec::<osmpbf::Relation>::with_capacity(8000));
-        relations.as_mut().unwrap().push(Self::convert(relation, date_granularity, string_table_builder));
+        relations.as_mut().unwrap().push(Self::convert(relation, options.date_granularity(), options.metadata_fields(), string_table_builder));
 
         RelationsGroupBuilder {
             relations,
-            date_granularity,
+            date_granularity: options.date_granularity(),
+            metadata_fields: *options.metadata_fields(),
         }
     }
 
     pub(crate) fn add(&mut self, relation: &Relation, string_table_builder: &mut StringTableBuilder) {
-        self.relations.as_mut().unwrap().push(Self::convert(relation, self.date_granularity, string_table_builder));
+        self.relations.as_mut().unwrap().push(Self::convert(relation, self.date_granularity, &self.metadata_fields, string_table_builder));
     }
 
     #[allow(clippy::field_reassign_with_default)]
-    fn convert(relation: &Relation, date_granularity: i32, string_table_builder: &mut StringTableBuilder) -> osmpbf::Relation {
+    fn convert(relation: &Relation, date_granularity: i32, metadata_fields: &MetadataFields, string_table_builder: &mut StringTableBuilder) -> osmpbf::Relation {
         let mut r = osmpbf::Relation::default();
 
         r.id = relation.id();
@@ -59,13 +63,28 @@ impl RelationsGroupBuilder {
             r.vals.push(val_index as u32)
         }
 
-        r.info = Some(osmpbf::Info::default());
-        r.info.as_mut().unwrap().visible = Some(relation.visible());
-        r.info.as_mut().unwrap().uid = Some(relation.uid());
-        r.info.as_mut().unwrap().changeset = Some(relation.changeset());
-        r.info.as_mut().unwrap().timestamp = Some(relation.timestamp() / date_granularity as i64);
-        r.info.as_mut().unwrap().version = Some(relation.version());
-        r.info.as_mut().unwrap().user_sid = Some(string_table_builder.add(relation.user()) as u32);
+        if !metadata_fields.is_empty() {
+            let mut info = osmpbf::Info::default();
+            if metadata_fields.visible() {
+                info.visible = Some(relation.visible());
+            }
+            if metadata_fields.uid() {
+                info.uid = Some(relation.uid());
+            }
+            if metadata_fields.changeset() {
+                info.changeset = Some(relation.changeset());
+            }
+            if metadata_fields.timestamp() {
+                info.timestamp = Some(relation.timestamp() / date_granularity as i64);
+            }
+            if metadata_fields.version() {
+                info.version = Some(relation.version());
+            }
+            if metadata_fields.user() {
+                info.user_sid = Some(string_table_builder.add(relation.user()) as u32);
+            }
+            r.info = Some(info);
+        }
         r
     }
 
@@ -1,69 +1,67 @@
-use std::cell::RefCell;
 use std::collections::HashMap;
-
-use uuid::Uuid;
+use std::sync::{Mutex, RwLock};
+use std::thread::ThreadId;
 
 use crate::osm::model::element::Element;
 
-thread_local! {
-    static ACCUMULATORS: RefCell<HashMap<String, Vec<Element>>> = RefCell::new(HashMap::new());
-}
-
 /// Accumulate Elements to avoid calling [ParallelWriter] for each element
+///
+/// Each thread that adds elements gets its own buffer. The buffers are owned by the accumulator
+/// and are released when the accumulator is dropped.
+///
+/// [ParallelWriter]: crate::osm::pbf::parallel_writer::ParallelWriter
 pub struct ThreadLocalAccumulator {
-    id: String,
     capacity: usize,
+    accumulators: RwLock<HashMap<ThreadId, Mutex<Vec<Element>>>>,
 }
 
 impl ThreadLocalAccumulator {
     pub fn new(capacity: usize) -> ThreadLocalAccumulator {
-        let id = Uuid::new_v4().to_string();
         ThreadLocalAccumulator {
-            id,
             capacity,
+            accumulators: RwLock::new(HashMap::new()),
         }
     }
 
     pub fn add(&self, element: Element) {
-        ACCUMULATORS.with(|accumulators| {
-            let mut accumulators = accumulators.borrow_mut();
-            let accumulator = accumulators.get_mut(self.id.as_str());
-            match accumulator {
-                None => {
-                    let mut acc = Vec::with_capacity(self.capacity);
-                    acc.push(element);
-                    accumulators.insert(self.id.clone(), acc);
-                }
-                Some(acc) => {
-                    acc.push(element);
-                }
+        let thread_id = std::thread::current().id();
+        {
+            let accumulators = self.accumulators.read().unwrap();
+            if let Some(accumulator) = accumulators.get(&thread_id) {
+                accumulator.lock().unwrap().push(element);
+                return;
             }
-        });
+        }
+        let mut accumulator = Vec::with_capacity(self.capacity);
+        accumulator.push(element);
+        self.accumulators.write().unwrap().insert(thread_id, Mutex::new(accumulator));
     }
 
     pub fn elements(&self) -> Vec<Element> {
-        ACCUMULATORS.with(|accumulators| {
-            if !accumulators.borrow().contains_key(self.id.as_str()) {
+        let thread_id = std::thread::current().id();
+        let accumulators = self.accumulators.read().unwrap();
+        match accumulators.get(&thread_id) {
+            None => {
                 Vec::new()
-            } else {
-                let mut accumulators = accumulators.borrow_mut();
-                let accumulator = accumulators.get_mut(self.id.as_str()).unwrap();
-                std::mem::replace(accumulator, Vec::with_capacity(self.capacity))
             }
-        })
+            Some(accumulator) => {
+                let mut accumulator = accumulator.lock().unwrap();
+                std::mem::replace(&mut *accumulator, Vec::with_capacity(self.capacity))
+            }
+        }
     }
 
     #[allow(dead_code)]
     pub(crate) fn len(&self) -> usize {
-        ACCUMULATORS.with(|accumulators| {
-            if !accumulators.borrow().contains_key(self.id.as_str()) {
+        let thread_id = std::thread::current().id();
+        let accumulators = self.accumulators.read().unwrap();
+        match accumulators.get(&thread_id) {
+            None => {
                 0
-            } else {
-                let accumulators = accumulators.borrow_mut();
-                let accumulator = accumulators.get(self.id.as_str()).unwrap();
-                accumulator.len()
             }
-        })
+            Some(accumulator) => {
+                accumulator.lock().unwrap().len()
+            }
+        }
     }
 }
-
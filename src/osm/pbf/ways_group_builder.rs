@@ -1,30 +1,34 @@
 use crate::osm::model::way::Way;
+use crate::osm::pbf::metadata_fields::MetadataFields;
 use crate::osm::pbf::string_table_builder::StringTableBuilder;
+use crate::osm::pbf::writer_options::WriterOptions;
 use crate::osmpbf;
 use crate::osmpbf::PrimitiveGroup;
 
 pub(crate) struct WaysGroupBuilder {
     ways: Option<Vec<osmpbf::Way>>,
     date_granularity: i32,
+    metadata_fields: MetadataFields,
 }
 
 impl WaysGroupBuilder {
-    pub(crate) fn new(date_granularity: i32, way: &Way, string_table_builder: &mut StringTableBuilder) -> WaysGroupBuilder {
+    pub(crate) fn new(options: &WriterOptions, way: &Way, string_table_builder: &mut StringTableBuilder) -> WaysGroupBuilder {
         let mut ways = Some(Vec::<osmpbf::Way>::with_capacity(8000));
-        ways.as_mut().unwrap().push(Self::convert(way, date_granularity, string_table_builder));
+        ways.as_mut().unwrap().push(Self::convert(way, options.date_granularity(), options.metadata_fields(), string_table_builder));
 
         WaysGroupBuilder {
             ways,
-            date_granularity,
+            date_granularity: options.date_granularity(),
+            metadata_fields: *options.metadata_fields(),
         }
     }
 
     pub(crate) fn add(&mut self, way: &Way, string_table_builder: &mut StringTableBuilder) {
-        self.ways.as_mut().unwrap().push(Self::convert(way, self.date_granularity, string_table_builder));
+        self.ways.as_mut().unwrap().push(Self::convert(way, self.date_granularity, &self.metadata_fields, string_table_builder));
     }
 
     #[allow(clippy::field_reassign_with_default)]
-    fn convert(way: &Way, date_granularity: i32, string_table_builder: &mut StringTableBuilder) -> osmpbf::Way {
+    fn convert(way: &Way, date_granularity: i32, metadata_fields: &MetadataFields, string_table_builder: &mut StringTableBuilder) -> osmpbf::Way {
         let mut w = osmpbf::Way::default();
         w.id = way.id();
         let mut last_ref = 0;
@@ -39,13 +43,28 @@ impl WaysGroupBuilder {
             w.keys.push(key_index as u32);
             w.vals.push(val_index as u32)
         }
-        w.info = Some(osmpbf::Info::default());
-        w.info.as_mut().unwrap().visible = Some(way.visible());
-        w.info.as_mut().unwrap().uid = Some(way.uid());
-        w.info.as_mut().unwrap().changeset = Some(way.changeset());
-        w.info.as_mut().unwrap().timestamp = Some(way.timestamp() / date_granularity as i64);
-        w.info.as_mut().unwrap().version = Some(way.version());
-        w.info.as_mut().unwrap().user_sid = Some(string_table_builder.add(way.user()) as u32);
+        if !metadata_fields.is_empty() {
+            let mut info = osmpbf::Info::default();
+            if metadata_fields.visible() {
+                info.visible = Some(way.visible());
+            }
+            if metadata_fields.uid() {
+                info.uid = Some(way.uid());
+            }
+            if metadata_fields.changeset() {
+                info.changeset = Some(way.changeset());
+            }
+            if metadata_fields.timestamp() {
+                info.timestamp = Some(way.timestamp() / date_granularity as i64);
+            }
+            if metadata_fields.version() {
+                info.version = Some(way.version());
+            }
+            if metadata_fields.user() {
+                info.user_sid = Some(string_table_builder.add(way.user()) as u32);
+            }
+            w.info = Some(info);
+        }
         w
     }
 
@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::io::Write;
+use std::io::{Seek, SeekFrom, Write};
 use std::path::PathBuf;
 
 use anyhow::{anyhow, Context};
@@ -8,11 +8,11 @@ use crate::osm::model::bounding_box::BoundingBox;
 use crate::osm::model::element::Element;
 use crate::osm::pbf::compression_type::CompressionType;
 use crate::osm::pbf::element_accumulator::ElementAccumulator;
-use crate::osm::pbf::file_block::FileBlock;
-use crate::osm::pbf::file_block_metadata::FileBlockMetadata;
+use crate::osm::pbf::file_block::{EncodedBlob, FileBlock};
 use crate::osm::pbf::file_info::FileInfo;
 use crate::osm::pbf::osm_data::OsmData;
 use crate::osm::pbf::osm_header::OsmHeader;
+use crate::osm::pbf::writer_options::WriterOptions;
 
 /// *.osm.pbf file reader
 ///
@@ -61,10 +61,18 @@ pub struct Writer {
     path: PathBuf,
     file_info: FileInfo,
     compression_type: CompressionType,
+    options: WriterOptions,
     file: File,
     element_accumulator: ElementAccumulator,
+    bounding_box: Option<BoundingBox>,
+    // the size of the placeholder header, when the header is rewritten on close
+    header_size: Option<usize>,
 }
 
+// extra space reserved in the placeholder header for the variations in the encoded size of the
+// final header
+const HEADER_RESERVE: usize = 64;
+
 impl Writer {
     /// Create a new [Writer] from [FileInfo]
     pub fn from_file_info(
@@ -72,6 +80,17 @@ impl Writer {
         file_info: FileInfo,
         compression_type: CompressionType,
     ) -> Result<Writer, anyhow::Error> {
+        Self::from_options(path, file_info, compression_type, WriterOptions::default())
+    }
+
+    /// Create a new [Writer] from [FileInfo] with custom [WriterOptions]
+    pub fn from_options(
+        path: PathBuf,
+        file_info: FileInfo,
+        compression_type: CompressionType,
+        options: WriterOptions,
+    ) -> Result<Writer, anyhow::Error> {
+        options.validate()?;
         let file = File::create(path.clone())
             .with_context(|| anyhow!("path: {}", path.display()))?;
         Ok(
@@ -79,8 +98,11 @@ impl Writer {
                 path: path.clone(),
                 file_info,
                 compression_type,
+                element_accumulator: ElementAccumulator::new(options.max_block_size()),
+                options,
                 file,
-                element_accumulator: ElementAccumulator::new(),
+                bounding_box: None,
+                header_size: None,
             }
         )
     }
@@ -132,18 +154,62 @@ impl Writer {
     ///
     /// Must be called before writing elements. That means that all header values, specifically the
     /// bounding box must be calculated before writing the file. I some cases that can incur a
-    /// costly additional iteration.
+    /// costly additional iteration. To avoid it, enable [WriterOptions::with_auto_bounding_box]. In
+    /// that case a placeholder header is written and replaced on [Writer::close] by a header with
+    /// the bounding box of the written nodes.
     pub fn write_header(&mut self) -> Result<(), anyhow::Error> {
-        let file_block = FileBlock::from_header(
-            OsmHeader::from_file_info(self.file_info.clone())
-        );
+        if self.options.auto_bounding_box() {
+            let (blob_header, blob_body) = self.serialize_header(Some(BoundingBox::new(-180.0, -90.0, 180.0, 90.0)))?;
+            let blob_header = FileBlock::pad_blob_header(&blob_header, Some(HEADER_RESERVE))?;
+            self.header_size = Some(4 + blob_header.len() + blob_body.len());
+            self.write_blob(blob_header, blob_body)
+        } else {
+            let file_block = FileBlock::from_header(
+                OsmHeader::from_file_info(self.file_info.clone())
+            );
+            self.write_file_block(file_block)
+        }
+    }
 
-        self.write_file_block(file_block)
+    fn serialize_header(&self, bounding_box: Option<BoundingBox>) -> Result<EncodedBlob, anyhow::Error> {
+        let mut file_info = self.file_info.clone();
+        file_info.with_bounding_box(&bounding_box);
+        let file_block = FileBlock::from_header(OsmHeader::from_file_info(file_info));
+        FileBlock::serialize(&file_block, self.compression_type.clone(), &self.options)
+    }
+
+    /// Replace the placeholder header with a header of exactly the same size
+    fn rewrite_header(&mut self, header_size: usize) -> Result<(), anyhow::Error> {
+        let (blob_header, blob_body) = self.serialize_header(self.bounding_box.clone())?;
+        for padding in std::iter::once(None).chain((0..header_size).map(Some)) {
+            let padded_blob_header = FileBlock::pad_blob_header(&blob_header, padding)?;
+            if 4 + padded_blob_header.len() + blob_body.len() == header_size {
+                self.file.seek(SeekFrom::Start(0))?;
+                self.write_blob(padded_blob_header, blob_body)?;
+                self.file.seek(SeekFrom::End(0))?;
+                return Ok(());
+            }
+        }
+        Err(anyhow!("Failed to fit the header into {} bytes reserved for it in {}", header_size, self.path.display()))
+    }
+
+    /// Extend the header bounding box, when it is computed from the written nodes
+    pub(crate) fn merge_bounding_box(&mut self, bounding_box: Option<BoundingBox>) {
+        if let Some(bounding_box) = bounding_box {
+            match self.bounding_box.as_mut() {
+                None => {
+                    self.bounding_box = Some(bounding_box);
+                }
+                Some(current) => {
+                    current.merge_bounding_box(&bounding_box);
+                }
+            }
+        }
     }
 
     /// Low level API to write a [FileBlock]
     pub fn write_file_block(&mut self, file_block: FileBlock) -> Result<(), anyhow::Error> {
-        let (blob_header, blob_body) = FileBlock::serialize(&file_block, self.compression_type.clone())?;
+        let (blob_header, blob_body) = FileBlock::serialize(&file_block, self.compression_type.clone(), &self.options)?;
         self.write_blob(blob_header, blob_body)
     }
 
@@ -176,26 +242,28 @@ impl Writer {
     /// Elements must be ordered, that is each element must be less then or equal to the following
     /// element
     pub fn write_elements(&mut self, elements: Vec<Element>) -> Result<(), anyhow::Error> {
-        let index = self.element_accumulator.index();
-        let data = FileBlock::Data {
-            metadata: FileBlockMetadata::new(
-                "OSMData".to_string(),
-                index,
-            ),
-            data: OsmData::from_elements(elements, None),
-        };
-        self.write_file_block(data)?;
+        if self.options.auto_bounding_box() {
+            self.merge_bounding_box(OsmData::nodes_bounding_box(&elements));
+        }
+        let blobs = FileBlock::serialize_elements(elements, self.compression_type.clone(), &self.options)?;
+        for (blob_header, blob_body) in blobs {
+            self.write_blob(blob_header, blob_body)?;
+        }
         Ok(())
     }
 
     /// Flush the internal buffers.
     ///
-    /// Must be called in the end to write any elements accumulated in internal buffers
+    /// Must be called in the end to write any elements accumulated in internal buffers and, if
+    /// enabled, the header with the computed bounding box
     pub fn close(&mut self) -> Result<(), anyhow::Error> {
         let elements = self.element_accumulator.elements();
         if !elements.is_empty() {
             self.write_elements(elements)?;
         }
+        if let Some(header_size) = self.header_size.take() {
+            self.rewrite_header(header_size)?;
+        }
         Ok(())
     }
 
@@ -0,0 +1,212 @@
+use anyhow::anyhow;
+
+use crate::osm::pbf::metadata_fields::MetadataFields;
+
+const NANODEG: f64 = 1E9f64;
+
+/// The maximal size of an uncompressed data block recommended by the PBF specification
+pub const RECOMMENDED_MAX_BLOCK_SIZE: usize = 16 * 1024 * 1024;
+
+/// The maximal size of an uncompressed data block allowed by the PBF specification
+pub const MAX_BLOCK_SIZE: usize = 32 * 1024 * 1024;
+
+/// Encoding options for *.osm.pbf writers
+///
+/// Coordinates are encoded as `(coordinate * 10^9 - offset) / granularity` and decoded as
+/// `(offset + granularity * value) / 10^9`, so the precision of the output is `granularity`
+/// nanodegrees. Timestamps are encoded in units of `date_granularity` milliseconds. The element
+/// metadata written to the output is selected with [MetadataFields]. Data blocks are split so that
+/// the size of each uncompressed block does not exceed `max_block_size`. With `auto_bounding_box`
+/// the bounding box in the file header is computed from the written nodes and the header is
+/// rewritten when the writer is closed, so it does not have to be known before writing.
+/// The defaults, granularity 100, date_granularity 1000 and zero offsets, preserve the precision of
+/// the OSM data and match the encoding of most *.osm.pbf producers.
+///
+/// Example:
+/// ```
+/// use osm_io::osm::pbf::writer_options::WriterOptions;
+/// fn example() -> Result<(), anyhow::Error> {
+///     // low precision output with 1e-5 degrees resolution
+///     let mut options = WriterOptions::default();
+///     options.with_granularity(10000);
+///     options.validate()?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct WriterOptions {
+    granularity: i32,
+    date_granularity: i32,
+    lat_offset: i64,
+    lon_offset: i64,
+    metadata_fields: MetadataFields,
+    max_block_size: usize,
+    auto_bounding_box: bool,
+}
+
+impl WriterOptions {
+    /// Get the coordinate granularity in nanodegrees
+    pub fn granularity(&self) -> i32 {
+        self.granularity
+    }
+
+    /// Set the coordinate granularity in nanodegrees
+    pub fn with_granularity(&mut self, granularity: i32) {
+        self.granularity = granularity;
+    }
+
+    /// Get the timestamp granularity in milliseconds
+    pub fn date_granularity(&self) -> i32 {
+        self.date_granularity
+    }
+
+    /// Set the timestamp granularity in milliseconds
+    pub fn with_date_granularity(&mut self, date_granularity: i32) {
+        self.date_granularity = date_granularity;
+    }
+
+    /// Get the latitude offset in nanodegrees
+    pub fn lat_offset(&self) -> i64 {
+        self.lat_offset
+    }
+
+    /// Set the latitude offset in nanodegrees
+    pub fn with_lat_offset(&mut self, lat_offset: i64) {
+        self.lat_offset = lat_offset;
+    }
+
+    /// Get the longitude offset in nanodegrees
+    pub fn lon_offset(&self) -> i64 {
+        self.lon_offset
+    }
+
+    /// Set the longitude offset in nanodegrees
+    pub fn with_lon_offset(&mut self, lon_offset: i64) {
+        self.lon_offset = lon_offset;
+    }
+
+    /// Get the selection of metadata fields written to the output
+    pub fn metadata_fields(&self) -> &MetadataFields {
+        &self.metadata_fields
+    }
+
+    /// Set the selection of metadata fields written to the output
+    pub fn with_metadata_fields(&mut self, metadata_fields: MetadataFields) {
+        self.metadata_fields = metadata_fields;
+    }
+
+    /// Get the maximal size of an uncompressed data block in bytes
+    pub fn max_block_size(&self) -> usize {
+        self.max_block_size
+    }
+
+    /// Set the maximal size of an uncompressed data block in bytes. Must not exceed [MAX_BLOCK_SIZE]
+    pub fn with_max_block_size(&mut self, max_block_size: usize) {
+        self.max_block_size = max_block_size;
+    }
+
+    /// True if the header bounding box is computed from the written nodes
+    pub fn auto_bounding_box(&self) -> bool {
+        self.auto_bounding_box
+    }
+
+    /// Compute the header bounding box from the written nodes, replacing the bounding box of the
+    /// [FileInfo](crate::osm::pbf::file_info::FileInfo) provided to the writer
+    pub fn with_auto_bounding_box(&mut self, auto_bounding_box: bool) {
+        self.auto_bounding_box = auto_bounding_box;
+    }
+
+    /// True if coordinates with 7 decimal digits, as used by OSM, are encoded without loss
+    pub fn is_lossless(&self) -> bool {
+        100 % self.granularity == 0 && self.lat_offset % 100 == 0 && self.lon_offset % 100 == 0
+    }
+
+    /// Verify that the options are valid and that any valid coordinate round-trips within the
+    /// precision defined by the granularity
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        if self.granularity <= 0 {
+            return Err(anyhow!("Granularity must be positive, found: {}", self.granularity));
+        }
+        if self.date_granularity <= 0 {
+            return Err(anyhow!("Date granularity must be positive, found: {}", self.date_granularity));
+        }
+        if self.max_block_size == 0 || self.max_block_size > MAX_BLOCK_SIZE {
+            return Err(anyhow!("Maximal block size must be between 1 and {MAX_BLOCK_SIZE}, found: {}", self.max_block_size));
+        }
+
+        let tolerance = self.granularity as f64 / 2.0 / NANODEG + 1E-12;
+        for lat in [-90.0, -45.1234567, 0.0, 45.1234567, 90.0] {
+            let decoded = Self::decode(Self::encode(lat, self.granularity, self.lat_offset), self.granularity, self.lat_offset);
+            if decoded.is_none_or(|decoded| (decoded - lat).abs() > tolerance) {
+                return Err(anyhow!("Latitude {lat} does not round-trip with {self:?}"));
+            }
+        }
+        for lon in [-180.0, -90.1234567, 0.0, 90.1234567, 180.0] {
+            let decoded = Self::decode(Self::encode(lon, self.granularity, self.lon_offset), self.granularity, self.lon_offset);
+            if decoded.is_none_or(|decoded| (decoded - lon).abs() > tolerance) {
+                return Err(anyhow!("Longitude {lon} does not round-trip with {self:?}"));
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn encode(coordinate: f64, granularity: i32, offset: i64) -> i64 {
+        ((coordinate * NANODEG - offset as f64) / granularity as f64).round() as i64
+    }
+
+    fn decode(value: i64, granularity: i32, offset: i64) -> Option<f64> {
+        (granularity as i64)
+            .checked_mul(value)
+            .and_then(|v| v.checked_add(offset))
+            .map(|v| v as f64 / NANODEG)
+    }
+}
+
+impl Default for WriterOptions {
+    fn default() -> Self {
+        WriterOptions {
+            granularity: 100,
+            date_granularity: 1000,
+            lat_offset: 0,
+            lon_offset: 0,
+            metadata_fields: MetadataFields::all(),
+            max_block_size: RECOMMENDED_MAX_BLOCK_SIZE,
+            auto_bounding_box: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let mut options = WriterOptions::default();
+        assert!(options.validate().is_ok());
+        assert!(options.is_lossless());
+
+        options.with_granularity(10000);
+        options.with_lat_offset(-5_000_000_000);
+        options.with_lon_offset(7_000_000_000);
+        assert!(options.validate().is_ok());
+        assert!(!options.is_lossless());
+
+        options.with_granularity(0);
+        assert!(options.validate().is_err());
+
+        options = WriterOptions::default();
+        options.with_date_granularity(-1);
+        assert!(options.validate().is_err());
+
+        options = WriterOptions::default();
+        options.with_lat_offset(i64::MAX);
+        assert!(options.validate().is_err());
+
+        options = WriterOptions::default();
+        options.with_max_block_size(MAX_BLOCK_SIZE + 1);
+        assert!(options.validate().is_err());
+        options.with_max_block_size(0);
+        assert!(options.validate().is_err());
+    }
+}
@@ -61,3 +61,19 @@ pub fn analyze_pbf_output(output_path: PathBuf, fixture_analysis_path: PathBuf)
     assert_eq!(atomic_ways_clone.fetch_or(0, Ordering::SeqCst), fixture_analysis["data"]["count"]["ways"].as_i64().unwrap());
     assert_eq!(atomic_relations_clone.fetch_or(0, Ordering::SeqCst), fixture_analysis["data"]["count"]["relations"].as_i64().unwrap());
 }
+
+/// The url of a scratch database used by the tests that require Postgresql, from
+/// OSM_IO_TEST_DATABASE_URL. The public schema of the database is recreated by the tests
+#[allow(dead_code)]
+pub fn test_database_url() -> Option<String> {
+    std::env::var("OSM_IO_TEST_DATABASE_URL").ok()
+}
+
+/// Recreate the public schema with the apidb structure. Requires the btree_gist extension
+#[cfg(feature = "apidb")]
+#[allow(dead_code)]
+pub fn create_apidb_schema(client: &mut postgres::Client) -> Result<(), anyhow::Error> {
+    client.batch_execute("DROP SCHEMA IF EXISTS public CASCADE; CREATE SCHEMA public;")?;
+    client.batch_execute(include_str!("../../src/osm/apidb_dump/sql/structure.sql"))?;
+    Ok(())
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "apidb")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::apidb_dump::read::reader::Reader as ApiDbDumpReader;
+use osm_io::osm::apidb_dump::write::writer::Writer as ApiDbDumpWriter;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::file_info::FileInfo;
+use osm_io::osm::pbf::reader::Reader as PbfReader;
+use osm_io::osm::pbf::writer::Writer as PbfWriter;
+
+mod common;
+
+#[test]
+fn test_apidb_dump_compression() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let dump_path = PathBuf::from("./target/results/compressed-history-niue-230109");
+    let tmp_path = PathBuf::from("./target/results/compressed-history-niue-230109-tmp");
+    let output_path = PathBuf::from("./target/results/compressed-history-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf.analysis.json");
+    if dump_path.exists() {
+        fs::remove_dir_all(&dump_path)?;
+    }
+
+    let pbf_reader = PbfReader::new(&input_path)?;
+    let mut apidb_dump_writer = ApiDbDumpWriter::new(dump_path.clone(), 6)?;
+    for element in pbf_reader.elements()? {
+        apidb_dump_writer.write_element(element)?;
+    }
+    apidb_dump_writer.close()?;
+
+    // the table data is written as NNNN.dat.gz and the compression is recorded in the TOC
+    assert!(dump_path.join("4260.dat.gz").is_file());
+    assert!(!dump_path.join("4260.dat").exists());
+    let toc = fs::read(dump_path.join("toc.dat"))?;
+    assert_eq!(toc[11..16], [0, 6, 0, 0, 0]);
+
+    assert!(ApiDbDumpWriter::new(PathBuf::from("./target/results/invalid-compression"), 10).is_err());
+
+    let apidb_dump_reader = ApiDbDumpReader::new(dump_path, tmp_path)?;
+    let file_info = FileInfo::new(
+        None,
+        ["OsmSchema-V0.6", "DenseNodes"].map(|s| s.to_string()).to_vec(),
+        ["Sort.Type_then_ID", "HistoricalInformation"].map(|s| s.to_string()).to_vec(),
+        Some("test-writer".to_string()),
+        Some("from-compressed-apidb-dump".to_string()),
+        None,
+        None,
+        None,
+    );
+    let mut pbf_writer = PbfWriter::from_file_info(output_path.clone(), file_info, CompressionType::Zlib)?;
+    pbf_writer.write_header()?;
+    for element in apidb_dump_reader.elements()? {
+        pbf_writer.write_element(element)?;
+    }
+    pbf_writer.close()?;
+
+    common::analyze_pbf_output(output_path, fixture_analysis_path);
+    Ok(())
+}
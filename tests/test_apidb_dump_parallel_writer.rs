@@ -0,0 +1,87 @@
+#![cfg(feature = "apidb")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::apidb_dump::write::parallel_writer::ParallelWriter;
+use osm_io::osm::apidb_dump::write::parallel_writer_options::ParallelWriterOptions;
+use osm_io::osm::apidb_dump::write::writer::Writer as ApiDbDumpWriter;
+use osm_io::osm::pbf::reader::Reader as PbfReader;
+use osm_io::osm::pbf::thread_local_accumulator::ThreadLocalAccumulator;
+
+mod common;
+
+#[test]
+fn test_apidb_dump_parallel_writer() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let sequential_dump_path = PathBuf::from("./target/results/sequential-history-niue-230109");
+    let parallel_dump_path = PathBuf::from("./target/results/parallel-history-niue-230109");
+    for path in [&sequential_dump_path, &parallel_dump_path] {
+        if path.exists() {
+            fs::remove_dir_all(path)?;
+        }
+    }
+
+    let pbf_reader = PbfReader::new(&input_path)?;
+    let mut apidb_dump_writer = ApiDbDumpWriter::new(sequential_dump_path.clone(), 0)?;
+    for element in pbf_reader.elements()? {
+        apidb_dump_writer.write_element(element)?;
+    }
+    apidb_dump_writer.close()?;
+
+    let parallel_writer = Arc::new(Mutex::new(ParallelWriter::new(parallel_dump_path.clone(), 0)?));
+    let parallel_writer_clone = parallel_writer.clone();
+    let tl_acc = ThreadLocalAccumulator::new(8000);
+    pbf_reader.parallel_for_each(4, move |element| {
+        if !element.is_sentinel() {
+            tl_acc.add(element);
+        } else {
+            parallel_writer.lock().unwrap().write_elements(tl_acc.elements())?;
+        }
+        Ok(())
+    })?;
+    parallel_writer_clone.lock().unwrap().close()?;
+
+    // all tables except public.users, which records the time of writing, are identical
+    let mut compared_tables = 0;
+    for entry in fs::read_dir(&sequential_dump_path)? {
+        let file_name = entry?.file_name();
+        if file_name == "toc.dat" || file_name == "4290.dat" {
+            continue;
+        }
+        let sequential_table = fs::read(sequential_dump_path.join(&file_name))?;
+        let parallel_table = fs::read(parallel_dump_path.join(&file_name))?;
+        assert!(sequential_table == parallel_table, "Table data differs: {:?}", file_name);
+        compared_tables += 1;
+    }
+    assert!(compared_tables > 19);
+    assert_eq!(
+        fs::read_to_string(sequential_dump_path.join("4290.dat"))?.lines().count(),
+        fs::read_to_string(parallel_dump_path.join("4290.dat"))?.lines().count(),
+    );
+    Ok(())
+}
+
+#[test]
+fn test_apidb_dump_parallel_writer_order_lost() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let dump_path = PathBuf::from("./target/results/parallel-order-lost-niue-230109");
+
+    let mut options = ParallelWriterOptions::default();
+    options.with_element_ordering_buffer_size(0);
+    options.with_batch_size(1);
+    let mut parallel_writer = ParallelWriter::from_options(dump_path, 0, options)?;
+    let pbf_reader = PbfReader::new(&input_path)?;
+    let mut elements: Vec<_> = pbf_reader.elements()?.filter(|element| element.is_node()).take(100).collect();
+    elements.reverse();
+    // the formatting thread may fail before all elements are sent
+    let _ = parallel_writer.write_elements(elements);
+    assert!(parallel_writer.close().is_err());
+    Ok(())
+}
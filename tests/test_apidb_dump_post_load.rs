@@ -0,0 +1,153 @@
+#![cfg(feature = "apidb")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use postgres::{Client, NoTls};
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::apidb_dump::write::database_writer::DatabaseWriter;
+use osm_io::osm::apidb_dump::write::post_load_options::PostLoadOptions;
+use osm_io::osm::apidb_dump::write::schema::Schema;
+use osm_io::osm::apidb_dump::write::writer::Writer as ApiDbDumpWriter;
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::reader::Reader as PbfReader;
+
+mod common;
+
+/// The largest node, way, relation, changeset and user ids of the input
+fn max_ids(input_path: &Path) -> Result<[i64; 5], anyhow::Error> {
+    let mut max_ids = [0; 5];
+    for element in PbfReader::new(input_path)?.elements()? {
+        let (index, id, changeset, uid) = match &element {
+            Element::Node { node } => {
+                (0, node.id(), node.changeset(), node.uid() as i64)
+            }
+            Element::Way { way } => {
+                (1, way.id(), way.changeset(), way.uid() as i64)
+            }
+            Element::Relation { relation } => {
+                (2, relation.id(), relation.changeset(), relation.uid() as i64)
+            }
+            Element::Sentinel => {
+                continue;
+            }
+        };
+        max_ids[index] = max_ids[index].max(id);
+        max_ids[3] = max_ids[3].max(changeset);
+        max_ids[4] = max_ids[4].max(uid);
+    }
+    Ok(max_ids)
+}
+
+fn setval_statements(max_ids: &[i64; 5]) -> Vec<String> {
+    [
+        "public.current_nodes_id_seq",
+        "public.current_ways_id_seq",
+        "public.current_relations_id_seq",
+        "public.changesets_id_seq",
+        "public.users_id_seq",
+    ].iter()
+        .zip(max_ids)
+        .map(|(sequence, max_id)| format!("SELECT pg_catalog.setval('{}', {}, true);", sequence, max_id))
+        .collect()
+}
+
+fn write_dump(input_path: &Path, dump_path: &Path, schema: Option<&Schema>, post_load_options: PostLoadOptions) -> Result<(), anyhow::Error> {
+    if dump_path.exists() {
+        fs::remove_dir_all(dump_path)?;
+    }
+    let mut writer = match schema {
+        None => {
+            ApiDbDumpWriter::new(dump_path.to_path_buf(), 0)?
+        }
+        Some(schema) => {
+            ApiDbDumpWriter::from_schema(dump_path.to_path_buf(), 0, schema)?
+        }
+    };
+    writer.with_post_load_options(post_load_options);
+    for element in PbfReader::new(input_path)?.elements()? {
+        writer.write_element(element)?;
+    }
+    writer.close()?;
+    Ok(())
+}
+
+fn last_value(client: &mut Client, sequence: &str) -> Result<i64, anyhow::Error> {
+    Ok(client.query_one(&format!("SELECT last_value FROM {}", sequence), &[])?.get(0))
+}
+
+#[test]
+fn test_apidb_dump_post_load() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let dump_path = PathBuf::from("./target/results/post-load-history-niue-230109");
+    let schema_dump_path = PathBuf::from("./target/results/post-load-schema-history-niue-230109");
+    let max_ids = max_ids(&input_path)?;
+    let setval_statements = setval_statements(&max_ids);
+
+    // the template TOC sets the sequences of the populated tables and resets the others
+    write_dump(&input_path, &dump_path, None, PostLoadOptions::default())?;
+    let toc = String::from_utf8_lossy(&fs::read(dump_path.join("toc.dat"))?).to_string();
+    for statement in &setval_statements {
+        assert!(toc.contains(statement), "missing in toc.dat: {}", statement);
+    }
+    assert!(toc.contains("SELECT pg_catalog.setval('public.acls_id_seq', 1, false);"));
+    let post_load_sql = fs::read_to_string(dump_path.join("post_load.sql"))?;
+    assert!(post_load_sql.contains("ANALYZE public.nodes;\n"));
+    assert!(!post_load_sql.contains("REINDEX"));
+
+    // the schema TOC has the SEQUENCE SET entries of the schema sequences
+    let mut post_load_options = PostLoadOptions::default();
+    post_load_options.with_analyze(false);
+    write_dump(&input_path, &schema_dump_path, Some(&Schema::bundled()?), post_load_options)?;
+    let toc = String::from_utf8_lossy(&fs::read(schema_dump_path.join("toc.dat"))?).to_string();
+    assert_eq!(toc.matches("SEQUENCE SET").count(), 32);
+    for statement in &setval_statements {
+        assert!(toc.contains(statement), "missing in toc.dat: {}", statement);
+    }
+    assert!(!schema_dump_path.join("post_load.sql").exists());
+
+    let database_url = match common::test_database_url() {
+        Some(database_url) => database_url,
+        None => {
+            log::warn!("OSM_IO_TEST_DATABASE_URL is not set, skipping the database part");
+            return Ok(());
+        }
+    };
+
+    // pg_restore sets the sequences, psql runs the post load steps
+    let mut client = Client::connect(&database_url, NoTls)?;
+    client.batch_execute("DROP SCHEMA IF EXISTS public CASCADE; CREATE SCHEMA public;")?;
+    let status = Command::new("pg_restore")
+        .args(["--exit-on-error", "--no-owner", "-d", &database_url])
+        .arg(&dump_path)
+        .status()?;
+    assert!(status.success());
+    assert_eq!(last_value(&mut client, "public.current_nodes_id_seq")?, max_ids[0]);
+    assert_eq!(last_value(&mut client, "public.current_ways_id_seq")?, max_ids[1]);
+    assert_eq!(last_value(&mut client, "public.current_relations_id_seq")?, max_ids[2]);
+    let status = Command::new("psql")
+        .args(["-v", "ON_ERROR_STOP=1", "-q", "-d", &database_url, "-f"])
+        .arg(dump_path.join("post_load.sql"))
+        .status()?;
+    assert!(status.success());
+    let analyzed: i64 = client.query_one("SELECT count(*) FROM pg_stat_user_tables WHERE relname = 'nodes' AND last_analyze IS NOT NULL", &[])?.get(0);
+    assert_eq!(analyzed, 1);
+
+    // the database writer sets the sequences after the load
+    client.batch_execute("DROP SCHEMA IF EXISTS public CASCADE; CREATE SCHEMA public;")?;
+    client.batch_execute(include_str!("../src/osm/apidb_dump/sql/structure.sql"))?;
+    let mut database_writer = DatabaseWriter::new(&database_url)?;
+    for element in PbfReader::new(&input_path)?.elements()? {
+        database_writer.write_element(element)?;
+    }
+    database_writer.close()?;
+    assert_eq!(last_value(&mut client, "public.changesets_id_seq")?, max_ids[3]);
+    assert_eq!(last_value(&mut client, "public.users_id_seq")?, max_ids[4]);
+    let next_node_id: i64 = client.query_one("SELECT nextval('public.current_nodes_id_seq')", &[])?.get(0);
+    assert_eq!(next_node_id, max_ids[0] + 1);
+    Ok(())
+}
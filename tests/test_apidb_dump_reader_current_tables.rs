@@ -0,0 +1,74 @@
+#![cfg(feature = "apidb")]
+
+use std::path::PathBuf;
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::apidb_dump::read::reader::Reader as ApiDbDumpReader;
+use osm_io::osm::apidb_dump::read::reader_options::ReaderOptions;
+use osm_io::osm::apidb_dump::read::table_set::TableSet;
+use osm_io::osm::apidb_dump::write::writer::Writer as ApiDbDumpWriter;
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::file_info::FileInfo;
+use osm_io::osm::pbf::reader::Reader as PbfReader;
+use osm_io::osm::pbf::writer::Writer as PbfWriter;
+
+mod common;
+
+#[test]
+fn test_apidb_dump_reader_current_tables() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let dump_path = PathBuf::from("./target/results/current-history-niue-230109");
+    let tmp_path = PathBuf::from("./target/results/current-history-niue-230109-tmp");
+    let output_path = PathBuf::from("./target/results/current-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+
+    log::info!("Started apidb dump reader current tables test");
+    let pbf_reader = PbfReader::new(&input_path)?;
+    let mut apidb_dump_writer = ApiDbDumpWriter::new(dump_path.clone(), 0)?;
+    for element in pbf_reader.elements()? {
+        apidb_dump_writer.write_element(element)?;
+    }
+    apidb_dump_writer.close()?;
+
+    let mut options = ReaderOptions::default();
+    options.with_table_set(TableSet::Current);
+    let apidb_dump_reader = ApiDbDumpReader::from_options(dump_path, tmp_path, options)?;
+
+    let file_info = FileInfo::new(
+        None,
+        ["OsmSchema-V0.6", "DenseNodes"].map(|s| s.to_string()).to_vec(),
+        ["Sort.Type_then_ID"].map(|s| s.to_string()).to_vec(),
+        Some("test-writer".to_string()),
+        Some("from-apidb-dump-current-tables".to_string()),
+        None,
+        None,
+        None,
+    );
+    let mut pbf_writer = PbfWriter::from_file_info(output_path.clone(), file_info, CompressionType::Zlib)?;
+    pbf_writer.write_header()?;
+    for element in apidb_dump_reader.elements()? {
+        match &element {
+            Element::Node { node } => {
+                assert!(node.visible());
+            }
+            Element::Way { way } => {
+                assert!(way.visible());
+            }
+            Element::Relation { relation } => {
+                assert!(relation.visible());
+            }
+            Element::Sentinel => {}
+        }
+        pbf_writer.write_element(element)?;
+    }
+    pbf_writer.close()?;
+
+    common::analyze_pbf_output(output_path, fixture_analysis_path);
+
+    log::info!("Finished apidb dump reader current tables test");
+    Ok(())
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "apidb")]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::apidb_dump::read::reader::Reader as ApiDbDumpReader;
+use osm_io::osm::apidb_dump::read::reader_options::ReaderOptions;
+use osm_io::osm::apidb_dump::read::table_order::TableOrder;
+use osm_io::osm::apidb_dump::write::writer::Writer as ApiDbDumpWriter;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::file_info::FileInfo;
+use osm_io::osm::pbf::reader::Reader as PbfReader;
+use osm_io::osm::pbf::writer::Writer as PbfWriter;
+
+mod common;
+
+fn read_dump(dump_path: &Path, tmp_path: &Path, options: ReaderOptions, output_path: &Path) -> Result<(), anyhow::Error> {
+    let apidb_dump_reader = ApiDbDumpReader::from_options(dump_path.to_path_buf(), tmp_path.to_path_buf(), options)?;
+    let file_info = FileInfo::new(
+        None,
+        ["OsmSchema-V0.6", "DenseNodes"].map(|s| s.to_string()).to_vec(),
+        ["Sort.Type_then_ID", "HistoricalInformation"].map(|s| s.to_string()).to_vec(),
+        Some("test-writer".to_string()),
+        Some("from-apidb-dump".to_string()),
+        None,
+        None,
+        None,
+    );
+    let mut pbf_writer = PbfWriter::from_file_info(output_path.to_path_buf(), file_info, CompressionType::Zlib)?;
+    pbf_writer.write_header()?;
+    for element in apidb_dump_reader.elements()? {
+        pbf_writer.write_element(element)?;
+    }
+    pbf_writer.close()?;
+    Ok(())
+}
+
+#[test]
+fn test_apidb_dump_reader_table_order() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let dump_path = PathBuf::from("./target/results/ordered-history-niue-230109");
+    let tmp_path = PathBuf::from("./target/results/ordered-history-niue-230109-tmp");
+    let output_path = PathBuf::from("./target/results/ordered-history-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf.analysis.json");
+    for path in [&dump_path, &tmp_path] {
+        if path.exists() {
+            fs::remove_dir_all(path)?;
+        }
+    }
+
+    let pbf_reader = PbfReader::new(&input_path)?;
+    let mut apidb_dump_writer = ApiDbDumpWriter::new(dump_path.clone(), 0)?;
+    for element in pbf_reader.elements()? {
+        apidb_dump_writer.write_element(element)?;
+    }
+    apidb_dump_writer.close()?;
+
+    // the tables written by Writer are ordered, nothing is sorted
+    read_dump(&dump_path, &tmp_path, ReaderOptions::default(), &output_path)?;
+    common::analyze_pbf_output(output_path.clone(), fixture_analysis_path.clone());
+    assert!(!tmp_path.join("sorted-public.nodes.dat").exists());
+
+    let mut options = ReaderOptions::default();
+    options.with_table_order(TableOrder::Trust);
+    read_dump(&dump_path, &tmp_path, options, &output_path)?;
+    common::analyze_pbf_output(output_path.clone(), fixture_analysis_path.clone());
+    assert!(!tmp_path.join("sorted-public.nodes.dat").exists());
+
+    // sorting leaves a marker of the completed sort, which allows to resume
+    let mut options = ReaderOptions::default();
+    options.with_table_order(TableOrder::Sort);
+    read_dump(&dump_path, &tmp_path, options.clone(), &output_path)?;
+    common::analyze_pbf_output(output_path.clone(), fixture_analysis_path.clone());
+    let sorted_nodes_path = tmp_path.join("sorted-public.nodes.dat");
+    assert!(tmp_path.join("sorted-public.nodes.dat.done").is_file());
+    let sorted_at = fs::metadata(&sorted_nodes_path)?.modified()?;
+
+    options.with_resume(true);
+    read_dump(&dump_path, &tmp_path, options, &output_path)?;
+    common::analyze_pbf_output(output_path.clone(), fixture_analysis_path.clone());
+    assert_eq!(fs::metadata(&sorted_nodes_path)?.modified()?, sorted_at);
+
+    // a table that is not ordered is sorted
+    fs::remove_dir_all(&tmp_path)?;
+    let nodes_path = dump_path.join("4260.dat");
+    let nodes = fs::read_to_string(&nodes_path)?;
+    let mut lines: Vec<&str> = nodes.lines().filter(|line| !line.starts_with("\\.")).collect();
+    lines.reverse();
+    fs::write(&nodes_path, format!("{}\n\\.\n", lines.join("\n")))?;
+    read_dump(&dump_path, &tmp_path, ReaderOptions::default(), &output_path)?;
+    common::analyze_pbf_output(output_path.clone(), fixture_analysis_path.clone());
+    assert!(sorted_nodes_path.is_file());
+    assert!(!tmp_path.join("sorted-public.ways.dat").exists());
+    Ok(())
+}
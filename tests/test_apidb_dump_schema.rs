@@ -0,0 +1,148 @@
+#![cfg(feature = "apidb")]
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+
+use postgres::{Client, NoTls};
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::apidb_dump::read::reader::Reader as ApiDbDumpReader;
+use osm_io::osm::apidb_dump::write::database_writer::DatabaseWriter;
+use osm_io::osm::apidb_dump::write::schema::Schema;
+use osm_io::osm::apidb_dump::write::writer::Writer as ApiDbDumpWriter;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::file_info::FileInfo;
+use osm_io::osm::pbf::reader::Reader as PbfReader;
+use osm_io::osm::pbf::writer::Writer as PbfWriter;
+
+mod common;
+
+fn replace(structure_sql: &str, from: &str, to: &str) -> String {
+    assert!(structure_sql.contains(from), "missing in structure.sql: {}", from);
+    structure_sql.replacen(from, to, 1)
+}
+
+/// A later version of the bundled schema: nodes.redaction_id is removed, users.company and the
+/// user_mutes table are added
+fn upgraded_structure_sql() -> String {
+    let structure_sql = include_str!("../src/osm/apidb_dump/sql/structure.sql");
+    let structure_sql = replace(
+        structure_sql,
+        "    tile bigint NOT NULL,\n    version bigint NOT NULL,\n    redaction_id integer\n);",
+        "    tile bigint NOT NULL,\n    version bigint NOT NULL\n);",
+    );
+    let structure_sql = replace(
+        &structure_sql,
+        "--\n-- Name: nodes nodes_redaction_id_fkey; Type: FK CONSTRAINT; Schema: public; Owner: -\n--\n\n\
+        ALTER TABLE ONLY public.nodes\n    ADD CONSTRAINT nodes_redaction_id_fkey FOREIGN KEY (redaction_id) REFERENCES public.redactions(id);\n\n\n",
+        "",
+    );
+    let structure_sql = replace(
+        &structure_sql,
+        "    tou_agreed timestamp without time zone\n);",
+        "    tou_agreed timestamp without time zone,\n    company character varying DEFAULT ''::character varying NOT NULL\n);",
+    );
+    let structure_sql = replace(
+        &structure_sql,
+        "--\n-- PostgreSQL database dump complete",
+        "--\n-- Name: user_mutes; Type: TABLE; Schema: public; Owner: -\n--\n\n\
+        CREATE TABLE public.user_mutes (\n    id bigint NOT NULL,\n    owner_id bigint NOT NULL\n);\n\n\n\
+        --\n-- PostgreSQL database dump complete",
+    );
+    replace(&structure_sql, "('9');", "('9'),\n('20240101000000');")
+}
+
+fn count(client: &mut Client, query: &str) -> Result<i64, anyhow::Error> {
+    Ok(client.query_one(query, &[])?.get(0))
+}
+
+#[test]
+fn test_apidb_dump_schema() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let dump_path = PathBuf::from("./target/results/upgraded-history-niue-230109");
+    let tmp_path = PathBuf::from("./target/results/upgraded-history-niue-230109-tmp");
+    let output_path = PathBuf::from("./target/results/upgraded-history-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf.analysis.json");
+    let fixture_analysis = common::read_fixture_analysis(&fixture_analysis_path);
+    for path in [&dump_path, &tmp_path] {
+        if path.exists() {
+            fs::remove_dir_all(path)?;
+        }
+    }
+
+    assert_eq!(Schema::bundled()?.version(), Some("20220223140543"));
+    let structure_sql = upgraded_structure_sql();
+    let schema = Schema::from_structure_sql(&structure_sql)?;
+    assert_eq!(schema.version(), Some("20240101000000"));
+
+    // a column without a default can't be loaded
+    let invalid_schema = Schema::from_structure_sql(
+        &replace(&structure_sql, "company character varying DEFAULT ''::character varying NOT NULL", "company character varying NOT NULL")
+    )?;
+    assert!(ApiDbDumpWriter::from_schema(PathBuf::from("./target/results/invalid-schema"), 0, &invalid_schema).is_err());
+
+    let pbf_reader = PbfReader::new(&input_path)?;
+    let mut apidb_dump_writer = ApiDbDumpWriter::from_schema(dump_path.clone(), 0, &schema)?;
+    for element in pbf_reader.elements()? {
+        apidb_dump_writer.write_element(element)?;
+    }
+    apidb_dump_writer.close()?;
+
+    let toc = String::from_utf8_lossy(&fs::read(dump_path.join("toc.dat"))?).to_string();
+    assert!(toc.contains("COPY public.nodes (node_id, latitude, longitude, changeset_id, visible, \"timestamp\", tile, version) FROM stdin;"));
+    assert!(toc.contains("COPY public.user_mutes (id, owner_id) FROM stdin;"));
+
+    let apidb_dump_reader = ApiDbDumpReader::new(dump_path.clone(), tmp_path)?;
+    let file_info = FileInfo::new(
+        None,
+        ["OsmSchema-V0.6", "DenseNodes"].map(|s| s.to_string()).to_vec(),
+        ["Sort.Type_then_ID", "HistoricalInformation"].map(|s| s.to_string()).to_vec(),
+        Some("test-writer".to_string()),
+        Some("from-upgraded-apidb-dump".to_string()),
+        None,
+        None,
+        None,
+    );
+    let mut pbf_writer = PbfWriter::from_file_info(output_path.clone(), file_info, CompressionType::Zlib)?;
+    pbf_writer.write_header()?;
+    for element in apidb_dump_reader.elements()? {
+        pbf_writer.write_element(element)?;
+    }
+    pbf_writer.close()?;
+    common::analyze_pbf_output(output_path, fixture_analysis_path);
+
+    let database_url = match common::test_database_url() {
+        Some(database_url) => database_url,
+        None => {
+            log::warn!("OSM_IO_TEST_DATABASE_URL is not set, skipping the database part");
+            return Ok(());
+        }
+    };
+
+    // the generated TOC restores the upgraded schema with the data
+    let mut client = Client::connect(&database_url, NoTls)?;
+    client.batch_execute("DROP SCHEMA IF EXISTS public CASCADE; CREATE SCHEMA public;")?;
+    let status = Command::new("pg_restore")
+        .args(["--exit-on-error", "--no-owner", "-d", &database_url])
+        .arg(&dump_path)
+        .status()?;
+    assert!(status.success());
+    assert_eq!(count(&mut client, "SELECT count(*) FROM public.nodes")?, fixture_analysis["data"]["count"]["nodes"].as_i64().unwrap());
+    assert_eq!(count(&mut client, "SELECT count(*) FROM public.users WHERE company = ''")?, count(&mut client, "SELECT count(*) FROM public.users")?);
+    assert!(count(&mut client, "SELECT count(*) FROM pg_constraint WHERE conname = 'ways_redaction_id_fkey'")? == 1);
+
+    // the database writer adapts to the schema of the database
+    client.batch_execute("DROP SCHEMA IF EXISTS public CASCADE; CREATE SCHEMA public;")?;
+    client.batch_execute(&structure_sql)?;
+    let mut database_writer = DatabaseWriter::new(&database_url)?;
+    for element in pbf_reader.elements()? {
+        database_writer.write_element(element)?;
+    }
+    database_writer.close()?;
+    assert_eq!(count(&mut client, "SELECT count(*) FROM public.nodes")?, fixture_analysis["data"]["count"]["nodes"].as_i64().unwrap());
+    assert_eq!(count(&mut client, "SELECT count(*) FROM public.ways")?, fixture_analysis["data"]["count"]["ways"].as_i64().unwrap());
+    Ok(())
+}
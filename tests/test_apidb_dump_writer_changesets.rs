@@ -0,0 +1,134 @@
+#![cfg(feature = "apidb")]
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::apidb_dump::write::writer::Writer as ApiDbDumpWriter;
+use osm_io::osm::model::bounding_box::BoundingBox;
+use osm_io::osm::model::changeset::Changeset;
+use osm_io::osm::model::element::Element;
+use osm_io::osm::model::tag::Tag;
+use osm_io::osm::pbf::reader::Reader as PbfReader;
+
+mod common;
+
+fn read_table_lines(path: PathBuf) -> Vec<Vec<String>> {
+    fs::read_to_string(&path).unwrap()
+        .lines()
+        .filter(|line| *line != "\\.")
+        .map(|line| line.split('\t').map(|s| s.to_string()).collect())
+        .collect()
+}
+
+#[test]
+fn test_apidb_dump_writer_changesets() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let output_path = PathBuf::from("./target/results/changesets-niue-230109");
+
+    // changeset id -> (min timestamp, max timestamp, number of changes, uid of the first element)
+    let mut expected: HashMap<i64, (i64, i64, i64, i32)> = HashMap::new();
+    let pbf_reader = PbfReader::new(&input_path)?;
+    for element in pbf_reader.elements()? {
+        let (changeset, timestamp, uid) = match &element {
+            Element::Node { node } => (node.changeset(), node.timestamp(), node.uid()),
+            Element::Way { way } => (way.changeset(), way.timestamp(), way.uid()),
+            Element::Relation { relation } => (relation.changeset(), relation.timestamp(), relation.uid()),
+            Element::Sentinel => continue,
+        };
+        let e = expected.entry(changeset).or_insert((timestamp, timestamp, 0, uid));
+        e.0 = e.0.min(timestamp);
+        e.1 = e.1.max(timestamp);
+        e.2 += 1;
+    }
+    let supplied_id = *expected.keys().min().unwrap();
+    // a changeset supplied without a uid and user
+    let anonymous_id = *expected.keys().max().unwrap();
+    let user_ids: Vec<String> = pbf_reader.elements()?
+        .filter_map(|element| match element {
+            Element::Node { node } => Some(node.uid()),
+            Element::Way { way } => Some(way.uid()),
+            Element::Relation { relation } => Some(relation.uid()),
+            Element::Sentinel => None,
+        })
+        .map(|uid| uid.to_string())
+        .collect();
+    assert!(!user_ids.contains(&"7".to_string()));
+
+    let mut writer = ApiDbDumpWriter::new(output_path.clone(), 0)?;
+    writer.add_changesets(
+        vec![
+            Changeset::new(
+                supplied_id,
+                1240388822000,
+                Some(1240392423000),
+                false,
+                7,
+                "alice".to_string(),
+                Some(BoundingBox::new(-169.9211, -19.0594, -169.8952, -19.0346)),
+                42,
+                vec![Tag::new("comment".to_string(), "Roads\tand paths".to_string())],
+            ),
+            Changeset::new(
+                anonymous_id,
+                1240388822000,
+                None,
+                false,
+                -1,
+                "".to_string(),
+                None,
+                1,
+                vec![],
+            ),
+        ]
+    )?;
+    for element in pbf_reader.elements()? {
+        writer.write_element(element)?;
+    }
+    writer.close()?;
+
+    // public.changesets (id, user_id, created_at, min_lat, max_lat, min_lon, max_lon, closed_at, num_changes)
+    let changesets = read_table_lines(output_path.join("4222.dat"));
+    assert_eq!(changesets.len(), expected.len());
+    for changeset in &changesets {
+        let id: i64 = changeset[0].parse()?;
+        if id == supplied_id {
+            assert_eq!(changeset[1..], ["7", "2009-04-22 08:27:02", "-190594000", "-190346000", "-1699211000", "-1698952000", "2009-04-22 09:27:03", "42"]);
+        } else if id == anonymous_id {
+            assert_eq!(changeset[1], expected[&id].3.to_string());
+            assert_eq!(changeset[8], "1");
+        } else {
+            let (min_timestamp, max_timestamp, num_changes, _) = expected[&id];
+            let created_at = chrono::DateTime::from_timestamp_millis(min_timestamp).unwrap().format("%Y-%m-%d %H:%M:%S").to_string();
+            let closed_at = chrono::DateTime::from_timestamp_millis(max_timestamp).unwrap().format("%Y-%m-%d %H:%M:%S").to_string();
+            assert_eq!(changeset[2], created_at);
+            assert_eq!(changeset[7], closed_at);
+            assert_eq!(changeset[8], num_changes.to_string());
+        }
+    }
+
+    // public.changeset_tags (changeset_id, k, v)
+    let changeset_tags = read_table_lines(output_path.join("4221.dat"));
+    let supplied_tags: Vec<&Vec<String>> = changeset_tags.iter()
+        .filter(|changeset_tag| changeset_tag[0] == supplied_id.to_string())
+        .collect();
+    assert_eq!(supplied_tags.len(), 1);
+    assert_eq!(supplied_tags[0][1..], ["comment", "Roads\\tand\\ paths"]);
+
+    // public.users (email, id, pass_crypt, creation_time, display_name, ...), the user of the
+    // supplied changeset has no elements but is referenced by changesets_user_id_fkey
+    let users = read_table_lines(output_path.join("4290.dat"));
+    let supplied_users: Vec<&Vec<String>> = users.iter()
+        .filter(|user| user[1] == "7")
+        .collect();
+    assert_eq!(supplied_users.len(), 1);
+    assert_eq!(supplied_users[0][4], "alice");
+    for changeset in &changesets {
+        assert!(users.iter().any(|user| user[1] == changeset[1]), "missing user {}", changeset[1]);
+    }
+    Ok(())
+}
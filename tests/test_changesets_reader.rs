@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use osm_io::osm::changesets::reader::Reader;
+use osm_io::osm::model::changeset::Changeset;
+
+mod common;
+
+#[test]
+fn test_changesets_reader() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/changesets.osm");
+    let reader = Reader::new(&input_path)?;
+    let changesets: Vec<Changeset> = reader.changesets()?.collect();
+    assert_eq!(changesets.len(), 4);
+
+    let changeset = &changesets[0];
+    assert_eq!(changeset.id(), 10);
+    assert_eq!(changeset.created_at(), 1240388822000);
+    assert_eq!(changeset.closed_at(), Some(1240392423000));
+    assert!(!changeset.open());
+    assert_eq!(changeset.uid(), 101);
+    assert_eq!(changeset.user(), "alice");
+    assert_eq!(changeset.num_changes(), 42);
+    let bounding_box = changeset.bounding_box().unwrap();
+    assert_eq!(bounding_box.left(), -169.9211);
+    assert_eq!(bounding_box.bottom(), -19.0594);
+    assert_eq!(bounding_box.right(), -169.8952);
+    assert_eq!(bounding_box.top(), -19.0346);
+    assert_eq!(changeset.tags().len(), 2);
+    assert_eq!(changeset.tags()[0].k(), "created_by");
+    assert_eq!(changeset.tags()[0].v(), "JOSM/1.5 (1515 en)");
+    assert_eq!(changeset.tags()[1].v(), "Roads & \"paths\" <Alofi>");
+
+    let changeset = &changesets[1];
+    assert_eq!(changeset.id(), 11);
+    assert!(changeset.bounding_box().is_none());
+    assert!(changeset.tags().is_empty());
+
+    // the discussion is skipped
+    let changeset = &changesets[2];
+    assert_eq!(changeset.id(), 12);
+    assert!(changeset.open());
+    assert_eq!(changeset.closed_at(), None);
+    assert_eq!(changeset.num_changes(), 3);
+    assert_eq!(changeset.tags().len(), 1);
+    assert_eq!(changeset.tags()[0].v(), "work in progress");
+
+    // an anonymous changeset
+    let changeset = &changesets[3];
+    assert_eq!(changeset.id(), 13);
+    assert_eq!(changeset.uid(), -1);
+    assert!(changeset.user().is_empty());
+    Ok(())
+}
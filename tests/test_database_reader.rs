@@ -0,0 +1,122 @@
+#![cfg(feature = "apidb")]
+
+use std::path::PathBuf;
+
+use postgres::{Client, NoTls};
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::apidb_dump::read::database_reader::DatabaseReader;
+use osm_io::osm::apidb_dump::read::reader_options::ReaderOptions;
+use osm_io::osm::apidb_dump::read::table_set::TableSet;
+use osm_io::osm::apidb_dump::write::database_writer::DatabaseWriter;
+use osm_io::osm::model::element::Element;
+use osm_io::osm::model::tag::Tag;
+use osm_io::osm::pbf::reader::Reader as PbfReader;
+
+mod common;
+
+fn sorted_tags(tags: &[Tag]) -> Vec<(String, String)> {
+    let mut tags: Vec<(String, String)> = tags.iter().map(|tag| (tag.k().clone(), tag.v().clone())).collect();
+    tags.sort();
+    tags
+}
+
+/// Requires a scratch database with the btree_gist extension available. The public schema of the
+/// database is recreated by the test. Skipped if OSM_IO_TEST_DATABASE_URL is not set
+#[test]
+fn test_database_reader() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let database_url = match common::test_database_url() {
+        Some(database_url) => database_url,
+        None => {
+            log::warn!("OSM_IO_TEST_DATABASE_URL is not set, skipping");
+            return Ok(());
+        }
+    };
+    let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let mut client = Client::connect(&database_url, NoTls)?;
+    common::create_apidb_schema(&mut client)?;
+
+    let pbf_reader = PbfReader::new(&input_path)?;
+    let mut database_writer = DatabaseWriter::new(&database_url)?;
+    for element in pbf_reader.elements()? {
+        database_writer.write_element(element)?;
+    }
+    database_writer.close()?;
+
+    let database_reader = DatabaseReader::new(&database_url)?;
+    let elements = database_reader.elements()?;
+    // the snapshot of the reader hides changes made after it was created
+    client.batch_execute("UPDATE public.node_tags SET v = 'changed'")?;
+
+    let mut expected_elements = pbf_reader.elements()?;
+    let mut count = 0;
+    for actual in elements.filter(|element| !matches!(element, Element::Sentinel)) {
+        let expected = expected_elements.next().unwrap();
+        count += 1;
+        match (&expected, &actual) {
+            (Element::Node { node: expected }, Element::Node { node: actual }) => {
+                assert_eq!(expected.id(), actual.id());
+                assert_eq!(expected.version(), actual.version());
+                assert_eq!(expected.timestamp(), actual.timestamp());
+                assert_eq!(expected.changeset(), actual.changeset());
+                assert_eq!(expected.visible(), actual.visible());
+                assert_eq!(expected.coordinate().lat7(), actual.coordinate().lat7());
+                assert_eq!(expected.coordinate().lon7(), actual.coordinate().lon7());
+                assert_eq!(sorted_tags(expected.tags()), sorted_tags(actual.tags()));
+            }
+            (Element::Way { way: expected }, Element::Way { way: actual }) => {
+                assert_eq!(expected.id(), actual.id());
+                assert_eq!(expected.version(), actual.version());
+                assert_eq!(expected.timestamp(), actual.timestamp());
+                assert_eq!(expected.changeset(), actual.changeset());
+                assert_eq!(expected.visible(), actual.visible());
+                assert_eq!(expected.refs(), actual.refs());
+                assert_eq!(sorted_tags(expected.tags()), sorted_tags(actual.tags()));
+            }
+            (Element::Relation { relation: expected }, Element::Relation { relation: actual }) => {
+                assert_eq!(expected.id(), actual.id());
+                assert_eq!(expected.version(), actual.version());
+                assert_eq!(expected.timestamp(), actual.timestamp());
+                assert_eq!(expected.changeset(), actual.changeset());
+                assert_eq!(expected.visible(), actual.visible());
+                assert_eq!(expected.members(), actual.members());
+                assert_eq!(sorted_tags(expected.tags()), sorted_tags(actual.tags()));
+            }
+            _ => {
+                panic!("Element type mismatch, expected: {:?}, actual: {:?}", expected, actual);
+            }
+        }
+    }
+    assert!(expected_elements.next().is_none());
+    assert!(count > 0);
+
+    // the current tables hold the same elements as the non-history extract
+    let fixture_analysis = common::read_fixture_analysis(&PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json"));
+    let mut options = ReaderOptions::default();
+    options.with_table_set(TableSet::Current);
+    let database_reader = DatabaseReader::from_options(&database_url, options)?;
+    let (mut nodes, mut ways, mut relations) = (0, 0, 0);
+    for element in database_reader.elements()? {
+        match element {
+            Element::Node { node } => {
+                assert!(node.visible());
+                nodes += 1;
+            }
+            Element::Way { way } => {
+                assert!(way.visible());
+                ways += 1;
+            }
+            Element::Relation { relation } => {
+                assert!(relation.visible());
+                relations += 1;
+            }
+            Element::Sentinel => {}
+        }
+    }
+    assert_eq!(nodes, fixture_analysis["data"]["count"]["nodes"].as_i64().unwrap());
+    assert_eq!(ways, fixture_analysis["data"]["count"]["ways"].as_i64().unwrap());
+    assert_eq!(relations, fixture_analysis["data"]["count"]["relations"].as_i64().unwrap());
+    Ok(())
+}
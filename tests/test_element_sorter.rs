@@ -0,0 +1,54 @@
+use std::path::PathBuf;
+
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::element_sorter::ElementSorter;
+use osm_io::osm::pbf::reader::Reader;
+use osm_io::osm::pbf::writer::Writer;
+
+mod common;
+
+#[test]
+fn test_element_sorter() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let output_path = PathBuf::from("./target/results/sorted-niue-230109.osm.pbf");
+    let tmp_path = PathBuf::from("./target/results/element-sorter-tmp");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+
+    let reader = Reader::new(&input_path)?;
+    let mut elements: Vec<Element> = reader.elements()?.collect();
+    elements.shuffle(&mut thread_rng());
+
+    let mut sorter = ElementSorter::new(tmp_path.clone(), 1024 * 1024)?;
+    sorter.add_elements(elements)?;
+    assert!(sorter.runs() > 1);
+
+    let mut file_info = reader.info().clone();
+    file_info.with_writingprogram_str("element-sorter-test-writer");
+    let mut writer = Writer::from_file_info(
+        output_path.clone(),
+        file_info,
+        CompressionType::Zlib,
+    )?;
+    writer.write_header()?;
+    for element in sorter.sort()? {
+        writer.write_element(element)?;
+    }
+    writer.close()?;
+
+    assert_eq!(std::fs::read_dir(&tmp_path)?.count(), 0);
+
+    let sorted_elements: Vec<Element> = Reader::new(&output_path)?.elements()?.collect();
+    assert!(sorted_elements.windows(2).all(|pair| pair[0] <= pair[1]));
+    let original_elements: Vec<Element> = reader.elements()?.collect();
+    assert_eq!(sorted_elements, original_elements);
+
+    common::analyze_pbf_output(output_path, fixture_analysis_path);
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::merger::Merger;
+use osm_io::osm::pbf::reader::Reader;
+use osm_io::osm::pbf::writer::Writer;
+
+mod common;
+
+fn write_part(reader: &Reader, output_path: &Path, filter: impl Fn(usize) -> bool) -> Result<(), anyhow::Error> {
+    let mut writer = Writer::from_file_info(
+        output_path.to_path_buf(),
+        reader.info().clone(),
+        CompressionType::Zlib,
+    )?;
+    writer.write_header()?;
+    for (i, element) in reader.elements()?.enumerate() {
+        if filter(i) {
+            writer.write_element(element)?;
+        }
+    }
+    writer.close()
+}
+
+#[test]
+fn test_merger() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let first_part_path = PathBuf::from("./target/results/merger-part-1-niue-230109.osm.pbf");
+    let second_part_path = PathBuf::from("./target/results/merger-part-2-niue-230109.osm.pbf");
+    let output_path = PathBuf::from("./target/results/merged-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+
+    let reader = Reader::new(&input_path)?;
+    write_part(&reader, &first_part_path, |i| i % 3 != 0)?;
+    write_part(&reader, &second_part_path, |i| i % 3 != 1)?;
+
+    let merger = Merger::new(&[first_part_path, second_part_path])?;
+    assert_eq!(
+        merger.file_info().bounding_box().as_ref().map(|b| b.to_string()),
+        reader.info().bounding_box().as_ref().map(|b| b.to_string())
+    );
+    merger.merge(output_path.clone(), CompressionType::Zlib)?;
+
+    common::analyze_pbf_output(output_path, fixture_analysis_path);
+    Ok(())
+}
+
+#[test]
+fn test_merger_history() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let all_versions_path = PathBuf::from("./target/results/merged-all-versions-history-niue-230109.osm.pbf");
+    let latest_versions_path = PathBuf::from("./target/results/merged-latest-versions-history-niue-230109.osm.pbf");
+    let visible_versions_path = PathBuf::from("./target/results/merged-visible-versions-history-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf.analysis.json");
+
+    let mut merger = Merger::new(&[input_path.clone(), input_path.clone()])?;
+    merger.with_keep_all_versions(true);
+    merger.merge(all_versions_path.clone(), CompressionType::Zlib)?;
+    common::analyze_pbf_output(all_versions_path, fixture_analysis_path);
+
+    let merger = Merger::new(&[input_path.clone(), input_path.clone()])?;
+    merger.merge(latest_versions_path.clone(), CompressionType::Zlib)?;
+    let (ids, deleted_ids) = latest_versions(&latest_versions_path, true)?;
+    assert!(!ids.is_empty());
+    // the deletions are kept by default, with the visible flag required to tell them apart
+    assert!(!deleted_ids.is_empty());
+
+    let mut merger = Merger::new(&[input_path.clone(), input_path.clone()])?;
+    merger.with_drop_deleted(true);
+    merger.merge(visible_versions_path.clone(), CompressionType::Zlib)?;
+    let (visible_ids, visible_deleted_ids) = latest_versions(&visible_versions_path, false)?;
+    assert!(visible_deleted_ids.is_empty());
+    assert_eq!(visible_ids, ids.difference(&deleted_ids).cloned().collect());
+    Ok(())
+}
+
+// element type and id
+type ElementIds = HashSet<(i32, i64)>;
+
+/// The ids of the elements of a file with a single version of each element, and the ids of the
+/// deleted elements among them
+fn latest_versions(path: &Path, historical_information: bool) -> Result<(ElementIds, ElementIds), anyhow::Error> {
+    let reader = Reader::new(path)?;
+    assert_eq!(reader.info().required_features().contains(&"HistoricalInformation".to_string()), historical_information);
+    let mut ids = HashSet::new();
+    let mut deleted_ids = HashSet::new();
+    for element in reader.elements()? {
+        let (id, visible) = match &element {
+            Element::Node { node } => {
+                ((0, node.id()), node.visible())
+            }
+            Element::Way { way } => {
+                ((1, way.id()), way.visible())
+            }
+            Element::Relation { relation } => {
+                ((2, relation.id()), relation.visible())
+            }
+            Element::Sentinel => {
+                continue;
+            }
+        };
+        assert!(ids.insert(id));
+        if !visible {
+            deleted_ids.insert(id);
+        }
+    }
+    Ok((ids, deleted_ids))
+}
@@ -0,0 +1,244 @@
+use std::path::PathBuf;
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::coordinate::Coordinate;
+use osm_io::osm::model::element::Element;
+use osm_io::osm::model::node::Node;
+use osm_io::osm::model::tag::Tag;
+use osm_io::osm::model::way::Way;
+use osm_io::osm::o5m;
+use osm_io::osm::pbf;
+use osm_io::osm::pbf::file_info::FileInfo;
+
+mod common;
+
+#[test]
+fn test_pbf_o5m_round_trip() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    for (input_path, output_path) in [
+        (PathBuf::from("./tests/fixtures/niue-230109.osm.pbf"), PathBuf::from("./target/results/niue-230109.o5m")),
+        (PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf"), PathBuf::from("./target/results/history-niue-230109.o5m")),
+    ] {
+        let reader = pbf::reader::Reader::new(&input_path)?;
+        let mut writer = o5m::writer::Writer::new(output_path.clone(), reader.info().clone())?;
+        writer.write_header()?;
+        for element in reader.elements()? {
+            writer.write_element(element)?;
+        }
+        writer.close()?;
+
+        let o5m_reader = o5m::reader::Reader::new(&output_path)?;
+        assert!(!o5m_reader.is_change());
+        assert_eq!(o5m_reader.info().osmosis_replication_timestamp(), reader.info().osmosis_replication_timestamp());
+        match (o5m_reader.info().bounding_box(), reader.info().bounding_box()) {
+            (Some(actual), Some(expected)) => {
+                assert!((actual.left() - expected.left()).abs() < 1E-7);
+                assert!((actual.bottom() - expected.bottom()).abs() < 1E-7);
+                assert!((actual.right() - expected.right()).abs() < 1E-7);
+                assert!((actual.top() - expected.top()).abs() < 1E-7);
+            }
+            (None, None) => {}
+            _ => {
+                panic!("Bounding box mismatch");
+            }
+        }
+
+        let expected = reader.elements()?.collect::<Vec<Element>>();
+        let actual = o5m_reader.elements()?.collect::<Vec<Element>>();
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual.iter()) {
+            match (expected, actual) {
+                (Element::Node { node: expected }, Element::Node { node: actual }) => {
+                    assert_eq!(expected.id(), actual.id());
+                    assert_eq!(expected.version(), actual.version());
+                    assert_eq!(expected.visible(), actual.visible());
+                    // deleted nodes have no coordinates in o5m
+                    if expected.visible() {
+                        assert_eq!(expected.coordinate().lat7(), actual.coordinate().lat7());
+                        assert_eq!(expected.coordinate().lon7(), actual.coordinate().lon7());
+                    }
+                    assert_eq!(expected.timestamp(), actual.timestamp());
+                    assert_eq!(expected.changeset(), actual.changeset());
+                    assert_eq!(expected.uid(), actual.uid());
+                    assert_eq!(expected.user(), actual.user());
+                    assert_eq!(expected.tags(), actual.tags());
+                }
+                (Element::Way { way: expected }, Element::Way { way: actual }) => {
+                    assert_eq!(expected.id(), actual.id());
+                    assert_eq!(expected.version(), actual.version());
+                    assert_eq!(expected.timestamp(), actual.timestamp());
+                    assert_eq!(expected.changeset(), actual.changeset());
+                    assert_eq!(expected.uid(), actual.uid());
+                    assert_eq!(expected.user(), actual.user());
+                    assert_eq!(expected.refs(), actual.refs());
+                    assert_eq!(expected.tags(), actual.tags());
+                }
+                (Element::Relation { relation: expected }, Element::Relation { relation: actual }) => {
+                    assert_eq!(expected.id(), actual.id());
+                    assert_eq!(expected.version(), actual.version());
+                    assert_eq!(expected.timestamp(), actual.timestamp());
+                    assert_eq!(expected.changeset(), actual.changeset());
+                    assert_eq!(expected.uid(), actual.uid());
+                    assert_eq!(expected.user(), actual.user());
+                    assert_eq!(expected.members(), actual.members());
+                    assert_eq!(expected.tags(), actual.tags());
+                }
+                _ => {
+                    panic!("Element type mismatch");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_o5c_deleted_elements() -> Result<(), anyhow::Error> {
+    common::setup();
+    let output_path = PathBuf::from("./target/results/change.o5c");
+    let mut writer = o5m::writer::Writer::new(output_path.clone(), FileInfo::default())?;
+    writer.with_change(true);
+    writer.write_header()?;
+    writer.write_elements(vec![
+        Element::Node {
+            node: Node::new(1, 2, Coordinate::new(-19.05, -169.9), 1673258400000, 10, 7, "Alice".to_string(), true, vec![Tag::new("amenity".to_string(), "cafe".to_string())])
+        },
+        Element::Node {
+            node: Node::new(2, 3, Coordinate::new(0.0, 0.0), 1673258401000, 11, 7, "Alice".to_string(), false, vec![])
+        },
+        Element::Way {
+            way: Way::new(5, 4, 1673258402000, 12, 8, "Bob".to_string(), false, vec![], vec![])
+        },
+        Element::Way {
+            way: Way::new(6, 1, 1673258403000, 13, -1, "".to_string(), true, vec![1], vec![])
+        },
+        Element::Sentinel,
+    ])?;
+    writer.close()?;
+
+    let reader = o5m::reader::Reader::new(&output_path)?;
+    assert!(reader.is_change());
+    let elements = reader.elements()?.collect::<Vec<Element>>();
+    assert_eq!(elements.len(), 4);
+    match &elements[0] {
+        Element::Node { node } => {
+            assert!(node.visible());
+            assert_eq!(node.uid(), 7);
+            assert_eq!(node.user(), "Alice");
+            assert_eq!(node.tags()[0].v(), "cafe");
+        }
+        _ => {
+            panic!("Expected a node");
+        }
+    }
+    match &elements[1] {
+        Element::Node { node } => {
+            assert!(!node.visible());
+            assert_eq!(node.version(), 3);
+            assert_eq!(node.changeset(), 11);
+            assert_eq!(node.user(), "Alice");
+        }
+        _ => {
+            panic!("Expected a node");
+        }
+    }
+    match &elements[2] {
+        Element::Way { way } => {
+            assert!(!way.visible());
+            assert_eq!(way.id(), 5);
+            assert_eq!(way.timestamp(), 1673258402000);
+            assert_eq!(way.user(), "Bob");
+        }
+        _ => {
+            panic!("Expected a way");
+        }
+    }
+    match &elements[3] {
+        Element::Way { way } => {
+            // no uid
+            assert_eq!(way.uid(), -1);
+            assert_eq!(way.user(), "");
+        }
+        _ => {
+            panic!("Expected a way");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_o5m_missing_timestamp() -> Result<(), anyhow::Error> {
+    common::setup();
+    let output_path = PathBuf::from("./target/results/missing-timestamp.o5m");
+    let mut writer = o5m::writer::Writer::new(output_path.clone(), FileInfo::default())?;
+    writer.write_header()?;
+    writer.write_elements(vec![
+        Element::Node {
+            node: Node::new(1, 0, Coordinate::new(-19.05, -169.9), -1, -1, -1, "".to_string(), true, vec![])
+        },
+        Element::Node {
+            node: Node::new(2, 3, Coordinate::new(-19.06, -169.8), -1, 11, 7, "Alice".to_string(), true, vec![])
+        },
+        Element::Way {
+            way: Way::new(5, 4, -1, 12, 8, "Bob".to_string(), true, vec![1, 2], vec![])
+        },
+        Element::Sentinel,
+    ])?;
+    writer.close()?;
+
+    let elements = o5m::reader::Reader::new(&output_path)?.elements()?.collect::<Vec<Element>>();
+    assert_eq!(elements.len(), 3);
+    // without a timestamp o5m has no changeset and author either
+    for (element, expected_version) in elements.iter().zip([0, 3, 4]) {
+        match element {
+            Element::Node { node } => {
+                assert_eq!(node.version(), expected_version);
+                assert_eq!(node.timestamp(), -1);
+                assert_eq!(node.changeset(), -1);
+                assert_eq!(node.uid(), -1);
+            }
+            Element::Way { way } => {
+                assert_eq!(way.version(), expected_version);
+                assert_eq!(way.timestamp(), -1);
+                assert_eq!(way.changeset(), -1);
+                assert_eq!(way.refs(), &vec![1, 2]);
+            }
+            _ => {
+                panic!("Unexpected element type");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_o5m_single_byte_datasets() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let output_path = PathBuf::from("./target/results/single-byte-datasets-niue-230109.o5m");
+    let marked_path = PathBuf::from("./target/results/marked-single-byte-datasets-niue-230109.o5m");
+    let reader = pbf::reader::Reader::new(&input_path)?;
+    let mut writer = o5m::writer::Writer::new(output_path.clone(), reader.info().clone())?;
+    writer.write_header()?;
+    for element in reader.elements()? {
+        writer.write_element(element)?;
+    }
+    writer.close()?;
+
+    // the types 0xf0 to 0xfd have no length and no payload, insert them after the reset and the
+    // "o5m2" header datasets
+    let mut data = std::fs::read(&output_path)?;
+    assert_eq!(&data[..7], b"\xff\xe0\x04o5m2");
+    data.splice(7..7, 0xf0_u8..=0xfd);
+    std::fs::write(&marked_path, data)?;
+
+    let expected = o5m::reader::Reader::new(&output_path)?.elements()?.collect::<Vec<Element>>();
+    let actual = o5m::reader::Reader::new(&marked_path)?.elements()?.collect::<Vec<Element>>();
+    assert_eq!(expected.len(), actual.len());
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        assert_eq!(format!("{:?}", expected), format!("{:?}", actual));
+    }
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::model::relation::Member;
+use osm_io::osm::opl;
+use osm_io::osm::pbf;
+
+mod common;
+
+#[test]
+fn test_opl_reader() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/small.opl");
+    let reader = opl::reader::Reader::new(&input_path)?;
+    let elements = reader.elements()?.collect::<Vec<Element>>();
+    assert_eq!(elements.len(), 6);
+    match &elements[0] {
+        Element::Node { node } => {
+            assert_eq!(node.id(), 1);
+            assert_eq!(node.user(), "Alice");
+            assert_eq!(node.timestamp(), 1673258400000);
+            assert_eq!(node.coordinate().lon(), -169.9);
+            assert_eq!(node.coordinate().lat(), -19.05);
+            assert_eq!(node.tags()[1].v(), "Café Central");
+        }
+        _ => {
+            panic!("Expected a node");
+        }
+    }
+    match &elements[2] {
+        Element::Node { node } => {
+            assert_eq!(node.version(), 1);
+            assert_eq!(node.changeset(), -1);
+            assert!(node.user().is_empty());
+            assert!(node.visible());
+        }
+        _ => {
+            panic!("Expected a node");
+        }
+    }
+    match &elements[3] {
+        Element::Way { way } => {
+            assert_eq!(way.refs(), &vec![1, 2, 3]);
+            assert_eq!(way.tags()[1].v(), "Main Street");
+        }
+        _ => {
+            panic!("Expected a way");
+        }
+    }
+    match &elements[4] {
+        Element::Relation { relation } => {
+            assert_eq!(relation.members().len(), 3);
+            match &relation.members()[2] {
+                Member::Relation { member } => {
+                    assert_eq!(member.id(), 21);
+                    assert_eq!(member.role(), "sub area");
+                }
+                _ => {
+                    panic!("Expected a relation member");
+                }
+            }
+        }
+        _ => {
+            panic!("Expected a relation");
+        }
+    }
+    match &elements[5] {
+        Element::Relation { relation } => {
+            assert!(!relation.visible());
+            assert!(relation.members().is_empty());
+            assert!(relation.tags().is_empty());
+        }
+        _ => {
+            panic!("Expected a relation");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pbf_opl_round_trip() -> Result<(), anyhow::Error> {
+    common::setup();
+    for (input_path, output_path) in [
+        (PathBuf::from("./tests/fixtures/niue-230109.osm.pbf"), PathBuf::from("./target/results/niue-230109.opl")),
+        (PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf"), PathBuf::from("./target/results/history-niue-230109.opl")),
+    ] {
+        let reader = pbf::reader::Reader::new(&input_path)?;
+        let mut writer = opl::writer::Writer::new(output_path.clone())?;
+        writer.write_header()?;
+        for element in reader.elements()? {
+            writer.write_element(element)?;
+        }
+        writer.close()?;
+
+        let expected = reader.elements()?.collect::<Vec<Element>>();
+        let actual = opl::reader::Reader::new(&output_path)?.elements()?.collect::<Vec<Element>>();
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual.iter()) {
+            match (expected, actual) {
+                (Element::Node { node: expected }, Element::Node { node: actual }) => {
+                    assert_eq!(expected.id(), actual.id());
+                    assert_eq!(expected.version(), actual.version());
+                    assert_eq!(expected.coordinate().lat7(), actual.coordinate().lat7());
+                    assert_eq!(expected.coordinate().lon7(), actual.coordinate().lon7());
+                    assert_eq!(expected.timestamp(), actual.timestamp());
+                    assert_eq!(expected.changeset(), actual.changeset());
+                    assert_eq!(expected.uid(), actual.uid());
+                    assert_eq!(expected.user(), actual.user());
+                    assert_eq!(expected.visible(), actual.visible());
+                    assert_eq!(expected.tags(), actual.tags());
+                }
+                (Element::Way { way: expected }, Element::Way { way: actual }) => {
+                    assert_eq!(expected.id(), actual.id());
+                    assert_eq!(expected.version(), actual.version());
+                    assert_eq!(expected.timestamp(), actual.timestamp());
+                    assert_eq!(expected.user(), actual.user());
+                    assert_eq!(expected.visible(), actual.visible());
+                    assert_eq!(expected.refs(), actual.refs());
+                    assert_eq!(expected.tags(), actual.tags());
+                }
+                (Element::Relation { relation: expected }, Element::Relation { relation: actual }) => {
+                    assert_eq!(expected.id(), actual.id());
+                    assert_eq!(expected.version(), actual.version());
+                    assert_eq!(expected.timestamp(), actual.timestamp());
+                    assert_eq!(expected.user(), actual.user());
+                    assert_eq!(expected.visible(), actual.visible());
+                    assert_eq!(expected.members(), actual.members());
+                    assert_eq!(expected.tags(), actual.tags());
+                }
+                _ => {
+                    panic!("Element type mismatch");
+                }
+            }
+        }
+    }
+    Ok(())
+}
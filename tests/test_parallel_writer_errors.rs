@@ -0,0 +1,140 @@
+use std::fs::File;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+
+use osm_io::osm::model::coordinate::Coordinate;
+use osm_io::osm::model::element::Element;
+use osm_io::osm::model::node::Node;
+use osm_io::osm::model::relation::Relation;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::file_info::FileInfo;
+use osm_io::osm::pbf::parallel_writer::ParallelWriter;
+use osm_io::osm::pbf::parallel_writer_options::ParallelWriterOptions;
+
+mod common;
+
+fn node(id: i64) -> Element {
+    Element::Node {
+        node: Node::new(id, 1, Coordinate::new(1.0, 1.0), 0, 1, 1, "user".to_string(), true, vec![]),
+    }
+}
+
+fn relation(id: i64) -> Element {
+    Element::Relation {
+        relation: Relation::new(id, 1, 0, 1, 1, "user".to_string(), true, vec![], vec![]),
+    }
+}
+
+fn small_options() -> ParallelWriterOptions {
+    let mut options = ParallelWriterOptions::default();
+    options.with_element_ordering_buffer_size(1);
+    options.with_file_block_size(1);
+    options.with_encoding_tasks(2);
+    options.with_element_ordering_queue_size(2);
+    options.with_encoding_queue_size(2);
+    options.with_writing_queue_size(2);
+    options
+}
+
+#[test]
+fn test_parallel_writer_order_error() -> Result<(), anyhow::Error> {
+    common::setup();
+    let output_path = PathBuf::from("./target/results/parallel-writer-order-error.osm.pbf");
+    let mut writer = ParallelWriter::from_options(
+        output_path,
+        FileInfo::default(),
+        CompressionType::Zlib,
+        small_options(),
+    )?;
+    writer.write_header()?;
+    writer.write_element(relation(10))?;
+    writer.write_element(relation(11))?;
+    writer.write_element(relation(12))?;
+    // the error is reported by one of the following calls
+    let result = writer.write_element(node(1))
+        .and_then(|_| writer.write_element(node(2)))
+        .and_then(|_| writer.close());
+    let error = result.expect_err("Expected an element order error");
+    assert!(error.to_string().contains("Element order"), "{error}");
+    Ok(())
+}
+
+#[test]
+fn test_parallel_writer_missing_header() -> Result<(), anyhow::Error> {
+    common::setup();
+    let output_path = PathBuf::from("./target/results/parallel-writer-missing-header.osm.pbf");
+    let mut writer = ParallelWriter::from_options(
+        output_path,
+        FileInfo::default(),
+        CompressionType::Zlib,
+        small_options(),
+    )?;
+    for id in 1..100 {
+        // errors are reported either here or on close
+        if writer.write_element(node(id)).is_err() {
+            break;
+        }
+    }
+    assert!(writer.close().is_err());
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_parallel_writer_write_error() -> Result<(), anyhow::Error> {
+    let mut writer = ParallelWriter::from_options(
+        PathBuf::from("/dev/full"),
+        FileInfo::default(),
+        CompressionType::Zlib,
+        small_options(),
+    )?;
+    assert!(writer.write_header().is_err());
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_parallel_writer_blob_write_error() -> Result<(), anyhow::Error> {
+    common::setup();
+    // the header is written to a pipe while it has a reader, the blobs after the reader is gone
+    let output_path = PathBuf::from("./target/results/parallel-writer-blob-write-error.fifo");
+    if output_path.exists() {
+        std::fs::remove_file(&output_path)?;
+    }
+    assert!(Command::new("mkfifo").arg(&output_path).status()?.success());
+    let reader_path = output_path.clone();
+    let reader = thread::spawn(move || File::open(reader_path));
+
+    let mut writer = ParallelWriter::from_options(
+        output_path,
+        FileInfo::default(),
+        CompressionType::Zlib,
+        small_options(),
+    )?;
+    writer.write_header()?;
+    drop(reader.join().unwrap()?);
+
+    for id in 1..100 {
+        // errors are reported either here or on close
+        if writer.write_element(node(id)).is_err() {
+            break;
+        }
+    }
+    let error = writer.close().expect_err("Expected a blob write error");
+    assert!(format!("{error:?}").contains("Failed to write blob"), "{error:?}");
+    Ok(())
+}
+
+#[test]
+fn test_parallel_writer_invalid_options() {
+    let mut options = ParallelWriterOptions::default();
+    options.with_encoding_queue_size(0);
+    let result = ParallelWriter::from_options(
+        PathBuf::from("./target/results/parallel-writer-invalid-options.osm.pbf"),
+        FileInfo::default(),
+        CompressionType::Zlib,
+        options,
+    );
+    assert!(result.is_err());
+}
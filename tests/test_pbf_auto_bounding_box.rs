@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::bounding_box::BoundingBox;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::file_info::FileInfo;
+use osm_io::osm::pbf::parallel_writer::ParallelWriter;
+use osm_io::osm::pbf::parallel_writer_options::ParallelWriterOptions;
+use osm_io::osm::pbf::reader::Reader;
+use osm_io::osm::pbf::writer::Writer;
+use osm_io::osm::pbf::writer_options::WriterOptions;
+
+mod common;
+
+fn auto_bounding_box() -> WriterOptions {
+    let mut options = WriterOptions::default();
+    options.with_auto_bounding_box(true);
+    options
+}
+
+fn file_info_without_bounding_box(reader: &Reader) -> FileInfo {
+    let mut file_info = reader.info().clone();
+    file_info.with_bounding_box(&None);
+    file_info
+}
+
+fn verify_bounding_box(output_path: &Path, fixture_analysis_path: &PathBuf) -> Result<(), anyhow::Error> {
+    let fixture_analysis = common::read_fixture_analysis(fixture_analysis_path);
+    let bounding_box = Reader::new(output_path)?.info().bounding_box().clone().expect("Expected a bounding box");
+    let expected = BoundingBox::new(
+        fixture_analysis["data"]["bbox"][0].as_f64().unwrap(),
+        fixture_analysis["data"]["bbox"][1].as_f64().unwrap(),
+        fixture_analysis["data"]["bbox"][2].as_f64().unwrap(),
+        fixture_analysis["data"]["bbox"][3].as_f64().unwrap(),
+    );
+    assert!((bounding_box.left() - expected.left()).abs() < 1e-7, "{bounding_box} != {expected}");
+    assert!((bounding_box.bottom() - expected.bottom()).abs() < 1e-7, "{bounding_box} != {expected}");
+    assert!((bounding_box.right() - expected.right()).abs() < 1e-7, "{bounding_box} != {expected}");
+    assert!((bounding_box.top() - expected.top()).abs() < 1e-7, "{bounding_box} != {expected}");
+    Ok(())
+}
+
+#[test]
+fn test_pbf_writer_auto_bounding_box() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let output_path = PathBuf::from("./target/results/auto-bbox-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+    let reader = Reader::new(&input_path)?;
+
+    let mut writer = Writer::from_options(
+        output_path.clone(),
+        file_info_without_bounding_box(&reader),
+        CompressionType::Zlib,
+        auto_bounding_box(),
+    )?;
+    writer.write_header()?;
+    for element in reader.elements()? {
+        writer.write_element(element)?;
+    }
+    writer.close()?;
+
+    verify_bounding_box(&output_path, &fixture_analysis_path)?;
+    assert!(reader.elements()?.eq(Reader::new(&output_path)?.elements()?));
+    common::analyze_pbf_output(output_path, fixture_analysis_path);
+    Ok(())
+}
+
+#[test]
+fn test_pbf_parallel_writer_auto_bounding_box() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let output_path = PathBuf::from("./target/results/parallel-auto-bbox-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+    let reader = Reader::new(&input_path)?;
+
+    let mut options = ParallelWriterOptions::default();
+    options.with_writer_options(auto_bounding_box());
+    let mut writer = ParallelWriter::from_options(
+        output_path.clone(),
+        file_info_without_bounding_box(&reader),
+        CompressionType::Uncompressed,
+        options,
+    )?;
+    writer.write_header()?;
+    writer.write_elements(reader.elements()?.collect())?;
+    writer.close()?;
+
+    verify_bounding_box(&output_path, &fixture_analysis_path)?;
+    assert!(reader.elements()?.eq(Reader::new(&output_path)?.elements()?));
+    Ok(())
+}
+
+#[test]
+fn test_pbf_writer_auto_bounding_box_no_nodes() -> Result<(), anyhow::Error> {
+    common::setup();
+    let output_path = PathBuf::from("./target/results/auto-bbox-empty.osm.pbf");
+    let mut writer = Writer::from_options(
+        output_path.clone(),
+        FileInfo::default(),
+        CompressionType::Zlib,
+        auto_bounding_box(),
+    )?;
+    writer.write_header()?;
+    writer.close()?;
+    assert!(Reader::new(&output_path)?.info().bounding_box().is_none());
+    Ok(())
+}
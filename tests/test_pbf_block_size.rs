@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::model::way::Way;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::file_info::FileInfo;
+use osm_io::osm::pbf::parallel_writer::ParallelWriter;
+use osm_io::osm::pbf::parallel_writer_options::ParallelWriterOptions;
+use osm_io::osm::pbf::reader::Reader;
+use osm_io::osm::pbf::writer::Writer;
+use osm_io::osm::pbf::writer_options::WriterOptions;
+
+mod common;
+
+fn small_blocks() -> WriterOptions {
+    let mut options = WriterOptions::default();
+    options.with_max_block_size(64 * 1024);
+    options
+}
+
+#[test]
+fn test_pbf_block_size() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let output_path = PathBuf::from("./target/results/small-blocks-niue-230109.osm.pbf");
+    let parallel_output_path = PathBuf::from("./target/results/parallel-small-blocks-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+    let reader = Reader::new(&input_path)?;
+
+    let mut writer = Writer::from_options(
+        output_path.clone(),
+        reader.info().clone(),
+        CompressionType::Zlib,
+        small_blocks(),
+    )?;
+    writer.write_header()?;
+    for element in reader.elements()? {
+        writer.write_element(element)?;
+    }
+    writer.close()?;
+
+    let mut options = ParallelWriterOptions::default();
+    options.with_writer_options(small_blocks());
+    let mut parallel_writer = ParallelWriter::from_options(
+        parallel_output_path.clone(),
+        reader.info().clone(),
+        CompressionType::Zlib,
+        options,
+    )?;
+    parallel_writer.write_header()?;
+    parallel_writer.write_elements(reader.elements()?.collect())?;
+    parallel_writer.close()?;
+
+    let input_blocks = reader.blocks()?.count();
+    for path in [output_path, parallel_output_path] {
+        assert!(Reader::new(&path)?.blocks()?.count() > input_blocks);
+        let expected = reader.elements()?;
+        let actual = Reader::new(&path)?.elements()?;
+        assert!(expected.eq(actual));
+        common::analyze_pbf_output(path, fixture_analysis_path.clone());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pbf_element_exceeds_block_size() -> Result<(), anyhow::Error> {
+    common::setup();
+    let output_path = PathBuf::from("./target/results/element-exceeds-block-size.osm.pbf");
+    let mut options = WriterOptions::default();
+    options.with_max_block_size(1024);
+    let mut writer = Writer::from_options(output_path, FileInfo::default(), CompressionType::Zlib, options)?;
+    writer.write_header()?;
+    let way = Way::new(1, 1, 0, 1, 1, "user".to_string(), true, (1..1000).collect(), vec![]);
+    let result = writer.write_elements(vec![Element::Way { way }]);
+    assert!(result.expect_err("Expected block size error").to_string().contains("way 1"));
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::path::PathBuf;
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::reader::Reader;
+
+mod common;
+
+fn assert_same_element(expected: &Element, actual: &Element) {
+    match (expected, actual) {
+        (Element::Node { node: expected }, Element::Node { node: actual }) => {
+            assert_eq!(expected.id(), actual.id());
+            assert_eq!(expected.version(), actual.version());
+            assert_eq!(expected.coordinate(), actual.coordinate());
+            assert_eq!(expected.timestamp(), actual.timestamp());
+            assert_eq!(expected.changeset(), actual.changeset());
+            assert_eq!(expected.uid(), actual.uid());
+            assert_eq!(expected.user(), actual.user());
+            assert_eq!(expected.visible(), actual.visible());
+            assert_eq!(expected.tags(), actual.tags());
+        }
+        (Element::Way { way: expected }, Element::Way { way: actual }) => {
+            assert_eq!(expected.id(), actual.id());
+            assert_eq!(expected.version(), actual.version());
+            assert_eq!(expected.timestamp(), actual.timestamp());
+            assert_eq!(expected.changeset(), actual.changeset());
+            assert_eq!(expected.uid(), actual.uid());
+            assert_eq!(expected.user(), actual.user());
+            assert_eq!(expected.visible(), actual.visible());
+            assert_eq!(expected.refs(), actual.refs());
+            assert_eq!(expected.tags(), actual.tags());
+        }
+        (Element::Relation { relation: expected }, Element::Relation { relation: actual }) => {
+            assert_eq!(expected.id(), actual.id());
+            assert_eq!(expected.version(), actual.version());
+            assert_eq!(expected.timestamp(), actual.timestamp());
+            assert_eq!(expected.changeset(), actual.changeset());
+            assert_eq!(expected.uid(), actual.uid());
+            assert_eq!(expected.user(), actual.user());
+            assert_eq!(expected.visible(), actual.visible());
+            assert_eq!(expected.members(), actual.members());
+            assert_eq!(expected.tags(), actual.tags());
+        }
+        _ => {
+            panic!("Element type mismatch");
+        }
+    }
+}
+
+#[test]
+fn test_pbf_block_views() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    for input_path in [
+        PathBuf::from("./tests/fixtures/niue-230109.osm.pbf"),
+        PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf"),
+    ] {
+        let reader = Reader::new(&input_path)?;
+        let mut actual = Vec::new();
+        for block_view in reader.block_views()? {
+            actual.extend(block_view.nodes().map(|node| Element::Node { node: node.to_owned() }));
+            actual.extend(block_view.ways().map(|way| Element::Way { way: way.to_owned() }));
+            actual.extend(block_view.relations().map(|relation| Element::Relation { relation: relation.to_owned() }));
+        }
+        let expected = reader.elements()?.collect::<Vec<Element>>();
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual.iter()) {
+            assert_same_element(expected, actual);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pbf_block_views_filter() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let reader = Reader::new(&input_path)?;
+    let expected = reader.ways()?
+        .filter(|way| way.tags().iter().any(|tag| tag.k() == "highway"))
+        .map(|way| way.id())
+        .collect::<Vec<i64>>();
+    let mut actual = Vec::new();
+    for block_view in reader.block_views()? {
+        actual.extend(
+            block_view.ways()
+                .filter(|way| way.tags().find("highway").is_some())
+                .map(|way| way.id())
+        );
+    }
+    assert!(!expected.is_empty());
+    assert_eq!(expected, actual);
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::model::element_kind::ElementKind;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::reader::Reader;
+use osm_io::osm::pbf::writer::Writer;
+
+mod common;
+
+fn verify_elements_of(reader: &Reader) -> Result<(), anyhow::Error> {
+    for kinds in [
+        vec![],
+        vec![ElementKind::Node],
+        vec![ElementKind::Way],
+        vec![ElementKind::Relation],
+        vec![ElementKind::Node, ElementKind::Relation],
+        vec![ElementKind::Way, ElementKind::Relation],
+        ElementKind::ALL.to_vec(),
+    ] {
+        let expected = reader.elements()?
+            .filter(|e| e.kind().is_some_and(|kind| kinds.contains(&kind)))
+            .collect::<Vec<Element>>();
+        let actual = reader.elements_of(&kinds)?.collect::<Vec<Element>>();
+        assert_eq!(expected, actual, "kinds: {kinds:?}");
+    }
+    Ok(())
+}
+
+fn write_unsorted_copy(reader: &Reader, output_path: &Path) -> Result<(), anyhow::Error> {
+    let mut file_info = reader.info().clone();
+    file_info.with_optional_features(&[]);
+    let mut writer = Writer::from_file_info(output_path.to_path_buf(), file_info, CompressionType::Zlib)?;
+    writer.write_header()?;
+    for element in reader.elements()? {
+        writer.write_element(element)?;
+    }
+    writer.close()
+}
+
+#[test]
+fn test_pbf_elements_of() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let history_input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let unsorted_output_path = PathBuf::from("./target/results/unsorted-niue-230109.osm.pbf");
+
+    let reader = Reader::new(&input_path)?;
+    assert!(reader.info().optional("Sort.Type_then_ID"));
+    verify_elements_of(&reader)?;
+    verify_elements_of(&Reader::new(&history_input_path)?)?;
+
+    write_unsorted_copy(&reader, &unsorted_output_path)?;
+    let unsorted_reader = Reader::new(&unsorted_output_path)?;
+    assert!(!unsorted_reader.info().optional("Sort.Type_then_ID"));
+    verify_elements_of(&unsorted_reader)?;
+    Ok(())
+}
+
+#[test]
+fn test_pbf_typed_iterators() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+    let fixture_analysis = common::read_fixture_analysis(&fixture_analysis_path);
+    let reader = Reader::new(&input_path)?;
+
+    assert_eq!(reader.nodes()?.count() as i64, fixture_analysis["data"]["count"]["nodes"].as_i64().unwrap());
+    assert_eq!(reader.ways()?.count() as i64, fixture_analysis["data"]["count"]["ways"].as_i64().unwrap());
+    assert_eq!(reader.relations()?.count() as i64, fixture_analysis["data"]["count"]["relations"].as_i64().unwrap());
+
+    let expected_relation_ids = reader.elements()?
+        .filter_map(|e| {
+            match e {
+                Element::Relation { relation } => Some(relation.id()),
+                _ => None,
+            }
+        })
+        .collect::<Vec<i64>>();
+    let relation_ids = reader.relations()?.map(|r| r.id()).collect::<Vec<i64>>();
+    assert_eq!(expected_relation_ids, relation_ids);
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::reader::Reader;
+
+mod common;
+
+#[test]
+fn test_pbf_par_fold() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+    let fixture_analysis = common::read_fixture_analysis(&fixture_analysis_path);
+    let reader = Reader::new(&input_path)?;
+
+    let (nodes, ways, relations) = reader.count_objects()?;
+    assert_eq!(nodes, fixture_analysis["data"]["count"]["nodes"].as_i64().unwrap());
+    assert_eq!(ways, fixture_analysis["data"]["count"]["ways"].as_i64().unwrap());
+    assert_eq!(relations, fixture_analysis["data"]["count"]["relations"].as_i64().unwrap());
+
+    let tag_counts = reader.par_fold(
+        4,
+        HashMap::<String, usize>::new,
+        |mut tag_counts, element| {
+            if let Element::Way { way } = element {
+                for tag in way.tags() {
+                    *tag_counts.entry(tag.k().clone()).or_default() += 1;
+                }
+            }
+            tag_counts
+        },
+        |mut a, b| {
+            for (k, count) in b {
+                *a.entry(k).or_default() += count;
+            }
+            a
+        },
+    )?;
+
+    let mut expected = HashMap::<String, usize>::new();
+    for element in reader.elements()? {
+        if let Element::Way { way } = element {
+            for tag in way.tags() {
+                *expected.entry(tag.k().clone()).or_default() += 1;
+            }
+        }
+    }
+    assert!(!expected.is_empty());
+    assert_eq!(expected, tag_counts);
+    Ok(())
+}
+
+#[test]
+fn test_pbf_map_reduce_error() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let reader = Reader::new(&input_path)?;
+    let result = reader.map_reduce(
+        4,
+        |elements| {
+            match elements.iter().any(|e| e.is_relation()) {
+                true => {
+                    Err(anyhow!("relations are not supported"))
+                }
+                false => {
+                    Ok(elements.len())
+                }
+            }
+        },
+        |a, b| a + b,
+    );
+    assert!(result.is_err());
+    Ok(())
+}
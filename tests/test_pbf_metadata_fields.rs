@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::metadata_fields::MetadataFields;
+use osm_io::osm::pbf::reader::Reader;
+use osm_io::osm::pbf::writer::Writer;
+use osm_io::osm::pbf::writer_options::WriterOptions;
+
+mod common;
+
+fn write_with_metadata_fields(reader: &Reader, output_path: &Path, metadata_fields: MetadataFields) -> Result<(), anyhow::Error> {
+    let mut options = WriterOptions::default();
+    options.with_metadata_fields(metadata_fields);
+    let mut writer = Writer::from_options(
+        output_path.to_path_buf(),
+        reader.info().clone(),
+        CompressionType::Zlib,
+        options,
+    )?;
+    writer.write_header()?;
+    for element in reader.elements()? {
+        if !element.is_sentinel() {
+            writer.write_element(element)?;
+        }
+    }
+    writer.close()
+}
+
+// (id, version, changeset, user, timestamp)
+type Metadata = (i64, i32, i64, String, i64);
+
+fn metadata(element: &Element) -> Metadata {
+    match element {
+        Element::Node { node } => {
+            (node.id(), node.version(), node.changeset(), node.user().clone(), node.timestamp())
+        }
+        Element::Way { way } => {
+            (way.id(), way.version(), way.changeset(), way.user().clone(), way.timestamp())
+        }
+        Element::Relation { relation } => {
+            (relation.id(), relation.version(), relation.changeset(), relation.user().clone(), relation.timestamp())
+        }
+        Element::Sentinel => {
+            panic!("Unexpected sentinel");
+        }
+    }
+}
+
+fn read_metadata(reader: &Reader) -> Result<Vec<Metadata>, anyhow::Error> {
+    Ok(
+        reader.elements()?
+            .filter(|e| !e.is_sentinel())
+            .map(|e| metadata(&e))
+            .collect()
+    )
+}
+
+#[test]
+fn test_pbf_metadata_fields() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let no_metadata_output_path = PathBuf::from("./target/results/no-metadata-niue-230109.osm.pbf");
+    let version_output_path = PathBuf::from("./target/results/version-only-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+    let reader = Reader::new(&input_path)?;
+    let expected = read_metadata(&reader)?;
+
+    write_with_metadata_fields(&reader, &no_metadata_output_path, MetadataFields::none())?;
+    common::analyze_pbf_output(no_metadata_output_path.clone(), fixture_analysis_path);
+    assert!(std::fs::metadata(&no_metadata_output_path)?.len() < std::fs::metadata(&input_path)?.len());
+    let actual = read_metadata(&Reader::new(&no_metadata_output_path)?)?;
+    assert_eq!(expected.len(), actual.len());
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        assert_eq!(expected.0, actual.0);
+        assert_eq!(actual.1, 0);
+        assert_eq!(actual.2, -1);
+        assert!(actual.3.is_empty());
+        assert_eq!(actual.4, -1);
+    }
+
+    let mut version_only = MetadataFields::none();
+    version_only.with_version(true);
+    write_with_metadata_fields(&reader, &version_output_path, version_only)?;
+    let actual = read_metadata(&Reader::new(&version_output_path)?)?;
+    assert_eq!(expected.len(), actual.len());
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        assert_eq!(expected.0, actual.0);
+        assert_eq!(expected.1, actual.1);
+        assert_eq!(actual.2, -1);
+        assert!(actual.3.is_empty());
+        // a missing timestamp is -1 for every element type
+        assert_eq!(actual.4, -1);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pbf_reader_skip_metadata() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let expected = Reader::new(&input_path)?.elements()?.collect::<Vec<Element>>();
+    let mut reader = Reader::new(&input_path)?;
+    reader.with_skip_metadata(true);
+    let actual = reader.elements()?.collect::<Vec<Element>>();
+    assert_eq!(expected.len(), actual.len());
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        match (expected, actual) {
+            (Element::Node { node: expected }, Element::Node { node: actual }) => {
+                assert_eq!(expected.id(), actual.id());
+                assert_eq!(expected.coordinate().lat(), actual.coordinate().lat());
+                assert_eq!(expected.coordinate().lon(), actual.coordinate().lon());
+                assert_eq!(expected.tags().len(), actual.tags().len());
+                assert_eq!(actual.version(), 0);
+                assert_eq!(actual.timestamp(), -1);
+                assert!(actual.user().is_empty());
+            }
+            (Element::Way { way: expected }, Element::Way { way: actual }) => {
+                assert_eq!(expected.id(), actual.id());
+                assert_eq!(expected.refs(), actual.refs());
+                assert_eq!(actual.version(), 0);
+                assert_eq!(actual.timestamp(), -1);
+                assert!(actual.user().is_empty());
+            }
+            (Element::Relation { relation: expected }, Element::Relation { relation: actual }) => {
+                assert_eq!(expected.id(), actual.id());
+                assert_eq!(expected.members().len(), actual.members().len());
+                assert_eq!(actual.version(), 0);
+                assert_eq!(actual.timestamp(), -1);
+                assert!(actual.user().is_empty());
+            }
+            (Element::Sentinel, Element::Sentinel) => {}
+            _ => {
+                panic!("Element type mismatch");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::model::element_kind::ElementKind;
+use osm_io::osm::pbf::reader::Reader;
+
+mod common;
+
+#[test]
+fn test_pbf_mmap_reader() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    for input_path in [
+        PathBuf::from("./tests/fixtures/niue-230109.osm.pbf"),
+        PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf"),
+    ] {
+        let reader = Reader::new(&input_path)?;
+        let mmap_reader = Reader::new_mmap(&input_path)?;
+        assert_eq!(reader.info().required_features(), mmap_reader.info().required_features());
+
+        let expected = reader.elements()?.collect::<Vec<Element>>();
+        assert_eq!(expected, mmap_reader.elements()?.collect::<Vec<Element>>());
+        assert_eq!(expected, mmap_reader.par_elements(4)?.collect::<Vec<Element>>());
+        assert_eq!(
+            reader.elements_of(&[ElementKind::Way])?.collect::<Vec<Element>>(),
+            mmap_reader.elements_of(&[ElementKind::Way])?.collect::<Vec<Element>>()
+        );
+        assert_eq!(reader.count_objects()?, mmap_reader.count_objects()?);
+
+        let elements = Arc::new(AtomicI64::new(0));
+        let elements_clone = elements.clone();
+        mmap_reader.parallel_for_each(4, move |element| {
+            if !element.is_sentinel() {
+                elements.fetch_add(1, Ordering::SeqCst);
+            }
+            Ok(())
+        })?;
+        assert_eq!(expected.len() as i64, elements_clone.load(Ordering::SeqCst));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pbf_mmap_reader_missing_file() {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/missing.osm.pbf");
+    assert!(Reader::new_mmap(&input_path).is_err());
+}
@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::parallel_writer::ParallelWriter;
+use osm_io::osm::pbf::reader::Reader;
+use osm_io::osm::pbf::thread_local_accumulator::ThreadLocalAccumulator;
+
+mod common;
+
+#[test]
+fn test_pbf_multiple_parallel_writers() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let all_output_path = PathBuf::from("./target/results/multiple-writers-all-niue-230109.osm.pbf");
+    let nodes_output_path = PathBuf::from("./target/results/multiple-writers-nodes-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+
+    let reader = Reader::new(&input_path)?;
+    let mut writers = Vec::new();
+    for output_path in [&all_output_path, &nodes_output_path] {
+        let mut writer = ParallelWriter::from_file_info(
+            4 * 8000 * 32,
+            8000,
+            output_path.clone(),
+            reader.info().clone(),
+            CompressionType::Zlib,
+        )?;
+        writer.write_header()?;
+        writers.push(Arc::new(Mutex::new(writer)));
+    }
+    let all_writer = writers[0].clone();
+    let nodes_writer = writers[1].clone();
+
+    let all_acc = ThreadLocalAccumulator::new(8000);
+    let nodes_acc = ThreadLocalAccumulator::new(8000);
+    reader.parallel_for_each(4, move |element| {
+        if !element.is_sentinel() {
+            if element.is_node() {
+                nodes_acc.add(element.clone());
+            }
+            all_acc.add(element);
+        } else {
+            all_writer.lock().unwrap().write_elements(all_acc.elements())?;
+            nodes_writer.lock().unwrap().write_elements(nodes_acc.elements())?;
+        }
+        Ok(())
+    })?;
+
+    for writer in &writers {
+        writer.lock().unwrap().close()?;
+    }
+
+    common::analyze_pbf_output(all_output_path, fixture_analysis_path.clone());
+
+    let fixture_analysis = common::read_fixture_analysis(&fixture_analysis_path);
+    let (nodes, ways, relations) = Reader::new(&nodes_output_path)?.count_objects()?;
+    assert_eq!(nodes, fixture_analysis["data"]["count"]["nodes"].as_i64().unwrap());
+    assert_eq!(ways, 0);
+    assert_eq!(relations, 0);
+    let elements = Reader::new(&nodes_output_path)?.elements()?.collect::<Vec<Element>>();
+    assert!(elements.windows(2).all(|pair| pair[0] < pair[1]));
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::reader::Reader;
+
+mod common;
+
+/// Compare all fields, the [Element] equality compares only the type, id and version
+fn assert_same_element(expected: &Element, actual: &Element) {
+    match (expected, actual) {
+        (Element::Node { node: expected }, Element::Node { node: actual }) => {
+            assert_eq!(expected.id(), actual.id());
+            assert_eq!(expected.version(), actual.version());
+            assert_eq!(expected.visible(), actual.visible());
+            assert_eq!(expected.coordinate().lat7(), actual.coordinate().lat7());
+            assert_eq!(expected.coordinate().lon7(), actual.coordinate().lon7());
+            assert_eq!(expected.timestamp(), actual.timestamp());
+            assert_eq!(expected.changeset(), actual.changeset());
+            assert_eq!(expected.uid(), actual.uid());
+            assert_eq!(expected.user(), actual.user());
+            assert_eq!(expected.tags(), actual.tags());
+        }
+        (Element::Way { way: expected }, Element::Way { way: actual }) => {
+            assert_eq!(expected.id(), actual.id());
+            assert_eq!(expected.version(), actual.version());
+            assert_eq!(expected.visible(), actual.visible());
+            assert_eq!(expected.timestamp(), actual.timestamp());
+            assert_eq!(expected.changeset(), actual.changeset());
+            assert_eq!(expected.uid(), actual.uid());
+            assert_eq!(expected.user(), actual.user());
+            assert_eq!(expected.refs(), actual.refs());
+            assert_eq!(expected.tags(), actual.tags());
+        }
+        (Element::Relation { relation: expected }, Element::Relation { relation: actual }) => {
+            assert_eq!(expected.id(), actual.id());
+            assert_eq!(expected.version(), actual.version());
+            assert_eq!(expected.visible(), actual.visible());
+            assert_eq!(expected.timestamp(), actual.timestamp());
+            assert_eq!(expected.changeset(), actual.changeset());
+            assert_eq!(expected.uid(), actual.uid());
+            assert_eq!(expected.user(), actual.user());
+            assert_eq!(expected.members(), actual.members());
+            assert_eq!(expected.tags(), actual.tags());
+        }
+        (Element::Sentinel, Element::Sentinel) => {}
+        _ => {
+            panic!("Element type mismatch");
+        }
+    }
+}
+
+#[test]
+fn test_pbf_par_elements() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    for input_path in [
+        PathBuf::from("./tests/fixtures/niue-230109.osm.pbf"),
+        PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf"),
+    ] {
+        let reader = Reader::new(&input_path)?;
+        for tasks in [1, 4] {
+            let expected = reader.elements()?.collect::<Vec<Element>>();
+            let actual = reader.par_elements(tasks)?.collect::<Vec<Element>>();
+            assert_eq!(expected.len(), actual.len(), "tasks: {tasks}, path: {}", input_path.display());
+            for (expected, actual) in expected.iter().zip(actual.iter()) {
+                assert_same_element(expected, actual);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pbf_par_elements_early_drop() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let reader = Reader::new(&input_path)?;
+    let first = reader.par_elements(4)?.take(10).collect::<Vec<Element>>();
+    let expected = reader.elements()?.take(10).collect::<Vec<Element>>();
+    assert_eq!(first.len(), expected.len());
+    for (expected, actual) in expected.iter().zip(first.iter()) {
+        assert_same_element(expected, actual);
+    }
+    assert!(reader.par_elements(0).is_err());
+    Ok(())
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "apidb")]
+
+use std::path::PathBuf;
+
+use benchmark_rs::stopwatch::StopWatch;
+use postgres::{Client, NoTls};
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::apidb_dump::write::commit_policy::CommitPolicy;
+use osm_io::osm::apidb_dump::write::database_writer::DatabaseWriter;
+use osm_io::osm::apidb_dump::write::database_writer_options::DatabaseWriterOptions;
+use osm_io::osm::pbf::reader::Reader as PbfReader;
+
+mod common;
+
+fn count(client: &mut Client, query: &str) -> Result<i64, anyhow::Error> {
+    Ok(client.query_one(query, &[])?.get(0))
+}
+
+/// Requires a scratch database with the btree_gist extension available. The public schema of the
+/// database is recreated by the test. Skipped if OSM_IO_TEST_DATABASE_URL is not set
+#[test]
+fn test_pbf_reader_database_writer_pipe() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let database_url = match common::test_database_url() {
+        Some(database_url) => database_url,
+        None => {
+            log::warn!("OSM_IO_TEST_DATABASE_URL is not set, skipping");
+            return Ok(());
+        }
+    };
+    let input_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf.analysis.json");
+    let fixture_analysis = common::read_fixture_analysis(&fixture_analysis_path);
+
+    let mut stopwatch = StopWatch::new();
+    stopwatch.start();
+
+    let mut default_options = DatabaseWriterOptions::default();
+    default_options.with_commit_policy(CommitPolicy::PerTable);
+    let mut batch_options = DatabaseWriterOptions::default();
+    batch_options.with_commit_policy(CommitPolicy::EveryRows(1000));
+    batch_options.with_drop_indexes(true);
+    batch_options.with_buffer_size(4096);
+
+    for options in [default_options, batch_options] {
+        log::info!("Started pbf reader database writer pipeline test with {:?}, time: {}", options, stopwatch);
+        let mut client = Client::connect(&database_url, NoTls)?;
+        common::create_apidb_schema(&mut client)?;
+        let constraints_query = "SELECT count(*) FROM pg_constraint WHERE connamespace = 'public'::regnamespace";
+        let indexes_query = "SELECT count(*) FROM pg_indexes WHERE schemaname = 'public'";
+        let constraints = count(&mut client, constraints_query)?;
+        let indexes = count(&mut client, indexes_query)?;
+
+        let pbf_reader = PbfReader::new(&input_path)?;
+        let mut database_writer = DatabaseWriter::from_options(&database_url, options)?;
+        for element in pbf_reader.elements()? {
+            database_writer.write_element(element)?;
+        }
+        database_writer.close()?;
+
+        assert_eq!(count(&mut client, "SELECT count(*) FROM public.nodes")?, fixture_analysis["data"]["count"]["nodes"].as_i64().unwrap());
+        assert_eq!(count(&mut client, "SELECT count(*) FROM public.ways")?, fixture_analysis["data"]["count"]["ways"].as_i64().unwrap());
+        assert_eq!(count(&mut client, "SELECT count(*) FROM public.relations")?, fixture_analysis["data"]["count"]["relations"].as_i64().unwrap());
+        assert!(count(&mut client, "SELECT count(*) FROM public.current_nodes")? > 0);
+        assert!(count(&mut client, "SELECT count(*) FROM public.users")? > 0);
+        assert_eq!(count(&mut client, constraints_query)?, constraints);
+        assert_eq!(count(&mut client, indexes_query)?, indexes);
+    }
+
+    log::info!("Finished pbf reader database writer pipeline test, time: {}", stopwatch);
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::model::element::Element;
+use osm_io::osm::pbf::compression_type::CompressionType;
+use osm_io::osm::pbf::parallel_writer::ParallelWriter;
+use osm_io::osm::pbf::parallel_writer_options::ParallelWriterOptions;
+use osm_io::osm::pbf::reader::Reader;
+use osm_io::osm::pbf::writer::Writer;
+use osm_io::osm::pbf::writer_options::WriterOptions;
+
+mod common;
+
+fn write_with_options(reader: &Reader, output_path: &Path, options: WriterOptions) -> Result<(), anyhow::Error> {
+    let mut writer = Writer::from_options(
+        output_path.to_path_buf(),
+        reader.info().clone(),
+        CompressionType::Zlib,
+        options,
+    )?;
+    writer.write_header()?;
+    for element in reader.elements()? {
+        if !element.is_sentinel() {
+            writer.write_element(element)?;
+        }
+    }
+    writer.close()
+}
+
+fn compare_nodes(input_path: &Path, output_path: &Path, tolerance: f64, timestamp_granularity: i64) -> Result<(), anyhow::Error> {
+    let expected = Reader::new(input_path)?.elements()?.filter(|e| e.is_node()).collect::<Vec<Element>>();
+    let actual = Reader::new(output_path)?.elements()?.filter(|e| e.is_node()).collect::<Vec<Element>>();
+    assert_eq!(expected.len(), actual.len());
+    for (expected, actual) in expected.iter().zip(actual.iter()) {
+        match (expected, actual) {
+            (Element::Node { node: expected }, Element::Node { node: actual }) => {
+                assert_eq!(expected.id(), actual.id());
+                assert!((expected.coordinate().lat() - actual.coordinate().lat()).abs() <= tolerance);
+                assert!((expected.coordinate().lon() - actual.coordinate().lon()).abs() <= tolerance);
+                assert_eq!(expected.timestamp() / timestamp_granularity, actual.timestamp() / timestamp_granularity);
+            }
+            _ => {
+                panic!("Expected nodes");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_pbf_writer_options() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let coarse_output_path = PathBuf::from("./target/results/coarse-niue-230109.osm.pbf");
+    let offset_output_path = PathBuf::from("./target/results/offset-niue-230109.osm.pbf");
+    let fixture_analysis_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json");
+    let reader = Reader::new(&input_path)?;
+
+    let mut coarse_options = WriterOptions::default();
+    coarse_options.with_granularity(10000);
+    coarse_options.with_date_granularity(60000);
+    coarse_options.with_lat_offset(-19_000_000_123);
+    coarse_options.with_lon_offset(-169_000_000_456);
+    write_with_options(&reader, &coarse_output_path, coarse_options)?;
+    compare_nodes(&input_path, &coarse_output_path, 0.5E-5 + 1E-9, 60000)?;
+    assert!(std::fs::metadata(&coarse_output_path)?.len() < std::fs::metadata(&input_path)?.len());
+
+    let mut offset_options = WriterOptions::default();
+    offset_options.with_lat_offset(-19_000_000_000);
+    offset_options.with_lon_offset(-169_000_000_000);
+    assert!(offset_options.is_lossless());
+    write_with_options(&reader, &offset_output_path, offset_options)?;
+    compare_nodes(&input_path, &offset_output_path, 1E-9, 1)?;
+    common::analyze_pbf_output(offset_output_path, fixture_analysis_path);
+    Ok(())
+}
+
+#[test]
+fn test_pbf_parallel_writer_options() -> Result<(), anyhow::Error> {
+    common::setup();
+    let input_path = PathBuf::from("./tests/fixtures/niue-230109.osm.pbf");
+    let output_path = PathBuf::from("./target/results/parallel-coarse-niue-230109.osm.pbf");
+    let reader = Reader::new(&input_path)?;
+
+    let mut writer_options = WriterOptions::default();
+    writer_options.with_granularity(1000);
+    writer_options.with_lat_offset(500);
+    let mut options = ParallelWriterOptions::default();
+    options.with_writer_options(writer_options);
+    let mut writer = ParallelWriter::from_options(
+        output_path.clone(),
+        reader.info().clone(),
+        CompressionType::Zlib,
+        options,
+    )?;
+    writer.write_header()?;
+    for element in reader.elements()? {
+        if !element.is_sentinel() {
+            writer.write_element(element)?;
+        }
+    }
+    writer.close()?;
+    compare_nodes(&input_path, &output_path, 0.5E-6 + 1E-9, 1)?;
+
+    let mut invalid_options = WriterOptions::default();
+    invalid_options.with_granularity(-1);
+    assert!(
+        Writer::from_options(output_path, reader.info().clone(), CompressionType::Zlib, invalid_options).is_err()
+    );
+    Ok(())
+}
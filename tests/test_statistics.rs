@@ -0,0 +1,64 @@
+use std::path::PathBuf;
+
+use simple_logger::SimpleLogger;
+
+use osm_io::osm::pbf::reader::Reader;
+
+mod common;
+
+fn assert_statistics(input_path: PathBuf, fixture_analysis_path: PathBuf) -> Result<(), anyhow::Error> {
+    let fixture_analysis = common::read_fixture_analysis(&fixture_analysis_path);
+    let reader = Reader::new(&input_path)?;
+    let statistics = reader.statistics(4, 10)?;
+    let statistics_json = statistics.to_json();
+
+    for section in ["count", "minid", "maxid"] {
+        for variant in ["nodes", "ways", "relations"] {
+            assert_eq!(
+                statistics_json[section][variant],
+                fixture_analysis["data"][section][variant],
+                "{section}.{variant}"
+            );
+        }
+    }
+    assert_eq!(statistics_json["timestamp"], fixture_analysis["data"]["timestamp"]);
+    for i in 0..4 {
+        let expected = fixture_analysis["data"]["bbox"][i].as_f64().unwrap();
+        let actual = statistics_json["bbox"][i].as_f64().unwrap();
+        assert!((expected - actual).abs() < 1e-7, "bbox[{i}]: {actual} != {expected}");
+    }
+
+    assert!(statistics.changesets() > 0);
+    assert!(statistics.users() > 0);
+    assert!(statistics.max_way_nodes() > 0);
+    assert!(statistics.max_relation_members() > 0);
+
+    let top_keys = statistics.top_keys();
+    assert_eq!(top_keys.len(), 10);
+    assert!(top_keys.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+    let top_tags = statistics.top_tags();
+    assert_eq!(top_tags.len(), 10);
+    assert!(top_tags.windows(2).all(|pair| pair[0].2 >= pair[1].2));
+    assert!(top_tags[0].2 <= top_keys[0].1);
+    assert_eq!(statistics_json["top_keys"][0]["key"].as_str().unwrap(), top_keys[0].0);
+    Ok(())
+}
+
+#[test]
+fn test_statistics() -> Result<(), anyhow::Error> {
+    SimpleLogger::new().init().unwrap();
+    common::setup();
+    assert_statistics(
+        PathBuf::from("./tests/fixtures/niue-230109.osm.pbf"),
+        PathBuf::from("./tests/fixtures/niue-230109.osm.pbf.analysis.json"),
+    )
+}
+
+#[test]
+fn test_history_statistics() -> Result<(), anyhow::Error> {
+    common::setup();
+    assert_statistics(
+        PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf"),
+        PathBuf::from("./tests/fixtures/history-niue-230109.osm.pbf.analysis.json"),
+    )
+}